@@ -1,5 +1,6 @@
 // Abstract Syntax Tree definitions
 
+use crate::lexer::TemplatePart;
 use crate::types::Span;
 
 /// Binary operators
@@ -10,8 +11,104 @@ pub enum BinOp {
     Mul,
     Div,
     Equal,
+    NotEqual,
+    StrictEqual,
+    StrictNotEqual,
     Less,
+    LessEqual,
     Greater,
+    GreaterEqual,
+}
+
+/// Unary operators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    /// `-x`
+    Neg,
+    /// `+x`
+    Plus,
+    /// `!x`
+    Not,
+}
+
+/// Short-circuiting logical operators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalOp {
+    /// `a && b`
+    And,
+    /// `a || b`
+    Or,
+    /// `a ?? b`
+    Nullish,
+}
+
+/// Assignment operators (`=` and the compound forms)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssignOp {
+    Assign,
+    AddAssign,
+    SubAssign,
+    MulAssign,
+    DivAssign,
+}
+
+impl AssignOp {
+    /// The binary operator a compound assignment applies, if any
+    pub fn binary_op(self) -> Option<BinOp> {
+        match self {
+            AssignOp::Assign => None,
+            AssignOp::AddAssign => Some(BinOp::Add),
+            AssignOp::SubAssign => Some(BinOp::Sub),
+            AssignOp::MulAssign => Some(BinOp::Mul),
+            AssignOp::DivAssign => Some(BinOp::Div),
+        }
+    }
+}
+
+/// Increment/decrement operators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateOp {
+    Increment,
+    Decrement,
+}
+
+/// Kind of variable declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclKind {
+    /// Function-scoped `var`
+    Var,
+    /// `let`, unreadable before its declaration runs
+    Let,
+    /// `const`, like `let` but never reassigned
+    Const,
+}
+
+impl DeclKind {
+    /// Whether the binding is in its temporal dead zone until the declaration runs
+    pub fn is_lexical(self) -> bool {
+        matches!(self, DeclKind::Let | DeclKind::Const)
+    }
+}
+
+/// A function parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /// Value used when the argument is missing or `undefined`
+    pub default: Option<ASTNode>,
+    /// Whether this is a rest parameter (`...name`), collecting the remaining arguments
+    pub rest: bool,
+}
+
+impl Param {
+    /// A plain parameter with no default value
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default: None,
+            rest: false,
+        }
+    }
 }
 
 /// AST Node types
@@ -23,15 +120,33 @@ pub enum ASTNode {
     /// Function declaration
     FunctionDecl {
         name: String,
-        params: Vec<String>,
+        params: Vec<Param>,
+        body: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Function expression: `function name?(params) { body }`; the name is
+    /// only visible inside the function
+    FunctionExpr {
+        name: Option<String>,
+        params: Vec<Param>,
+        body: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Arrow function: `(params) => body`, where the body is a block or a
+    /// single expression whose value is returned
+    ArrowFunction {
+        params: Vec<Param>,
         body: Box<ASTNode>,
         span: Span,
     },
     
-    /// Let variable declaration
-    LetDecl {
+    /// Variable declaration: `var`/`let`/`const name [= init]`
+    VarDecl {
+        kind: DeclKind,
         name: String,
-        init: Box<ASTNode>,
+        init: Option<Box<ASTNode>>,
         span: Span,
     },
     
@@ -44,6 +159,39 @@ pub enum ASTNode {
         span: Span,
     },
     
+    /// While loop statement
+    WhileStmt {
+        cond: Box<ASTNode>,
+        body: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Do-while loop statement
+    DoWhileStmt {
+        body: Box<ASTNode>,
+        cond: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Break statement, optionally targeting a label
+    BreakStmt {
+        label: Option<String>,
+        span: Span,
+    },
+    
+    /// Continue statement, optionally targeting a loop label
+    ContinueStmt {
+        label: Option<String>,
+        span: Span,
+    },
+    
+    /// Labelled statement: `label: body`
+    LabeledStmt {
+        label: String,
+        body: Box<ASTNode>,
+        span: Span,
+    },
+    
     /// If statement
     IfStmt {
         cond: Box<ASTNode>,
@@ -52,12 +200,29 @@ pub enum ASTNode {
         span: Span,
     },
     
-    /// Return statement
+    /// Return statement; `return;` has no value
     ReturnStmt {
+        value: Option<Box<ASTNode>>,
+        span: Span,
+    },
+    
+    /// Throw statement
+    ThrowStmt {
         value: Box<ASTNode>,
         span: Span,
     },
     
+    /// Try statement with a `catch` clause, a `finally` block, or both.
+    ///
+    /// `param` is the catch clause's binding, which may be omitted.
+    TryStmt {
+        block: Box<ASTNode>,
+        param: Option<String>,
+        handler: Option<Box<ASTNode>>,
+        finalizer: Option<Box<ASTNode>>,
+        span: Span,
+    },
+    
     /// Block statement (multiple statements)
     BlockStmt {
         statements: Vec<ASTNode>,
@@ -72,6 +237,59 @@ pub enum ASTNode {
         span: Span,
     },
     
+    /// Unary expression: `-x`, `+x`, `!x`
+    UnaryExpr {
+        op: UnaryOp,
+        operand: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Logical expression; the right side is only evaluated when needed
+    LogicalExpr {
+        op: LogicalOp,
+        left: Box<ASTNode>,
+        right: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Assignment expression: `target = value`, `target += value`, ...
+    AssignExpr {
+        op: AssignOp,
+        target: Box<ASTNode>,
+        value: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Increment/decrement expression: `++x`, `x--`, ...
+    UpdateExpr {
+        op: UpdateOp,
+        prefix: bool,
+        target: Box<ASTNode>,
+        span: Span,
+    },
+    
+    /// Property access: `object.name` or `object[expr]`.
+    ///
+    /// For the dot form the property is a string literal holding the name.
+    MemberExpr {
+        object: Box<ASTNode>,
+        property: Box<ASTNode>,
+        computed: bool,
+        span: Span,
+    },
+    
+    /// Object literal: `{ key: value, ... }`
+    ObjectLiteral {
+        properties: Vec<(String, ASTNode)>,
+        span: Span,
+    },
+    
+    /// Array literal: `[expr, ...]`
+    ArrayLiteral {
+        elements: Vec<ASTNode>,
+        span: Span,
+    },
+    
     /// Function call expression
     CallExpr {
         callee: Box<ASTNode>,
@@ -79,6 +297,12 @@ pub enum ASTNode {
         span: Span,
     },
     
+    /// Spread argument of a call: `...iterable`
+    Spread {
+        argument: Box<ASTNode>,
+        span: Span,
+    },
+    
     /// Identifier
     Identifier {
         name: String,
@@ -90,6 +314,43 @@ pub enum ASTNode {
         value: f64,
         span: Span,
     },
+    
+    /// Template literal: `quasis[0]${expressions[0]}quasis[1]...`
+    TemplateLiteral {
+        quasis: Vec<TemplatePart>,
+        expressions: Vec<ASTNode>,
+        span: Span,
+    },
+    
+    /// Tagged template: `` tag`...` `` calls `tag(strings, ...expressions)`
+    TaggedTemplate {
+        tag: Box<ASTNode>,
+        quasis: Vec<TemplatePart>,
+        expressions: Vec<ASTNode>,
+        span: Span,
+    },
+    
+    /// String literal (escape sequences already decoded)
+    StringLiteral {
+        value: String,
+        span: Span,
+    },
+    
+    /// Boolean literal (`true` / `false`)
+    BooleanLiteral {
+        value: bool,
+        span: Span,
+    },
+    
+    /// `null` literal
+    NullLiteral {
+        span: Span,
+    },
+    
+    /// Placeholder for a statement that failed to parse, left by error recovery
+    Error {
+        span: Span,
+    },
 }
 
 impl ASTNode {
@@ -98,17 +359,181 @@ impl ASTNode {
         match self {
             ASTNode::Program(_) => Span::new(0, 0),
             ASTNode::FunctionDecl { span, .. } => *span,
-            ASTNode::LetDecl { span, .. } => *span,
+            ASTNode::FunctionExpr { span, .. } => *span,
+            ASTNode::ArrowFunction { span, .. } => *span,
+            ASTNode::VarDecl { span, .. } => *span,
             ASTNode::ForStmt { span, .. } => *span,
+            ASTNode::WhileStmt { span, .. } => *span,
+            ASTNode::DoWhileStmt { span, .. } => *span,
+            ASTNode::BreakStmt { span, .. } => *span,
+            ASTNode::ContinueStmt { span, .. } => *span,
+            ASTNode::LabeledStmt { span, .. } => *span,
             ASTNode::IfStmt { span, .. } => *span,
             ASTNode::ReturnStmt { span, .. } => *span,
+            ASTNode::ThrowStmt { span, .. } => *span,
+            ASTNode::TryStmt { span, .. } => *span,
             ASTNode::BlockStmt { span, .. } => *span,
             ASTNode::BinaryExpr { span, .. } => *span,
+            ASTNode::UnaryExpr { span, .. } => *span,
+            ASTNode::LogicalExpr { span, .. } => *span,
+            ASTNode::AssignExpr { span, .. } => *span,
+            ASTNode::UpdateExpr { span, .. } => *span,
+            ASTNode::MemberExpr { span, .. } => *span,
+            ASTNode::ObjectLiteral { span, .. } => *span,
+            ASTNode::ArrayLiteral { span, .. } => *span,
             ASTNode::CallExpr { span, .. } => *span,
+            ASTNode::Spread { span, .. } => *span,
             ASTNode::Identifier { span, .. } => *span,
             ASTNode::NumberLiteral { span, .. } => *span,
+            ASTNode::StringLiteral { span, .. } => *span,
+            ASTNode::TemplateLiteral { span, .. } => *span,
+            ASTNode::TaggedTemplate { span, .. } => *span,
+            ASTNode::BooleanLiteral { span, .. } => *span,
+            ASTNode::NullLiteral { span } => *span,
+            ASTNode::Error { span } => *span,
         }
     }
+    
+    /// Call `f` on each direct child node, in source order
+    pub fn for_each_child<'a>(&'a self, mut f: impl FnMut(&'a ASTNode)) {
+        match self {
+            ASTNode::Program(stmts) | ASTNode::BlockStmt { statements: stmts, .. } => {
+                stmts.iter().for_each(f);
+            }
+            ASTNode::FunctionDecl { params, body, .. }
+            | ASTNode::FunctionExpr { params, body, .. }
+            | ASTNode::ArrowFunction { params, body, .. } => {
+                params.iter().filter_map(|param| param.default.as_ref()).for_each(&mut f);
+                f(body);
+            }
+            ASTNode::VarDecl { init, .. } => {
+                if let Some(init) = init {
+                    f(init);
+                }
+            }
+            ASTNode::ForStmt { init, cond, update, body, .. } => {
+                f(init);
+                f(cond);
+                f(update);
+                f(body);
+            }
+            ASTNode::WhileStmt { cond, body, .. } => {
+                f(cond);
+                f(body);
+            }
+            ASTNode::DoWhileStmt { body, cond, .. } => {
+                f(body);
+                f(cond);
+            }
+            ASTNode::LabeledStmt { body, .. } => f(body),
+            ASTNode::IfStmt { cond, then_branch, else_branch, .. } => {
+                f(cond);
+                f(then_branch);
+                if let Some(else_branch) = else_branch {
+                    f(else_branch);
+                }
+            }
+            ASTNode::ReturnStmt { value, .. } => {
+                if let Some(value) = value {
+                    f(value);
+                }
+            }
+            ASTNode::ThrowStmt { value, .. } => f(value),
+            ASTNode::TryStmt { block, handler, finalizer, .. } => {
+                f(block);
+                if let Some(handler) = handler {
+                    f(handler);
+                }
+                if let Some(finalizer) = finalizer {
+                    f(finalizer);
+                }
+            }
+            ASTNode::BinaryExpr { left, right, .. } | ASTNode::LogicalExpr { left, right, .. } => {
+                f(left);
+                f(right);
+            }
+            ASTNode::UnaryExpr { operand, .. } => f(operand),
+            ASTNode::AssignExpr { target, value, .. } => {
+                f(target);
+                f(value);
+            }
+            ASTNode::UpdateExpr { target, .. } => f(target),
+            ASTNode::MemberExpr { object, property, .. } => {
+                f(object);
+                f(property);
+            }
+            ASTNode::ObjectLiteral { properties, .. } => {
+                properties.iter().for_each(|(_, value)| f(value));
+            }
+            ASTNode::ArrayLiteral { elements, .. } => elements.iter().for_each(f),
+            ASTNode::CallExpr { callee, args, .. } => {
+                f(callee);
+                args.iter().for_each(f);
+            }
+            ASTNode::Spread { argument, .. } => f(argument),
+            ASTNode::TemplateLiteral { expressions, .. } => expressions.iter().for_each(f),
+            ASTNode::TaggedTemplate { tag, expressions, .. } => {
+                f(tag);
+                expressions.iter().for_each(f);
+            }
+            ASTNode::BreakStmt { .. }
+            | ASTNode::ContinueStmt { .. }
+            | ASTNode::Identifier { .. }
+            | ASTNode::NumberLiteral { .. }
+            | ASTNode::StringLiteral { .. }
+            | ASTNode::BooleanLiteral { .. }
+            | ASTNode::NullLiteral { .. }
+            | ASTNode::Error { .. } => {}
+        }
+    }
+    
+    /// Whether this node is a function declaration, which is hoisted out of its statement list
+    pub fn is_function_decl(&self) -> bool {
+        matches!(self, ASTNode::FunctionDecl { .. })
+    }
+    
+    /// Whether this node is a `let` or `const` declaration, which is scoped to its block
+    pub fn is_lexical_decl(&self) -> bool {
+        matches!(self, ASTNode::VarDecl { kind, .. } if kind.is_lexical())
+    }
+    
+    /// Whether this node is an iteration statement (a valid `continue` target)
+    pub fn is_loop(&self) -> bool {
+        matches!(
+            self,
+            ASTNode::ForStmt { .. } | ASTNode::WhileStmt { .. } | ASTNode::DoWhileStmt { .. }
+        )
+    }
+    
+    /// Whether this node is an expression (as opposed to a declaration or statement)
+    /// Whether this is a function expression or an arrow function
+    pub fn is_function_expr(&self) -> bool {
+        matches!(self, ASTNode::FunctionExpr { .. } | ASTNode::ArrowFunction { .. })
+    }
+    
+    pub fn is_expression(&self) -> bool {
+        matches!(
+            self,
+            ASTNode::BinaryExpr { .. }
+                | ASTNode::UnaryExpr { .. }
+                | ASTNode::LogicalExpr { .. }
+                | ASTNode::AssignExpr { .. }
+                | ASTNode::UpdateExpr { .. }
+                | ASTNode::MemberExpr { .. }
+                | ASTNode::ObjectLiteral { .. }
+                | ASTNode::ArrayLiteral { .. }
+                | ASTNode::CallExpr { .. }
+                | ASTNode::FunctionExpr { .. }
+                | ASTNode::ArrowFunction { .. }
+                | ASTNode::Identifier { .. }
+                | ASTNode::NumberLiteral { .. }
+                | ASTNode::StringLiteral { .. }
+                | ASTNode::TemplateLiteral { .. }
+                | ASTNode::TaggedTemplate { .. }
+                | ASTNode::BooleanLiteral { .. }
+                | ASTNode::NullLiteral { .. }
+        )
+    }
 }
 
 /// Complete AST with root node
@@ -136,6 +561,15 @@ mod tests {
         assert_eq!(node.span(), Span::new(0, 2));
     }
     
+    #[test]
+    fn test_string_literal() {
+        let node = ASTNode::StringLiteral {
+            value: "hi".to_string(),
+            span: Span::new(0, 4),
+        };
+        assert_eq!(node.span(), Span::new(0, 4));
+    }
+    
     #[test]
     fn test_identifier() {
         let node = ASTNode::Identifier {
@@ -173,9 +607,10 @@ mod tests {
             span: Span::new(8, 10),
         });
         
-        let node = ASTNode::LetDecl {
+        let node = ASTNode::VarDecl {
+            kind: DeclKind::Let,
             name: "x".to_string(),
-            init,
+            init: Some(init),
             span: Span::new(0, 10),
         };
         
@@ -191,7 +626,7 @@ mod tests {
         
         let node = ASTNode::FunctionDecl {
             name: "foo".to_string(),
-            params: vec!["a".to_string(), "b".to_string()],
+            params: vec![Param::new("a"), Param::new("b")],
             body,
             span: Span::new(0, 22),
         };
@@ -199,6 +634,20 @@ mod tests {
         assert_eq!(node.span(), Span::new(0, 22));
     }
     
+    #[test]
+    fn test_for_each_child() {
+        let node = ASTNode::BinaryExpr {
+            op: BinOp::Add,
+            left: Box::new(ASTNode::Identifier { name: "a".to_string(), span: Span::new(0, 1) }),
+            right: Box::new(ASTNode::NumberLiteral { value: 1.0, span: Span::new(4, 5) }),
+            span: Span::new(0, 5),
+        };
+        
+        let mut spans = Vec::new();
+        node.for_each_child(|child| spans.push(child.span()));
+        assert_eq!(spans, vec![Span::new(0, 1), Span::new(4, 5)]);
+    }
+    
     #[test]
     fn test_ast_creation() {
         let root = ASTNode::Program(vec![
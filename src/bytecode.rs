@@ -1,6 +1,7 @@
 // Bytecode definitions and generation
 
-use crate::types::Value;
+use crate::types::{FunctionId, Span, Value};
+use std::rc::Rc;
 
 /// Bytecode instructions
 #[derive(Debug, Clone, PartialEq)]
@@ -11,6 +12,46 @@ pub enum Instruction {
     LoadLocal(usize),
     /// Store to a local variable
     StoreLocal(usize),
+    /// Load a top-level (script) variable from inside a function
+    LoadGlobal(usize),
+    /// Store to a top-level (script) variable from inside a function
+    StoreGlobal(usize),
+    /// Look up a global by the name in this constant, throwing a ReferenceError
+    /// for the source span if no script declared it
+    LoadGlobalName(usize, Span),
+    /// Store to a global looked up by the name in this constant
+    StoreGlobalName(usize, Span),
+    /// Load a variable held in the frame's upvalue cell at this index
+    LoadUpvalue(usize),
+    /// Store to the frame's upvalue cell at this index
+    StoreUpvalue(usize),
+    /// Replace the frame's upvalue cell at this index with a new cell holding a copy
+    /// of its value, so closures made from now on do not share the old one
+    FreshCell(usize),
+    /// Push the marker held by a `let`/`const` binding before its declaration runs
+    LoadHole,
+    /// Throw a ReferenceError if the top of stack is the hole; the operand is the
+    /// constant holding the variable's name
+    CheckInitialized(usize),
+    /// Throw a TypeError for assigning to the `const` named by this constant
+    ThrowConstAssignment(usize),
+    /// Pop a value and throw it, unwinding to the nearest exception handler
+    Throw,
+    /// Pop the exception a `finally` block was entered with and throw the
+    /// original error again, keeping its location and stack trace
+    Rethrow,
+    /// Create a closure of a function, capturing cells from the current frame
+    MakeClosure(FunctionId),
+    /// Create an object from N key/value pairs on the stack
+    CreateObject(usize),
+    /// Create an array from the top N values on the stack
+    CreateArray(usize),
+    /// Pop an array or string and append its elements to the array below it
+    AppendSpread,
+    /// Pop a key and an object, push the object's property
+    GetProperty,
+    /// Pop a value, a key and an object, set the property and push the value
+    SetProperty,
     /// Add two values
     Add,
     /// Subtract two values
@@ -19,16 +60,177 @@ pub enum Instruction {
     Mul,
     /// Divide two values
     Div,
+    /// Loose equality (`==`)
+    Equal,
+    /// Loose inequality (`!=`)
+    NotEqual,
+    /// Strict equality (`===`)
+    StrictEqual,
+    /// Strict inequality (`!==`)
+    StrictNotEqual,
+    /// Less than (`<`)
+    Less,
+    /// Less than or equal (`<=`)
+    LessEqual,
+    /// Greater than (`>`)
+    Greater,
+    /// Greater than or equal (`>=`)
+    GreaterEqual,
+    /// Discard the top value on the stack
+    Pop,
+    /// Duplicate the top value on the stack
+    Dup,
+    /// Duplicate the top two values on the stack, keeping their order
+    Dup2,
+    /// Move the top value below the N values beneath it
+    MoveUnder(usize),
+    /// Convert the top value on the stack to a number
+    ToNumber,
+    /// Negate the top value on the stack as a number (`-x`)
+    Negate,
+    /// Replace the top value on the stack with its logical negation (`!x`)
+    Not,
     /// Print the top value on the stack (for console.log/print)
     Print,
-    /// Call a function with N arguments
+    /// Call a function with N arguments (callee below the arguments on the stack)
     Call(usize),
+    /// Call a method with N arguments (receiver and key below the arguments)
+    CallMethod(usize),
+    /// Call a function with the arguments in an array (callee below the array)
+    CallWithSpread,
+    /// Call a method with the arguments in an array (receiver and key below the array)
+    CallMethodWithSpread,
     /// Return from function
     Return,
     /// Unconditional jump
     Jump(isize),
-    /// Jump if top of stack is false
+    /// Pop the top of stack and jump if it is falsy
     JumpIfFalse(isize),
+    /// Jump if the top of stack is truthy, keeping it; otherwise pop it (`||`)
+    JumpIfTrueOrPop(isize),
+    /// Jump if the top of stack is falsy, keeping it; otherwise pop it (`&&`)
+    JumpIfFalseOrPop(isize),
+    /// Jump if the top of stack is not null/undefined, keeping it; otherwise pop it (`??`)
+    JumpIfNotNullishOrPop(isize),
+}
+
+/// An entry in a chunk's exception handler table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionHandler {
+    /// First instruction covered by the handler
+    pub start: usize,
+    /// Instruction just past the covered range
+    pub end: usize,
+    /// Where execution resumes, with the thrown value pushed
+    pub target: usize,
+    /// Operand stack depth of the frame to restore before pushing the value
+    pub stack_depth: usize,
+}
+
+/// Source positions of a chunk's instructions, in a compact encoding.
+///
+/// Each entry gives the span of the instructions from its index up to the
+/// next entry's. Entries are stored as variable-length deltas from the one
+/// before, so a table usually takes a few bytes per statement.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionTable {
+    bytes: Vec<u8>,
+    /// Last encoded entry, which the next one is a delta from
+    last_encoded: Option<(usize, Span)>,
+    /// Newest entry, held back until an entry for a later instruction arrives
+    pending: Option<(usize, Span)>,
+}
+
+impl PositionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Attribute the instructions from `index` on to `span`. Indices must not
+    /// decrease; a second entry for the same index replaces the first.
+    pub fn add(&mut self, index: usize, span: Span) {
+        if self.pending.is_some_and(|(pending, _)| pending == index) {
+            self.pending = None;
+        }
+        let current = self.pending.or(self.last_encoded).map(|(_, span)| span);
+        if current == Some(span) {
+            return;
+        }
+        if let Some(entry) = self.pending.replace((index, span)) {
+            self.encode(entry);
+        }
+    }
+    
+    /// The span of the instruction at `index`
+    pub fn lookup(&self, index: usize) -> Option<Span> {
+        self.iter()
+            .take_while(|&(start, _)| start <= index)
+            .last()
+            .map(|(_, span)| span)
+    }
+    
+    /// The entries as (first instruction index, span) pairs, in order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Span)> + '_ {
+        let mut pos = 0;
+        let mut last = (0, Span::new(0, 0));
+        let decoded = std::iter::from_fn(move || {
+            if pos >= self.bytes.len() {
+                return None;
+            }
+            let index = last.0 + read_varint(&self.bytes, &mut pos) as usize;
+            let start = (last.1.start as i64 + unzigzag(read_varint(&self.bytes, &mut pos))) as usize;
+            let end = start + read_varint(&self.bytes, &mut pos) as usize;
+            last = (index, Span::new(start, end));
+            Some(last)
+        });
+        decoded.chain(self.pending)
+    }
+    
+    /// Number of bytes used by the encoded entries
+    pub fn encoded_size(&self) -> usize {
+        self.bytes.len()
+    }
+    
+    fn encode(&mut self, (index, span): (usize, Span)) {
+        let (last_index, last_span) = self.last_encoded.unwrap_or((0, Span::new(0, 0)));
+        write_varint(&mut self.bytes, (index - last_index) as u64);
+        write_varint(&mut self.bytes, zigzag(span.start as i64 - last_span.start as i64));
+        write_varint(&mut self.bytes, span.end.saturating_sub(span.start) as u64);
+        self.last_encoded = Some((index, span));
+    }
+}
+
+/// Append `value` seven bits at a time, low bits first
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Read a value written by `write_varint`, advancing `pos`
+fn read_varint(bytes: &[u8], pos: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(&byte) = bytes.get(*pos) {
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+/// Map signed deltas to unsigned ones, keeping small magnitudes small
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
 }
 
 /// A chunk of bytecode with constants and metadata
@@ -37,6 +239,10 @@ pub struct BytecodeChunk {
     pub instructions: Vec<Instruction>,
     pub constants: Vec<Value>,
     pub local_count: usize,
+    /// Exception handlers, innermost first
+    pub handlers: Vec<ExceptionHandler>,
+    /// Source span of each instruction
+    pub positions: PositionTable,
 }
 
 impl BytecodeChunk {
@@ -45,6 +251,8 @@ impl BytecodeChunk {
             instructions: Vec::new(),
             constants: Vec::new(),
             local_count: 0,
+            handlers: Vec::new(),
+            positions: PositionTable::new(),
         }
     }
     
@@ -59,6 +267,27 @@ impl BytecodeChunk {
         self.constants.len() - 1
     }
     
+    /// Register an exception handler; inner handlers must be added before the
+    /// handlers enclosing them
+    pub fn add_handler(&mut self, handler: ExceptionHandler) {
+        self.handlers.push(handler);
+    }
+    
+    /// The innermost handler covering the instruction at `ip`
+    pub fn handler_for(&self, ip: usize) -> Option<&ExceptionHandler> {
+        self.handlers.iter().find(|handler| handler.start <= ip && ip < handler.end)
+    }
+    
+    /// Attribute the instructions emitted from now on to `span`
+    pub fn add_position(&mut self, span: Span) {
+        self.positions.add(self.instructions.len(), span);
+    }
+    
+    /// The source span of the instruction at `index`
+    pub fn position_at(&self, index: usize) -> Option<Span> {
+        self.positions.lookup(index)
+    }
+    
     /// Set the number of local variables
     pub fn set_local_count(&mut self, count: usize) {
         self.local_count = count;
@@ -71,6 +300,61 @@ impl Default for BytecodeChunk {
     }
 }
 
+/// A compiled user-defined function
+#[derive(Debug, Clone)]
+pub struct FunctionInfo {
+    pub id: FunctionId,
+    pub name: String,
+    /// Number of declared parameters, not counting a rest parameter;
+    /// arguments occupy the first locals
+    pub arity: usize,
+    /// Whether a rest parameter, in the local after the others, collects the remaining arguments
+    pub rest: bool,
+    /// Whether the local after the parameters holds the `arguments` object
+    pub arguments: bool,
+    /// Shared so that call frames do not copy the bytecode
+    pub chunk: Rc<BytecodeChunk>,
+    /// Upvalue indices of the creating frame whose cells a new closure captures
+    pub captures: Vec<usize>,
+    /// Number of fresh cells for the function's own captured variables,
+    /// which follow the captured cells in a frame's upvalues
+    pub cell_count: usize,
+}
+
+impl FunctionInfo {
+    pub fn new(id: FunctionId, name: String, arity: usize, chunk: BytecodeChunk) -> Self {
+        Self {
+            id,
+            name,
+            arity,
+            rest: false,
+            arguments: false,
+            chunk: Rc::new(chunk),
+            captures: Vec::new(),
+            cell_count: 0,
+        }
+    }
+    
+    /// Set the closure layout computed by scope analysis
+    pub fn with_upvalues(mut self, captures: Vec<usize>, cell_count: usize) -> Self {
+        self.captures = captures;
+        self.cell_count = cell_count;
+        self
+    }
+    
+    /// Set whether the function has a rest parameter
+    pub fn with_rest(mut self, rest: bool) -> Self {
+        self.rest = rest;
+        self
+    }
+    
+    /// Set whether the function uses its `arguments` object
+    pub fn with_arguments(mut self, arguments: bool) -> Self {
+        self.arguments = arguments;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,6 +379,7 @@ mod tests {
     }
     
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_add_constant() {
         let mut chunk = BytecodeChunk::new();
         let idx1 = chunk.add_constant(Value::Number(42.0));
@@ -112,6 +397,54 @@ mod tests {
         assert_eq!(chunk.local_count, 5);
     }
     
+    #[test]
+    fn test_position_at() {
+        let mut chunk = BytecodeChunk::new();
+        assert_eq!(chunk.position_at(0), None);
+        
+        chunk.add_position(Span::new(0, 10));
+        chunk.emit(Instruction::LoadConst(0));
+        chunk.add_position(Span::new(4, 5));
+        // Replaced before anything is emitted for it
+        chunk.add_position(Span::new(6, 7));
+        chunk.emit(Instruction::LoadConst(1));
+        chunk.add_position(Span::new(0, 10));
+        chunk.emit(Instruction::Add);
+        chunk.add_position(Span::new(0, 10));
+        chunk.emit(Instruction::Return);
+        
+        let entries: Vec<_> = chunk.positions.iter().collect();
+        assert_eq!(entries, [(0, Span::new(0, 10)), (1, Span::new(6, 7)), (2, Span::new(0, 10))]);
+        assert_eq!(chunk.position_at(1), Some(Span::new(6, 7)));
+        assert_eq!(chunk.position_at(3), Some(Span::new(0, 10)));
+    }
+    
+    #[test]
+    fn test_position_table_round_trip() {
+        let spans = [(0, Span::new(5000, 5040)), (3, Span::new(12, 12)), (200, Span::new(70000, 70300)), (201, Span::new(1, 2))];
+        let mut table = PositionTable::new();
+        for (index, span) in spans {
+            table.add(index, span);
+        }
+        
+        assert_eq!(table.iter().collect::<Vec<_>>(), spans);
+        assert_eq!(table.lookup(150), Some(Span::new(12, 12)));
+        assert_eq!(table.lookup(1000), Some(Span::new(1, 2)));
+        // The newest entry is only encoded once a later one arrives
+        assert_eq!(table.encoded_size(), 15);
+    }
+    
+    #[test]
+    fn test_handler_for_picks_innermost() {
+        let mut chunk = BytecodeChunk::new();
+        chunk.add_handler(ExceptionHandler { start: 2, end: 4, target: 10, stack_depth: 0 });
+        chunk.add_handler(ExceptionHandler { start: 0, end: 8, target: 20, stack_depth: 1 });
+        
+        assert_eq!(chunk.handler_for(3).map(|h| h.target), Some(10));
+        assert_eq!(chunk.handler_for(4).map(|h| h.target), Some(20));
+        assert_eq!(chunk.handler_for(8), None);
+    }
+    
     #[test]
     fn test_instruction_types() {
         let instructions = vec![
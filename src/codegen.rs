@@ -1,14 +1,101 @@
 // Bytecode generation from AST
 
-use crate::ast::{ASTNode, BinOp};
-use crate::bytecode::{BytecodeChunk, Instruction};
-use crate::scope::Scope;
-use crate::types::Value;
+use crate::ast::{ASTNode, BinOp, DeclKind, LogicalOp, Param, UnaryOp, UpdateOp};
+use crate::bytecode::{BytecodeChunk, ExceptionHandler, FunctionInfo, Instruction};
+use crate::error::CompileError;
+use crate::lexer::TemplatePart;
+use crate::scope::{Binding, FunctionAnalysis, Scope, ScopeType};
+use crate::types::{FunctionId, Span, Value};
+use std::collections::{BTreeSet, HashMap};
+
+/// A statement that `break` (and, for loops, `continue`) can target
+struct LoopContext {
+    labels: Vec<String>,
+    is_loop: bool,
+    /// Jumps to patch to the end of the statement
+    break_jumps: Vec<usize>,
+    /// Jumps to patch to the loop's continue point
+    continue_jumps: Vec<usize>,
+    /// Values on the operand stack when the statement started
+    stack_depth: usize,
+}
+
+impl LoopContext {
+    fn new(labels: Vec<String>, is_loop: bool, stack_depth: usize) -> Self {
+        Self {
+            labels,
+            is_loop,
+            break_jumps: Vec::new(),
+            continue_jumps: Vec::new(),
+            stack_depth,
+        }
+    }
+}
+
+/// A `try` block or `catch` clause whose code is protected by a handler
+struct TryContext {
+    /// `finally` block to run when control leaves the clause
+    finalizer: Option<ASTNode>,
+    /// Length of the loop stack when the statement started
+    loop_depth: usize,
+    /// Protected instruction ranges, split where a jump out runs a `finally`
+    ranges: Vec<(usize, usize)>,
+    /// Start of the range still open
+    range_start: usize,
+}
+
+impl TryContext {
+    fn new(finalizer: Option<ASTNode>, loop_depth: usize, start: usize) -> Self {
+        Self {
+            finalizer,
+            loop_depth,
+            ranges: Vec::new(),
+            range_start: start,
+        }
+    }
+    
+    /// Close the open range at `end`
+    fn close(&mut self, end: usize) {
+        if end > self.range_start {
+            self.ranges.push((self.range_start, end));
+        }
+    }
+}
+
+/// A variable an assignment or update writes to
+#[derive(Clone, Copy)]
+enum AssignTarget<'a> {
+    /// Resolved through the scope chain
+    Resolved(&'a str, Binding),
+    /// Looked up among the globals by the name in this constant
+    Global(usize, Span),
+}
 
 /// Bytecode generator
 pub struct BytecodeGenerator {
     chunk: BytecodeChunk,
     scope: Scope,
+    /// Functions compiled so far, including nested ones
+    functions: HashMap<FunctionId, FunctionInfo>,
+    /// Next free function ID (0 is reserved for the top-level script)
+    next_function_id: FunctionId,
+    /// Enclosing break/continue targets, innermost last
+    loop_stack: Vec<LoopContext>,
+    /// Labels waiting to be attached to the next loop
+    pending_labels: Vec<String>,
+    /// Enclosing protected clauses, innermost last
+    try_stack: Vec<TryContext>,
+    /// Span of the innermost node being compiled
+    current_span: Option<Span>,
+    /// Values the enclosing statements keep on the operand stack, such as a
+    /// pending exception or return value while a `finally` block runs
+    stack_depth: usize,
+    /// Closure and TDZ facts about the function being compiled
+    analysis: FunctionAnalysis,
+    /// Whether unresolved names are reported at compile time
+    early_errors: bool,
+    /// Problems found in early-error mode, in source order
+    errors: Vec<CompileError>,
 }
 
 impl BytecodeGenerator {
@@ -16,9 +103,31 @@ impl BytecodeGenerator {
         Self {
             chunk: BytecodeChunk::new(),
             scope,
+            functions: HashMap::new(),
+            next_function_id: 1,
+            loop_stack: Vec::new(),
+            pending_labels: Vec::new(),
+            try_stack: Vec::new(),
+            current_span: None,
+            stack_depth: 0,
+            analysis: FunctionAnalysis::default(),
+            early_errors: false,
+            errors: Vec::new(),
         }
     }
     
+    /// Report names that no scope declares as compile errors, instead of
+    /// leaving them to a global lookup at runtime
+    pub fn with_early_errors(mut self, enabled: bool) -> Self {
+        self.early_errors = enabled;
+        self
+    }
+    
+    /// Errors found by `generate` in early-error mode
+    pub fn errors(&self) -> &[CompileError] {
+        &self.errors
+    }
+    
     /// Generate bytecode from AST
     pub fn generate(&mut self, ast: &ASTNode) -> BytecodeChunk {
         self.compile_node(ast);
@@ -26,12 +135,607 @@ impl BytecodeGenerator {
         self.chunk.clone()
     }
     
-    /// Compile a single AST node
+    /// The scope being compiled into, including the declarations `generate` added
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+    
+    /// Functions compiled by `generate`, keyed by ID
+    pub fn functions(&self) -> &HashMap<FunctionId, FunctionInfo> {
+        &self.functions
+    }
+    
+    /// Take ownership of the compiled functions, leaving the table empty
+    pub fn take_functions(&mut self) -> HashMap<FunctionId, FunctionInfo> {
+        std::mem::take(&mut self.functions)
+    }
+    
+    /// Next function ID that will be assigned
+    pub fn next_function_id(&self) -> FunctionId {
+        self.next_function_id
+    }
+    
+    /// Start assigning function IDs from `id`, so IDs stay unique across generators
+    pub fn set_next_function_id(&mut self, id: FunctionId) {
+        self.next_function_id = id;
+    }
+    
+    /// Compile a function body into its own chunk and register it in the function table
+    fn compile_function(&mut self, name: &str, params: &[Param], body: &ASTNode) -> FunctionId {
+        let analysis = FunctionAnalysis::analyze_function(params, body);
+        self.compile_function_with_analysis(name, params, body, analysis)
+    }
+    
+    fn compile_function_with_analysis(
+        &mut self,
+        name: &str,
+        params: &[Param],
+        body: &ASTNode,
+        analysis: FunctionAnalysis,
+    ) -> FunctionId {
+        let id = self.next_function_id;
+        self.next_function_id += 1;
+        
+        // Parameters, then the `arguments` object if used, occupy the first locals of the new frame
+        let mut locals: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+        if analysis.arguments {
+            locals.push("arguments".to_string());
+        }
+        
+        let mut scope = self.scope.function_scope();
+        
+        // A frame's upvalues start with the cells its closure captured from this frame...
+        let mut captures = Vec::new();
+        for name in &analysis.free {
+            if let Some(Binding::Captured(index)) = self.scope.resolve(name) {
+                scope.declare_cell(name.clone());
+                if let Some(kind) = self.scope.kind(name) {
+                    scope.set_kind(name.clone(), kind);
+                }
+                captures.push(index);
+            }
+        }
+        // ...followed by fresh cells for its own parameters and `var`s that nested
+        // closures use; captured block-scoped variables get theirs on block entry
+        let entry_cells: BTreeSet<&String> = locals.iter()
+            .chain(&analysis.vars)
+            .filter(|name| analysis.captured.contains(*name))
+            .collect();
+        for name in &entry_cells {
+            scope.declare_cell(name.to_string());
+        }
+        let cell_count = entry_cells.len();
+        
+        for local in &locals {
+            scope.declare(local.clone());
+        }
+        
+        let mut generator = BytecodeGenerator::new(scope).with_early_errors(self.early_errors);
+        generator.set_next_function_id(self.next_function_id);
+        
+        // Captured parameters are moved into their cells on entry
+        for (slot, local) in locals.iter().enumerate() {
+            if let Some(Binding::Captured(cell)) = generator.scope.resolve(local) {
+                generator.chunk.emit(Instruction::LoadLocal(slot));
+                generator.chunk.emit(Instruction::StoreUpvalue(cell));
+            }
+        }
+        let arguments = analysis.arguments;
+        generator.analysis = analysis;
+        for param in params {
+            if let Some(default) = &param.default {
+                generator.compile_default(&param.name, default);
+            }
+        }
+        generator.hoist_declarations();
+        let chunk = generator.generate(body);
+        
+        self.next_function_id = generator.next_function_id;
+        self.functions.extend(generator.take_functions());
+        self.errors.append(&mut generator.errors);
+        let rest = params.last().is_some_and(|param| param.rest);
+        let arity = params.len() - usize::from(rest);
+        let info = FunctionInfo::new(id, name.to_string(), arity, chunk)
+            .with_upvalues(captures, cell_count)
+            .with_rest(rest)
+            .with_arguments(arguments);
+        self.functions.insert(id, info);
+        id
+    }
+    
+    /// Compile a function or arrow function expression into a closure.
+    /// Anonymous ones are named after the variable they initialise, if any.
+    fn compile_function_expr(&mut self, node: &ASTNode, inferred_name: &str) {
+        match node {
+            ASTNode::FunctionExpr { name: Some(name), params, body, .. } => {
+                // Inside its body the name refers to the function itself
+                let self_binding = self.analysis.captured.contains(name).then(|| {
+                    self.enter_block();
+                    self.declare_block_binding(name)
+                });
+                let id = self.compile_function(name, params, body);
+                self.chunk.emit(Instruction::MakeClosure(id));
+                if let Some(binding) = self_binding {
+                    self.chunk.emit(Instruction::Dup);
+                    self.emit_store(binding);
+                    self.exit_block();
+                }
+            }
+            ASTNode::FunctionExpr { name: None, params, body, .. } => {
+                let id = self.compile_function(inferred_name, params, body);
+                self.chunk.emit(Instruction::MakeClosure(id));
+            }
+            ASTNode::ArrowFunction { params, body, .. } => {
+                // An expression body is the function's return value
+                let returned;
+                let body = if matches!(**body, ASTNode::BlockStmt { .. }) {
+                    &**body
+                } else {
+                    returned = ASTNode::ReturnStmt {
+                        value: Some(body.clone()),
+                        span: body.span(),
+                    };
+                    &returned
+                };
+                // Arrow functions see the `arguments` of the function around them
+                let analysis = FunctionAnalysis::analyze(params, body);
+                let id = self.compile_function_with_analysis(inferred_name, params, body, analysis);
+                self.chunk.emit(Instruction::MakeClosure(id));
+            }
+            _ => self.compile_node(node),
+        }
+    }
+    
+    /// Compile a value bound to `name`; an anonymous function takes the name
+    fn compile_named_value(&mut self, node: &ASTNode, name: &str) {
+        if !node.is_function_expr() {
+            return self.compile_node(node);
+        }
+        
+        let outer = self.current_span.replace(node.span());
+        self.chunk.add_position(node.span());
+        self.compile_function_expr(node, name);
+        self.current_span = outer;
+        if let Some(span) = outer {
+            self.chunk.add_position(span);
+        }
+    }
+    
+    /// Replace a parameter's `undefined` argument with its default value
+    fn compile_default(&mut self, name: &str, default: &ASTNode) {
+        let Some(binding) = self.scope.resolve(name) else {
+            return;
+        };
+        self.emit_load(binding);
+        let idx = self.chunk.add_constant(Value::Undefined);
+        self.chunk.emit(Instruction::LoadConst(idx));
+        self.chunk.emit(Instruction::StrictEqual);
+        let skip = self.emit_jump(Instruction::JumpIfFalse(0));
+        
+        self.compile_named_value(default, name);
+        self.emit_store(binding);
+        self.patch_jump(skip, self.chunk.instructions.len());
+    }
+    
+    /// Compile call arguments that include spreads into one array on the stack
+    fn compile_spread_args(&mut self, args: &[ASTNode]) {
+        // Arguments before the first spread start the array; later runs of
+        // plain arguments are gathered into arrays and spread onto it
+        let first = args.iter()
+            .position(|arg| matches!(arg, ASTNode::Spread { .. }))
+            .unwrap_or(args.len());
+        for arg in &args[..first] {
+            self.compile_node(arg);
+        }
+        self.chunk.emit(Instruction::CreateArray(first));
+        
+        let mut plain = 0;
+        for arg in &args[first..] {
+            let spread = matches!(arg, ASTNode::Spread { .. });
+            if spread && plain > 0 {
+                self.chunk.emit(Instruction::CreateArray(plain));
+                self.chunk.emit(Instruction::AppendSpread);
+                plain = 0;
+            }
+            self.compile_node(arg);
+            if !spread {
+                plain += 1;
+            }
+        }
+        if plain > 0 {
+            self.chunk.emit(Instruction::CreateArray(plain));
+            self.chunk.emit(Instruction::AppendSpread);
+        }
+    }
+    
+    /// Declare a function's `var` variables up front; they start out `undefined`
+    fn hoist_declarations(&mut self) {
+        for name in self.analysis.vars.clone() {
+            // Redeclaring a parameter or an earlier `var` reuses its slot
+            if self.scope.declares_local(&name) {
+                continue;
+            }
+            self.scope.declare_binding(name.clone());
+            self.scope.set_kind(name, DeclKind::Var);
+        }
+    }
+    
+    /// Declare a statement list's `let`/`const` variables and create the
+    /// closures for its function declarations before running it.
+    ///
+    /// A `let`/`const` that could be accessed before its declaration runs,
+    /// from earlier code or from a closure, holds the hole until then so the
+    /// access can be reported.
+    fn hoist_block(&mut self, statements: &[ASTNode]) {
+        for stmt in statements {
+            if let ASTNode::VarDecl { kind, name, .. } = stmt {
+                if !kind.is_lexical() {
+                    continue;
+                }
+                let binding = self.declare_block_binding(name);
+                self.scope.set_kind(name.clone(), *kind);
+                let reachable_early = self.analysis.captured.contains(name)
+                    || self.analysis.used_before_declaration.contains(name);
+                if reachable_early {
+                    self.chunk.emit(Instruction::LoadHole);
+                    self.emit_store(binding);
+                }
+            }
+        }
+        
+        for stmt in statements {
+            if let ASTNode::FunctionDecl { name, params, body, .. } = stmt {
+                // Declare the name first so the body can call itself recursively
+                let binding = self.declare_block_binding(name);
+                let id = self.compile_function(name, params, body);
+                
+                self.chunk.emit(Instruction::MakeClosure(id));
+                self.emit_store(binding);
+            }
+        }
+    }
+    
+    /// Declare a block-scoped variable in the current scope.
+    ///
+    /// Outside the top level, a captured one gets a fresh cell each time the
+    /// block is entered, so closures from different runs of it (such as loop
+    /// iterations) do not share it.
+    fn declare_block_binding(&mut self, name: &str) -> Binding {
+        if self.analysis.captured.contains(name) && *self.scope.scope_type() != ScopeType::Global {
+            let cell = self.scope.declare_cell(name.to_string());
+            self.chunk.emit(Instruction::FreshCell(cell));
+            Binding::Captured(cell)
+        } else {
+            Binding::Local(self.scope.declare(name.to_string()))
+        }
+    }
+    
+    /// Enter a block scope
+    fn enter_block(&mut self) {
+        self.scope = self.scope.block_scope();
+    }
+    
+    /// Leave the innermost block scope
+    fn exit_block(&mut self) {
+        let block = std::mem::replace(&mut self.scope, Scope::global());
+        self.scope = block.exit_block();
+    }
+    
+    /// Whether accessing `name` here needs a temporal-dead-zone check
+    fn needs_tdz_check(&self, name: &str) -> bool {
+        self.scope.kind(name).is_some_and(DeclKind::is_lexical) && !self.scope.is_initialized(name)
+    }
+    
+    /// Emit a check that the loaded value of `name` is not the hole, if it might be
+    fn emit_tdz_check(&mut self, name: &str) {
+        if self.needs_tdz_check(name) {
+            let idx = self.chunk.add_constant(Value::String(name.into()));
+            self.chunk.emit(Instruction::CheckInitialized(idx));
+        }
+    }
+    
+    /// Emit a store to `name`, failing at runtime if it is a `const` or still uninitialised
+    fn emit_checked_store(&mut self, name: &str, binding: Binding) {
+        if self.scope.kind(name) == Some(DeclKind::Const) {
+            let idx = self.chunk.add_constant(Value::String(name.into()));
+            self.chunk.emit(Instruction::ThrowConstAssignment(idx));
+            return;
+        }
+        if self.needs_tdz_check(name) {
+            self.emit_load(binding);
+            self.emit_tdz_check(name);
+            self.chunk.emit(Instruction::Pop);
+        }
+        self.emit_store(binding);
+    }
+    
+    /// Constant holding a name no scope declares, which is looked up among the
+    /// globals at runtime; in early-error mode this is reported as well
+    fn unresolved_name(&mut self, name: &str, span: Span) -> usize {
+        if self.early_errors {
+            self.errors.push(CompileError::UnresolvedReference {
+                name: name.to_string(),
+                span,
+            });
+        }
+        self.chunk.add_constant(Value::String(name.into()))
+    }
+    
+    /// Resolve the variable an assignment or update writes to
+    fn assign_target<'a>(&mut self, name: &'a str, span: Span) -> AssignTarget<'a> {
+        match self.scope.resolve(name) {
+            Some(binding) => AssignTarget::Resolved(name, binding),
+            None => AssignTarget::Global(self.unresolved_name(name, span), span),
+        }
+    }
+    
+    /// Emit a load of an assignment target's current value
+    fn emit_target_load(&mut self, target: AssignTarget) {
+        match target {
+            AssignTarget::Resolved(name, binding) => {
+                self.emit_load(binding);
+                self.emit_tdz_check(name);
+            }
+            AssignTarget::Global(idx, span) => self.chunk.emit(Instruction::LoadGlobalName(idx, span)),
+        }
+    }
+    
+    /// Emit a store to an assignment target
+    fn emit_target_store(&mut self, target: AssignTarget) {
+        match target {
+            AssignTarget::Resolved(name, binding) => self.emit_checked_store(name, binding),
+            AssignTarget::Global(idx, span) => self.chunk.emit(Instruction::StoreGlobalName(idx, span)),
+        }
+    }
+    
+    /// Emit the instruction that pushes a resolved variable
+    fn emit_load(&mut self, binding: Binding) {
+        match binding {
+            Binding::Local(idx) => self.chunk.emit(Instruction::LoadLocal(idx)),
+            Binding::Captured(idx) => self.chunk.emit(Instruction::LoadUpvalue(idx)),
+            Binding::Global(idx) => self.chunk.emit(Instruction::LoadGlobal(idx)),
+        }
+    }
+    
+    /// Emit the instruction that pops into a resolved variable
+    fn emit_store(&mut self, binding: Binding) {
+        match binding {
+            Binding::Local(idx) => self.chunk.emit(Instruction::StoreLocal(idx)),
+            Binding::Captured(idx) => self.chunk.emit(Instruction::StoreUpvalue(idx)),
+            Binding::Global(idx) => self.chunk.emit(Instruction::StoreGlobal(idx)),
+        }
+    }
+    
+    /// Emit the instruction for an arithmetic or comparison operator
+    fn emit_binary_op(&mut self, op: &BinOp) {
+        let instruction = match op {
+            BinOp::Add => Instruction::Add,
+            BinOp::Sub => Instruction::Sub,
+            BinOp::Mul => Instruction::Mul,
+            BinOp::Div => Instruction::Div,
+            BinOp::Equal => Instruction::Equal,
+            BinOp::NotEqual => Instruction::NotEqual,
+            BinOp::StrictEqual => Instruction::StrictEqual,
+            BinOp::StrictNotEqual => Instruction::StrictNotEqual,
+            BinOp::Less => Instruction::Less,
+            BinOp::LessEqual => Instruction::LessEqual,
+            BinOp::Greater => Instruction::Greater,
+            BinOp::GreaterEqual => Instruction::GreaterEqual,
+        };
+        self.chunk.emit(instruction);
+    }
+    
+    /// Emit a forward jump with a placeholder offset, returning its index
+    fn emit_jump(&mut self, instruction: Instruction) -> usize {
+        self.chunk.emit(instruction);
+        self.chunk.instructions.len() - 1
+    }
+    
+    /// Emit a backward jump to `target`
+    fn emit_loop(&mut self, target: usize) {
+        let offset = (target as isize) - (self.chunk.instructions.len() as isize) - 1;
+        self.chunk.emit(Instruction::Jump(offset));
+    }
+    
+    /// Point the jump at `idx` to `target`
+    fn patch_jump(&mut self, idx: usize, target: usize) {
+        let offset = (target as isize) - (idx as isize) - 1;
+        self.chunk.instructions[idx] = match self.chunk.instructions[idx] {
+            Instruction::Jump(_) => Instruction::Jump(offset),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(offset),
+            Instruction::JumpIfTrueOrPop(_) => Instruction::JumpIfTrueOrPop(offset),
+            Instruction::JumpIfFalseOrPop(_) => Instruction::JumpIfFalseOrPop(offset),
+            Instruction::JumpIfNotNullishOrPop(_) => Instruction::JumpIfNotNullishOrPop(offset),
+            ref other => unreachable!("patching non-jump instruction {:?}", other),
+        };
+    }
+    
+    /// Start a loop, attaching any labels that precede it
+    fn enter_loop(&mut self) {
+        let labels = std::mem::take(&mut self.pending_labels);
+        self.loop_stack.push(LoopContext::new(labels, true, self.stack_depth));
+    }
+    
+    /// Finish the innermost loop, patching its continue and break jumps
+    fn exit_loop(&mut self, continue_target: usize) {
+        let context = self.loop_stack.pop().expect("exit_loop without enter_loop");
+        for idx in context.continue_jumps {
+            self.patch_jump(idx, continue_target);
+        }
+        let end = self.chunk.instructions.len();
+        for idx in context.break_jumps {
+            self.patch_jump(idx, end);
+        }
+    }
+    
+    /// Index of the statement targeted by a `break`/`continue` with an optional label
+    fn jump_target(&self, label: Option<&str>) -> Option<usize> {
+        self.loop_stack.iter().rposition(|context| match label {
+            Some(label) => context.labels.iter().any(|l| l == label),
+            None => context.is_loop,
+        })
+    }
+    
+    /// Number of protected clauses enclosing the loop stack entry at `target`
+    fn try_depth_outside(&self, target: usize) -> usize {
+        self.try_stack.iter().take_while(|context| context.loop_depth <= target).count()
+    }
+    
+    /// Emit `instruction` to leave every protected clause but the outer `keep`
+    /// with `depth` values on the operand stack, running their `finally` blocks
+    /// on the way out, innermost first.
+    ///
+    /// Values kept above `depth`, such as the pending exception of a `finally`
+    /// being left, are popped first. The inlined `finally` code is kept out of
+    /// the clauses it leaves, so an exception it throws goes to the handlers
+    /// outside them.
+    fn emit_exit(&mut self, keep: usize, depth: usize, instruction: Instruction) -> usize {
+        let outer_depth = self.stack_depth;
+        for _ in depth..outer_depth {
+            self.chunk.emit(Instruction::Pop);
+        }
+        self.stack_depth = depth;
+        let idx = self.emit_finalizers_and_jump(keep, instruction);
+        self.stack_depth = outer_depth;
+        idx
+    }
+    
+    /// Run the `finally` blocks of every protected clause but the outer `keep`,
+    /// then emit `instruction`
+    fn emit_finalizers_and_jump(&mut self, keep: usize, instruction: Instruction) -> usize {
+        let crossed = &self.try_stack[keep..];
+        if crossed.iter().all(|context| context.finalizer.is_none()) {
+            return self.emit_jump(instruction);
+        }
+        
+        let mut left = Vec::new();
+        while self.try_stack.len() > keep {
+            let mut context = self.try_stack.pop().expect("crossed try context");
+            context.close(self.chunk.instructions.len());
+            if let Some(finalizer) = &context.finalizer {
+                self.compile_node(finalizer);
+            }
+            left.push(context);
+        }
+        
+        let idx = self.emit_jump(instruction);
+        let start = self.chunk.instructions.len();
+        for mut context in left.into_iter().rev() {
+            context.range_start = start;
+            self.try_stack.push(context);
+        }
+        idx
+    }
+    
+    /// Start a protected clause
+    fn enter_try(&mut self, finalizer: Option<&ASTNode>) {
+        let context = TryContext::new(finalizer.cloned(), self.loop_stack.len(), self.chunk.instructions.len());
+        self.try_stack.push(context);
+    }
+    
+    /// Finish the innermost protected clause, returning its ranges
+    fn exit_try(&mut self) -> Vec<(usize, usize)> {
+        let mut context = self.try_stack.pop().expect("exit_try without enter_try");
+        context.close(self.chunk.instructions.len());
+        context.ranges
+    }
+    
+    /// Register handlers sending exceptions in `ranges` to `target`
+    fn add_handlers(&mut self, ranges: Vec<(usize, usize)>, target: usize) {
+        for (start, end) in ranges {
+            self.chunk.add_handler(ExceptionHandler {
+                start,
+                end,
+                target,
+                stack_depth: self.stack_depth,
+            });
+        }
+    }
+    
+    /// Compile a `finally` block run with the pending exception or return value on the stack
+    fn compile_pending_finalizer(&mut self, finalizer: &ASTNode) {
+        self.stack_depth += 1;
+        self.compile_node(finalizer);
+        self.stack_depth -= 1;
+    }
+    
+    /// Compile a statement, discarding the value of expression statements
+    fn compile_statement(&mut self, node: &ASTNode) {
+        self.compile_node(node);
+        if node.is_expression() {
+            self.chunk.emit(Instruction::Pop);
+        }
+    }
+    
+    /// Load a string constant
+    fn emit_string(&mut self, value: &str) {
+        let idx = self.chunk.add_constant(Value::String(value.into()));
+        self.chunk.emit(Instruction::LoadConst(idx));
+    }
+    
+    /// Compile the callee of a call, returning whether it is a method call. A
+    /// method's receiver and key stay on the stack for `CallMethod`.
+    fn compile_callee(&mut self, callee: &ASTNode) -> bool {
+        if let ASTNode::MemberExpr { object, property, .. } = callee {
+            self.compile_node(object);
+            self.compile_node(property);
+            true
+        } else {
+            self.compile_node(callee);
+            false
+        }
+    }
+    
+    /// Build the strings array passed to a template tag: the cooked strings,
+    /// with the raw ones as its `raw` property. Each evaluation makes a new
+    /// array, rather than reusing one per call site as the spec does.
+    fn compile_template_object(&mut self, quasis: &[TemplatePart]) {
+        for quasi in quasis {
+            let value = quasi.cooked.as_deref().map_or(Value::Undefined, |text| Value::String(text.into()));
+            let idx = self.chunk.add_constant(value);
+            self.chunk.emit(Instruction::LoadConst(idx));
+        }
+        self.chunk.emit(Instruction::CreateArray(quasis.len()));
+        
+        self.chunk.emit(Instruction::Dup);
+        self.emit_string("raw");
+        for quasi in quasis {
+            self.emit_string(&quasi.raw);
+        }
+        self.chunk.emit(Instruction::CreateArray(quasis.len()));
+        self.chunk.emit(Instruction::SetProperty);
+        self.chunk.emit(Instruction::Pop);
+    }
+    
+    /// Compile a single AST node, attributing its code to its span in the
+    /// chunk's position table
     fn compile_node(&mut self, node: &ASTNode) {
+        let outer = self.current_span.replace(node.span());
+        self.chunk.add_position(node.span());
+        self.compile_node_code(node);
+        
+        // Code emitted after the node belongs to the enclosing one again
+        self.current_span = outer;
+        if let Some(span) = outer {
+            self.chunk.add_position(span);
+        }
+    }
+    
+    fn compile_node_code(&mut self, node: &ASTNode) {
         match node {
             ASTNode::Program(stmts) => {
-                for stmt in stmts {
-                    self.compile_node(stmt);
+                self.analysis = FunctionAnalysis::analyze(&[], node);
+                self.hoist_declarations();
+                self.hoist_block(stmts);
+                
+                // The value of a trailing expression statement is the program's result
+                if let Some((last, rest)) = stmts.split_last() {
+                    for stmt in rest.iter().filter(|stmt| !stmt.is_function_decl()) {
+                        self.compile_statement(stmt);
+                    }
+                    if !last.is_function_decl() {
+                        self.compile_node(last);
+                    }
                 }
             }
             
@@ -40,32 +744,217 @@ impl BytecodeGenerator {
                 self.chunk.emit(Instruction::LoadConst(idx));
             }
             
-            ASTNode::Identifier { name, .. } => {
-                if let Some(idx) = self.scope.lookup(name) {
-                    self.chunk.emit(Instruction::LoadLocal(idx));
+            ASTNode::BooleanLiteral { value, .. } => {
+                let idx = self.chunk.add_constant(Value::Boolean(*value));
+                self.chunk.emit(Instruction::LoadConst(idx));
+            }
+            
+            ASTNode::StringLiteral { value, .. } => self.emit_string(value),
+            
+            ASTNode::TemplateLiteral { quasis, expressions, .. } => {
+                // Starting from the leading text makes every `+` a string concatenation
+                self.emit_string(quasis[0].cooked.as_deref().unwrap_or_default());
+                for (expr, quasi) in expressions.iter().zip(&quasis[1..]) {
+                    self.compile_node(expr);
+                    self.chunk.emit(Instruction::Add);
+                    let text = quasi.cooked.as_deref().unwrap_or_default();
+                    if !text.is_empty() {
+                        self.emit_string(text);
+                        self.chunk.emit(Instruction::Add);
+                    }
+                }
+            }
+            
+            ASTNode::NullLiteral { .. } => {
+                let idx = self.chunk.add_constant(Value::Null);
+                self.chunk.emit(Instruction::LoadConst(idx));
+            }
+            
+            ASTNode::Error { .. } => {
+                // Only a recovering parse produces these, alongside its diagnostics
+                self.errors.push(CompileError::UnsupportedFeature {
+                    feature: "statement with a syntax error".to_string(),
+                });
+            }
+            
+            ASTNode::Identifier { name, span } => {
+                if let Some(binding) = self.scope.resolve(name) {
+                    self.emit_load(binding);
+                    self.emit_tdz_check(name);
+                } else {
+                    let idx = self.unresolved_name(name, *span);
+                    self.chunk.emit(Instruction::LoadGlobalName(idx, *span));
                 }
             }
             
             ASTNode::BinaryExpr { op, left, right, .. } => {
                 self.compile_node(left);
                 self.compile_node(right);
+                self.emit_binary_op(op);
+            }
+            
+            ASTNode::UnaryExpr { op, operand, .. } => {
+                // Negative number literals become a single constant
+                if let (UnaryOp::Neg, ASTNode::NumberLiteral { value, .. }) = (op, &**operand) {
+                    let idx = self.chunk.add_constant(Value::Number(-value));
+                    self.chunk.emit(Instruction::LoadConst(idx));
+                    return;
+                }
+                
+                self.compile_node(operand);
+                self.chunk.emit(match op {
+                    UnaryOp::Neg => Instruction::Negate,
+                    UnaryOp::Plus => Instruction::ToNumber,
+                    UnaryOp::Not => Instruction::Not,
+                });
+            }
+            
+            ASTNode::LogicalExpr { op, left, right, .. } => {
+                // The left value is the result unless the operator needs the right side
+                self.compile_node(left);
+                let jump = self.emit_jump(match op {
+                    LogicalOp::And => Instruction::JumpIfFalseOrPop(0),
+                    LogicalOp::Or => Instruction::JumpIfTrueOrPop(0),
+                    LogicalOp::Nullish => Instruction::JumpIfNotNullishOrPop(0),
+                });
+                self.compile_node(right);
+                
+                let end = self.chunk.instructions.len();
+                self.patch_jump(jump, end);
+            }
+            
+            ASTNode::AssignExpr { op, target, value, .. } => {
+                if let ASTNode::MemberExpr { object, property, .. } = &**target {
+                    self.compile_node(object);
+                    self.compile_node(property);
+                    if let Some(bin_op) = op.binary_op() {
+                        self.chunk.emit(Instruction::Dup2);
+                        self.chunk.emit(Instruction::GetProperty);
+                        self.compile_node(value);
+                        self.emit_binary_op(&bin_op);
+                    } else {
+                        self.compile_node(value);
+                    }
+                    // SetProperty leaves the assigned value as the result
+                    self.chunk.emit(Instruction::SetProperty);
+                    return;
+                }
                 
-                match op {
-                    BinOp::Add => self.chunk.emit(Instruction::Add),
-                    BinOp::Sub => self.chunk.emit(Instruction::Sub),
-                    BinOp::Mul => self.chunk.emit(Instruction::Mul),
-                    BinOp::Div => self.chunk.emit(Instruction::Div),
-                    _ => {}
+                let ASTNode::Identifier { name, span } = &**target else {
+                    // Invalid target: still evaluate the value as the result
+                    self.compile_node(value);
+                    return;
+                };
+                let target = self.assign_target(name, *span);
+                
+                if let Some(bin_op) = op.binary_op() {
+                    self.emit_target_load(target);
+                    self.compile_node(value);
+                    self.emit_binary_op(&bin_op);
+                } else {
+                    self.compile_node(value);
+                }
+                
+                // Leave the assigned value as the expression's result
+                self.chunk.emit(Instruction::Dup);
+                self.emit_target_store(target);
+            }
+            
+            ASTNode::UpdateExpr { op, prefix, target, .. } => {
+                if let ASTNode::MemberExpr { object, property, .. } = &**target {
+                    self.compile_node(object);
+                    self.compile_node(property);
+                    self.chunk.emit(Instruction::Dup2);
+                    self.chunk.emit(Instruction::GetProperty);
+                    self.chunk.emit(Instruction::ToNumber);
+                    if !*prefix {
+                        // Stash the old value beneath the object and key
+                        self.chunk.emit(Instruction::Dup);
+                        self.chunk.emit(Instruction::MoveUnder(3));
+                    }
+                    
+                    let one = self.chunk.add_constant(Value::Number(1.0));
+                    self.chunk.emit(Instruction::LoadConst(one));
+                    self.chunk.emit(match op {
+                        UpdateOp::Increment => Instruction::Add,
+                        UpdateOp::Decrement => Instruction::Sub,
+                    });
+                    self.chunk.emit(Instruction::SetProperty);
+                    if !*prefix {
+                        self.chunk.emit(Instruction::Pop);
+                    }
+                    return;
+                }
+                
+                let ASTNode::Identifier { name, span } = &**target else {
+                    let idx = self.chunk.add_constant(Value::Number(f64::NAN));
+                    self.chunk.emit(Instruction::LoadConst(idx));
+                    return;
+                };
+                let target = self.assign_target(name, *span);
+                
+                self.emit_target_load(target);
+                self.chunk.emit(Instruction::ToNumber);
+                if !*prefix {
+                    // Postfix keeps the old (numeric) value as the result
+                    self.chunk.emit(Instruction::Dup);
+                }
+                
+                let one = self.chunk.add_constant(Value::Number(1.0));
+                self.chunk.emit(Instruction::LoadConst(one));
+                self.chunk.emit(match op {
+                    UpdateOp::Increment => Instruction::Add,
+                    UpdateOp::Decrement => Instruction::Sub,
+                });
+                
+                if *prefix {
+                    self.chunk.emit(Instruction::Dup);
+                }
+                self.emit_target_store(target);
+            }
+            
+            ASTNode::MemberExpr { object, property, .. } => {
+                self.compile_node(object);
+                self.compile_node(property);
+                self.chunk.emit(Instruction::GetProperty);
+            }
+            
+            ASTNode::ObjectLiteral { properties, .. } => {
+                for (key, value) in properties {
+                    let idx = self.chunk.add_constant(Value::String(key.as_str().into()));
+                    self.chunk.emit(Instruction::LoadConst(idx));
+                    self.compile_node(value);
+                }
+                self.chunk.emit(Instruction::CreateObject(properties.len()));
+            }
+            
+            ASTNode::ArrayLiteral { elements, .. } => {
+                for element in elements {
+                    self.compile_node(element);
                 }
+                self.chunk.emit(Instruction::CreateArray(elements.len()));
             }
             
-            ASTNode::LetDecl { name, init, .. } => {
-                // Compile the initializer
-                self.compile_node(init);
+            ASTNode::VarDecl { kind, name, init, .. } => {
+                match init {
+                    Some(init) => self.compile_named_value(init, name),
+                    // `var x;` leaves any earlier value alone
+                    None if *kind == DeclKind::Var => return,
+                    None => {
+                        let idx = self.chunk.add_constant(Value::Undefined);
+                        self.chunk.emit(Instruction::LoadConst(idx));
+                    }
+                }
                 
-                // Declare the variable and store
-                let idx = self.scope.declare(name.clone());
-                self.chunk.emit(Instruction::StoreLocal(idx));
+                // The variable was hoisted to the top of its function or block
+                let binding = match self.scope.resolve(name) {
+                    Some(binding) => binding,
+                    None => self.scope.declare_binding(name.clone()),
+                };
+                self.emit_store(binding);
+                if kind.is_lexical() {
+                    self.scope.mark_initialized(name.clone());
+                }
             }
             
             ASTNode::CallExpr { callee, args, .. } => {
@@ -79,9 +968,17 @@ impl BytecodeGenerator {
                     }
                 }
                 
-                // General function call handling
-                // Compile callee
-                self.compile_node(callee);
+                let method = self.compile_callee(callee);
+                
+                if args.iter().any(|arg| matches!(arg, ASTNode::Spread { .. })) {
+                    self.compile_spread_args(args);
+                    if method {
+                        self.chunk.emit(Instruction::CallMethodWithSpread);
+                    } else {
+                        self.chunk.emit(Instruction::CallWithSpread);
+                    }
+                    return;
+                }
                 
                 // Compile arguments
                 for arg in args {
@@ -89,18 +986,115 @@ impl BytecodeGenerator {
                 }
                 
                 // Emit call instruction
-                self.chunk.emit(Instruction::Call(args.len()));
+                if method {
+                    self.chunk.emit(Instruction::CallMethod(args.len()));
+                } else {
+                    self.chunk.emit(Instruction::Call(args.len()));
+                }
+            }
+            
+            ASTNode::Spread { argument, .. } => {
+                // Only in call arguments, with the array being built below it
+                self.compile_node(argument);
+                self.chunk.emit(Instruction::AppendSpread);
+            }
+            
+            ASTNode::TaggedTemplate { tag, quasis, expressions, .. } => {
+                let method = self.compile_callee(tag);
+                self.compile_template_object(quasis);
+                for expr in expressions {
+                    self.compile_node(expr);
+                }
+                
+                let argc = expressions.len() + 1;
+                if method {
+                    self.chunk.emit(Instruction::CallMethod(argc));
+                } else {
+                    self.chunk.emit(Instruction::Call(argc));
+                }
             }
             
             ASTNode::ReturnStmt { value, .. } => {
+                if let Some(value) = value {
+                    self.compile_node(value);
+                } else {
+                    let idx = self.chunk.add_constant(Value::Undefined);
+                    self.chunk.emit(Instruction::LoadConst(idx));
+                }
+                // Every enclosing `finally` runs with the return value held on the stack
+                self.stack_depth += 1;
+                self.emit_exit(0, self.stack_depth, Instruction::Return);
+                self.stack_depth -= 1;
+            }
+            
+            ASTNode::ThrowStmt { value, .. } => {
                 self.compile_node(value);
-                self.chunk.emit(Instruction::Return);
+                self.chunk.emit(Instruction::Throw);
+            }
+            
+            ASTNode::TryStmt { block, param, handler, finalizer, .. } => {
+                let finalizer = finalizer.as_deref();
+                
+                self.enter_try(finalizer);
+                self.compile_node(block);
+                let block_ranges = self.exit_try();
+                let block_exit = self.emit_jump(Instruction::Jump(0));
+                
+                // Exceptions from the catch clause still run the `finally` block
+                let mut handler_ranges = Vec::new();
+                if let Some(handler) = handler {
+                    let catch_start = self.chunk.instructions.len();
+                    self.add_handlers(block_ranges.clone(), catch_start);
+                    if finalizer.is_some() {
+                        self.enter_try(finalizer);
+                    }
+                    
+                    // The exception is on the stack on entry
+                    self.enter_block();
+                    match param {
+                        Some(name) => {
+                            let binding = self.declare_block_binding(name);
+                            self.emit_store(binding);
+                        }
+                        None => self.chunk.emit(Instruction::Pop),
+                    }
+                    self.compile_node(handler);
+                    self.exit_block();
+                    
+                    if finalizer.is_some() {
+                        handler_ranges = self.exit_try();
+                    }
+                }
+                
+                let normal_exit = self.chunk.instructions.len();
+                self.patch_jump(block_exit, normal_exit);
+                
+                if let Some(finalizer) = finalizer {
+                    self.compile_node(finalizer);
+                    let end_jump = self.emit_jump(Instruction::Jump(0));
+                    
+                    // Exceptions that escape the clauses run the `finally` block
+                    // and are then thrown again
+                    let rethrow = self.chunk.instructions.len();
+                    if handler.is_none() {
+                        self.add_handlers(block_ranges, rethrow);
+                    }
+                    self.add_handlers(handler_ranges, rethrow);
+                    self.compile_pending_finalizer(finalizer);
+                    self.chunk.emit(Instruction::Rethrow);
+                    
+                    let end = self.chunk.instructions.len();
+                    self.patch_jump(end_jump, end);
+                }
             }
             
             ASTNode::BlockStmt { statements, .. } => {
-                for stmt in statements {
-                    self.compile_node(stmt);
+                self.enter_block();
+                self.hoist_block(statements);
+                for stmt in statements.iter().filter(|stmt| !stmt.is_function_decl()) {
+                    self.compile_statement(stmt);
                 }
+                self.exit_block();
             }
             
             ASTNode::IfStmt { cond, then_branch, else_branch, .. } => {
@@ -135,39 +1129,136 @@ impl BytecodeGenerator {
             }
             
             ASTNode::ForStmt { init, cond, update, body, .. } => {
+                // `let`/`const` in the header are scoped to the loop
+                let lexical = init.is_lexical_decl();
+                if lexical {
+                    self.enter_block();
+                    self.hoist_block(std::slice::from_ref(&**init));
+                }
+                self.enter_loop();
+                
                 // Compile init
-                self.compile_node(init);
+                self.compile_statement(init);
+                
+                // Each iteration gets its own copy of captured header variables
+                let per_iteration = match &**init {
+                    ASTNode::VarDecl { name, .. } if lexical => match self.scope.resolve(name) {
+                        Some(Binding::Captured(cell)) => Some(cell),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(cell) = per_iteration {
+                    self.chunk.emit(Instruction::FreshCell(cell));
+                }
                 
                 // Loop start
                 let loop_start = self.chunk.instructions.len();
                 
-                // Compile condition
+                // Compile condition, exiting the loop when it is false
                 self.compile_node(cond);
-                
-                // Jump if false (exit loop)
-                let jump_if_false_idx = self.chunk.instructions.len();
-                self.chunk.emit(Instruction::JumpIfFalse(0));
+                let exit_jump = self.emit_jump(Instruction::JumpIfFalse(0));
                 
                 // Compile body
-                self.compile_node(body);
+                self.compile_statement(body);
                 
-                // Compile update
-                self.compile_node(update);
+                // `continue` runs the update before re-testing the condition
+                let continue_target = self.chunk.instructions.len();
+                if let Some(cell) = per_iteration {
+                    self.chunk.emit(Instruction::FreshCell(cell));
+                }
+                self.compile_statement(update);
+                self.emit_loop(loop_start);
                 
-                // Jump back to loop start
-                let current = self.chunk.instructions.len();
-                let jump_back_offset = (loop_start as isize) - (current as isize) - 1;
-                self.chunk.emit(Instruction::Jump(jump_back_offset));
+                let end = self.chunk.instructions.len();
+                self.patch_jump(exit_jump, end);
+                self.exit_loop(continue_target);
+                if lexical {
+                    self.exit_block();
+                }
+            }
+            
+            ASTNode::WhileStmt { cond, body, .. } => {
+                self.enter_loop();
+                
+                let loop_start = self.chunk.instructions.len();
+                self.compile_node(cond);
+                let exit_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                
+                self.compile_statement(body);
+                self.emit_loop(loop_start);
                 
-                // Patch jump_if_false
                 let end = self.chunk.instructions.len();
-                let jump_if_false_offset = (end as isize) - (jump_if_false_idx as isize) - 1;
-                self.chunk.instructions[jump_if_false_idx] = Instruction::JumpIfFalse(jump_if_false_offset);
+                self.patch_jump(exit_jump, end);
+                self.exit_loop(loop_start);
+            }
+            
+            ASTNode::DoWhileStmt { body, cond, .. } => {
+                self.enter_loop();
+                
+                let loop_start = self.chunk.instructions.len();
+                self.compile_statement(body);
+                
+                // `continue` skips to the condition
+                let continue_target = self.chunk.instructions.len();
+                self.compile_node(cond);
+                let exit_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                self.emit_loop(loop_start);
+                
+                let end = self.chunk.instructions.len();
+                self.patch_jump(exit_jump, end);
+                self.exit_loop(continue_target);
+            }
+            
+            ASTNode::BreakStmt { label, .. } => {
+                if let Some(target) = self.jump_target(label.as_deref()) {
+                    let depth = self.loop_stack[target].stack_depth;
+                    let jump = self.emit_exit(self.try_depth_outside(target), depth, Instruction::Jump(0));
+                    self.loop_stack[target].break_jumps.push(jump);
+                } else {
+                    self.emit_jump(Instruction::Jump(0));
+                }
+            }
+            
+            ASTNode::ContinueStmt { label, .. } => {
+                if let Some(target) = self.jump_target(label.as_deref()) {
+                    let depth = self.loop_stack[target].stack_depth;
+                    let jump = self.emit_exit(self.try_depth_outside(target), depth, Instruction::Jump(0));
+                    self.loop_stack[target].continue_jumps.push(jump);
+                } else {
+                    self.emit_jump(Instruction::Jump(0));
+                }
+            }
+            
+            ASTNode::LabeledStmt { label, body, .. } => {
+                self.pending_labels.push(label.clone());
+                if body.is_loop() || matches!(**body, ASTNode::LabeledStmt { .. }) {
+                    // The loop picks up the pending labels itself
+                    self.compile_statement(body);
+                } else {
+                    // Any other labelled statement can only be broken out of
+                    let labels = std::mem::take(&mut self.pending_labels);
+                    self.loop_stack.push(LoopContext::new(labels, false, self.stack_depth));
+                    self.compile_statement(body);
+                    let context = self.loop_stack.pop().expect("labelled statement context");
+                    let end = self.chunk.instructions.len();
+                    for idx in context.break_jumps {
+                        self.patch_jump(idx, end);
+                    }
+                }
+            }
+            
+            ASTNode::FunctionExpr { .. } | ASTNode::ArrowFunction { .. } => {
+                self.compile_function_expr(node, "");
             }
             
             ASTNode::FunctionDecl { name, params, body, .. } => {
-                // For now, we'll skip function declarations in bytecode generation
-                // They would need to be compiled separately and stored
+                // Declare the name first so the body can call itself recursively
+                let binding = self.scope.declare_binding(name.clone());
+                let id = self.compile_function(name, params, body);
+                
+                self.chunk.emit(Instruction::MakeClosure(id));
+                self.emit_store(binding);
             }
         }
     }
@@ -191,6 +1282,18 @@ mod tests {
         assert_eq!(chunk.constants[0], Value::Number(42.0));
     }
     
+    #[test]
+    fn test_compile_string() {
+        let mut parser = Parser::new("\"hi\"".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions, vec![Instruction::LoadConst(0)]);
+        assert_eq!(chunk.constants[0], Value::String("hi".into()));
+    }
+    
     #[test]
     fn test_compile_binary_expr() {
         let mut parser = Parser::new("1 + 2".to_string());
@@ -217,6 +1320,410 @@ mod tests {
         assert!(matches!(chunk.instructions[chunk.instructions.len() - 1], Instruction::StoreLocal(0)));
     }
     
+    #[test]
+    fn test_compile_comparison() {
+        let mut parser = Parser::new("1 <= 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[chunk.instructions.len() - 1], Instruction::LessEqual);
+    }
+    
+    #[test]
+    fn test_compile_expression_statements_are_popped() {
+        let mut parser = Parser::new("1; 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // Only the final expression's value is kept
+        assert_eq!(chunk.instructions, vec![
+            Instruction::LoadConst(0),
+            Instruction::Pop,
+            Instruction::LoadConst(1),
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_function_decl() {
+        let mut parser = Parser::new("let g = 1; function add(a, b) { return a + b + g; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // The function is hoisted above `g`, which a call could reach before it is initialised
+        assert_eq!(chunk.instructions, vec![
+            Instruction::LoadHole,
+            Instruction::StoreLocal(0),
+            Instruction::MakeClosure(1),
+            Instruction::StoreLocal(1),
+            Instruction::LoadConst(0),
+            Instruction::StoreLocal(0),
+        ]);
+        assert_eq!(chunk.local_count, 2);
+        
+        let func = &gen.functions()[&1];
+        assert_eq!(func.name, "add");
+        assert_eq!(func.arity, 2);
+        assert_eq!(func.chunk.instructions, vec![
+            Instruction::LoadLocal(0),
+            Instruction::LoadLocal(1),
+            Instruction::Add,
+            Instruction::LoadGlobal(0),
+            Instruction::CheckInitialized(0),
+            Instruction::Add,
+            Instruction::Return,
+        ]);
+        assert_eq!(func.chunk.constants[0], Value::String("g".into()));
+    }
+    
+    #[test]
+    fn test_compile_nested_function_ids() {
+        let mut parser = Parser::new("function a() { function b() { return 1; } return 2; } function c() { return 3; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        gen.set_next_function_id(10);
+        gen.generate(&ast.root);
+        
+        let mut names: Vec<(FunctionId, &str)> = gen.functions().values()
+            .map(|f| (f.id, f.name.as_str()))
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![(10, "a"), (11, "b"), (12, "c")]);
+        assert_eq!(gen.next_function_id(), 13);
+    }
+    
+    #[test]
+    fn test_compile_closure_upvalues() {
+        let source = "function counter(start) { let n = 0; function next() { n += start; return n; } return next; }";
+        let mut parser = Parser::new(source.to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        gen.generate(&ast.root);
+        
+        // counter moves the parameter into its entry cell, and gives the
+        // block-scoped `n` a fresh cell when its body is entered
+        let counter = &gen.functions()[&1];
+        assert_eq!(counter.captures, Vec::<usize>::new());
+        assert_eq!(counter.cell_count, 1);
+        assert_eq!(counter.chunk.instructions[..5], [
+            Instruction::LoadLocal(0),
+            Instruction::StoreUpvalue(0),
+            Instruction::FreshCell(1),
+            Instruction::LoadHole,
+            Instruction::StoreUpvalue(1),
+        ]);
+        assert!(counter.chunk.instructions.contains(&Instruction::MakeClosure(2)));
+        
+        // next captures both cells of counter's frame, in name order
+        let next = &gen.functions()[&2];
+        assert_eq!(next.captures, vec![1, 0]);
+        assert_eq!(next.cell_count, 0);
+        assert_eq!(next.chunk.instructions[..4], [
+            Instruction::LoadUpvalue(0),
+            Instruction::CheckInitialized(0),
+            Instruction::LoadUpvalue(1),
+            Instruction::Add,
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_compound_assignment() {
+        let mut parser = Parser::new("let x = 1; x += 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[2..], [
+            Instruction::LoadLocal(0),
+            Instruction::LoadConst(1),
+            Instruction::Add,
+            Instruction::Dup,
+            Instruction::StoreLocal(0),
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_postfix_increment() {
+        let mut parser = Parser::new("let i = 0; i++".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[2..], [
+            Instruction::LoadLocal(0),
+            Instruction::ToNumber,
+            Instruction::Dup,
+            Instruction::LoadConst(1),
+            Instruction::Add,
+            Instruction::StoreLocal(0),
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_while_with_break() {
+        let mut parser = Parser::new("let x = true; while (x) { break; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[2..], [
+            Instruction::LoadLocal(0),
+            Instruction::JumpIfFalse(2),
+            Instruction::Jump(1),
+            Instruction::Jump(-4),
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_continue_targets_for_update() {
+        let mut parser = Parser::new("for (let i = 0; i < 3; i++) { continue; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // The continue jump lands on the update, right after itself
+        let continue_idx = chunk.instructions.iter()
+            .position(|inst| *inst == Instruction::Jump(0))
+            .unwrap();
+        assert_eq!(chunk.instructions[continue_idx + 1], Instruction::LoadLocal(0));
+    }
+    
+    #[test]
+    fn test_compile_unary() {
+        let mut parser = Parser::new("-1; let a = 2; -a; +a; !a".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // -1 folds to a constant
+        assert_eq!(chunk.instructions[0], Instruction::LoadConst(0));
+        assert_eq!(chunk.constants[0], Value::Number(-1.0));
+        assert!(chunk.instructions.contains(&Instruction::Negate));
+        assert!(chunk.instructions.contains(&Instruction::ToNumber));
+        assert_eq!(chunk.instructions.last(), Some(&Instruction::Not));
+    }
+    
+    #[test]
+    fn test_compile_logical_short_circuit() {
+        let cases = [
+            ("a && b", Instruction::JumpIfFalseOrPop(1)),
+            ("a || b", Instruction::JumpIfTrueOrPop(1)),
+            ("a ?? b", Instruction::JumpIfNotNullishOrPop(1)),
+        ];
+        
+        for (source, jump) in cases {
+            let source = format!("let a = 1; let b = 2; {}", source);
+            let mut parser = Parser::new(source);
+            let ast = parser.parse().unwrap();
+            
+            let mut gen = BytecodeGenerator::new(Scope::global());
+            let chunk = gen.generate(&ast.root);
+            
+            // The jump skips exactly the right operand
+            assert_eq!(chunk.instructions[4..], [
+                Instruction::LoadLocal(0),
+                jump,
+                Instruction::LoadLocal(1),
+            ]);
+        }
+    }
+    
+    #[test]
+    fn test_compile_object_and_member() {
+        let mut parser = Parser::new("let o = { a: 1 }; o.a += 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[..4], [
+            Instruction::LoadConst(0),
+            Instruction::LoadConst(1),
+            Instruction::CreateObject(1),
+            Instruction::StoreLocal(0),
+        ]);
+        assert_eq!(chunk.constants[0], Value::String("a".into()));
+        
+        // Compound assignment reads through a copy of the object and key
+        assert_eq!(chunk.instructions[4..], [
+            Instruction::LoadLocal(0),
+            Instruction::LoadConst(2),
+            Instruction::Dup2,
+            Instruction::GetProperty,
+            Instruction::LoadConst(3),
+            Instruction::Add,
+            Instruction::SetProperty,
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_array_method_call() {
+        let mut parser = Parser::new("[1, 2].push(3)".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        assert_eq!(chunk.instructions[2..], [
+            Instruction::CreateArray(2),
+            Instruction::LoadConst(2),
+            Instruction::LoadConst(3),
+            Instruction::CallMethod(1),
+        ]);
+        assert_eq!(chunk.constants[2], Value::String("push".into()));
+    }
+    
+    #[test]
+    fn test_compile_block_shadowing() {
+        let mut parser = Parser::new("let x = 1; { let x = 2; x; } { let y = 3; } x".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // The inner `x` gets its own slot, which the sibling block's `y` reuses
+        assert_eq!(chunk.instructions, vec![
+            Instruction::LoadConst(0),
+            Instruction::StoreLocal(0),
+            Instruction::LoadConst(1),
+            Instruction::StoreLocal(1),
+            Instruction::LoadLocal(1),
+            Instruction::Pop,
+            Instruction::LoadConst(2),
+            Instruction::StoreLocal(1),
+            Instruction::LoadLocal(0),
+        ]);
+        assert_eq!(chunk.local_count, 2);
+    }
+    
+    #[test]
+    fn test_compile_per_iteration_binding() {
+        let source = "function f() { for (let i = 0; i < 2; i++) { function g() { return i; } } }";
+        let mut parser = Parser::new(source.to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        gen.generate(&ast.root);
+        
+        // The captured `i` is copied into a fresh cell before the first
+        // iteration and before each update
+        let f = &gen.functions()[&1];
+        assert_eq!(f.cell_count, 0);
+        let fresh = f.chunk.instructions.iter()
+            .filter(|instruction| **instruction == Instruction::FreshCell(0))
+            .count();
+        assert_eq!(fresh, 3);
+        assert_eq!(gen.functions()[&2].captures, vec![0]);
+    }
+    
+    #[test]
+    fn test_compile_unresolved_identifier() {
+        let mut parser = Parser::new("missing + 1; other = 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // Unresolved names become by-name global accesses carrying their span
+        assert_eq!(chunk.instructions[0], Instruction::LoadGlobalName(0, Span::new(0, 7)));
+        assert_eq!(chunk.constants[0], Value::String("missing".into()));
+        assert!(chunk.instructions.contains(&Instruction::StoreGlobalName(2, Span::new(13, 18))));
+        assert!(gen.errors().is_empty());
+        
+        // Early-error mode reports each of them at compile time
+        let source = "let known = 1; function f() { return known + gone; } f(lost)";
+        let ast = Parser::new(source.to_string()).parse().unwrap();
+        let mut gen = BytecodeGenerator::new(Scope::global()).with_early_errors(true);
+        gen.generate(&ast.root);
+        assert_eq!(gen.errors(), [
+            CompileError::UnresolvedReference { name: "gone".to_string(), span: Span::new(45, 49) },
+            CompileError::UnresolvedReference { name: "lost".to_string(), span: Span::new(55, 59) },
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_function_expressions() {
+        let mut parser = Parser::new("let sq = x => x * x; let f = function fact(n) { return fact; };".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        assert!(chunk.instructions.contains(&Instruction::MakeClosure(1)));
+        assert!(chunk.instructions.contains(&Instruction::MakeClosure(2)));
+        
+        // The arrow takes the name of its variable and returns its body's value
+        let sq = &gen.functions()[&1];
+        assert_eq!(sq.name, "sq");
+        assert_eq!(sq.chunk.instructions, vec![
+            Instruction::LoadLocal(0),
+            Instruction::LoadLocal(0),
+            Instruction::Mul,
+            Instruction::Return,
+        ]);
+        
+        // A named expression keeps its own name and sees itself through a cell
+        let fact = &gen.functions()[&2];
+        assert_eq!(fact.name, "fact");
+        assert_eq!(fact.captures.len(), 1);
+    }
+    
+    #[test]
+    fn test_compile_spread_call() {
+        let mut parser = Parser::new("f(1, ...a, 2, 3)".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // Plain arguments after a spread are gathered into an array of their own
+        assert_eq!(chunk.instructions[2..], [
+            Instruction::CreateArray(1),
+            Instruction::LoadGlobalName(2, Span::new(8, 9)),
+            Instruction::AppendSpread,
+            Instruction::LoadConst(3),
+            Instruction::LoadConst(4),
+            Instruction::CreateArray(2),
+            Instruction::AppendSpread,
+            Instruction::CallWithSpread,
+        ]);
+    }
+    
+    #[test]
+    fn test_compile_default_and_rest_params() {
+        let mut parser = Parser::new("function f(a, b = 2, ...c) { return arguments; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        gen.generate(&ast.root);
+        
+        let func = &gen.functions()[&1];
+        assert_eq!(func.arity, 2);
+        assert!(func.rest && func.arguments);
+        // `b` is replaced only when it is undefined; `arguments` follows the rest parameter
+        assert_eq!(func.chunk.instructions, vec![
+            Instruction::LoadLocal(1),
+            Instruction::LoadConst(0),
+            Instruction::StrictEqual,
+            Instruction::JumpIfFalse(2),
+            Instruction::LoadConst(1),
+            Instruction::StoreLocal(1),
+            Instruction::LoadLocal(3),
+            Instruction::Return,
+        ]);
+    }
+    
     #[test]
     fn test_compile_call_expr() {
         let mut parser = Parser::new("foo(1, 2)".to_string());
@@ -228,4 +1735,64 @@ mod tests {
         // Should end with Call(2)
         assert!(matches!(chunk.instructions[chunk.instructions.len() - 1], Instruction::Call(2)));
     }
+    
+    #[test]
+    fn test_compile_try_catch_handler_table() {
+        let mut parser = Parser::new("try { throw 1; } catch (e) { e; }".to_string());
+        let ast = parser.parse().unwrap();
+        
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // The try block jumps over the catch clause, which stores the exception
+        assert_eq!(chunk.instructions[..4], [
+            Instruction::LoadConst(0),
+            Instruction::Throw,
+            Instruction::Jump(3),
+            Instruction::StoreLocal(0),
+        ]);
+        assert_eq!(chunk.handlers, [ExceptionHandler { start: 0, end: 2, target: 3, stack_depth: 0 }]);
+    }
+    
+    #[test]
+    fn test_compile_finally_on_every_exit() {
+        let source = "function f() { while (true) { try { h(); break; } finally { g(); } } return 1; }";
+        let ast = Parser::new(source.to_string()).parse().unwrap();
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        gen.generate(&ast.root);
+        let chunk = &gen.functions()[&1].chunk;
+        
+        // `h()`, then `g()` inlined after the break, on normal completion and before rethrowing
+        let calls: Vec<usize> = chunk.instructions.iter()
+            .enumerate()
+            .filter(|(_, i)| **i == Instruction::Call(0))
+            .map(|(pc, _)| pc)
+            .collect();
+        assert_eq!(calls.len(), 4);
+        
+        // Only `h()` is protected; the copy run by the break is outside the range
+        assert_eq!(chunk.handlers.len(), 1);
+        let handler = chunk.handlers[0];
+        assert!(handler.start < calls[0] && calls[0] < handler.end);
+        assert!(handler.end <= calls[1]);
+        assert_eq!(chunk.instructions[handler.target..].iter().filter(|i| **i == Instruction::Rethrow).count(), 1);
+    }
+    
+    #[test]
+    fn test_compile_records_positions() {
+        let source = "let a = 1;\nprint(a.b + 2);";
+        let ast = Parser::new(source.to_string()).parse().unwrap();
+        let mut gen = BytecodeGenerator::new(Scope::global());
+        let chunk = gen.generate(&ast.root);
+        
+        // Each instruction maps to the innermost node it was emitted for
+        let at = |instruction: Instruction| {
+            let index = chunk.instructions.iter().position(|i| *i == instruction).unwrap();
+            chunk.position_at(index)
+        };
+        assert_eq!(at(Instruction::StoreLocal(0)), Some(Span::new(0, 9)));
+        assert_eq!(at(Instruction::GetProperty), Some(Span::new(17, 20)));
+        assert_eq!(at(Instruction::Add), Some(Span::new(17, 24)));
+        assert_eq!(at(Instruction::Print), Some(Span::new(11, 25)));
+    }
 }
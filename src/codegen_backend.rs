@@ -77,6 +77,38 @@ impl CodeGenerator {
                     compiled.code.extend_from_slice(&value.to_le_bytes());
                 }
                 
+                IRNode::HeapConstant { index, .. } => {
+                    // Mock: encode constant pool load
+                    compiled.code.push(0x02); // LOAD_HEAP_CONST opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::Hole { .. } => {
+                    // Mock: encode the hole marker
+                    compiled.code.push(0x03); // LOAD_HOLE opcode
+                }
+                
+                IRNode::CheckHole { .. } => {
+                    // Mock: encode uninitialised binding check
+                    compiled.code.push(0x51); // CHECK_HOLE opcode
+                }
+                
+                IRNode::Throw { .. } => {
+                    // Mock: encode throw
+                    compiled.code.push(0x71); // THROW opcode
+                }
+                
+                IRNode::Rethrow { .. } => {
+                    // Mock: encode rethrow of a pending exception
+                    compiled.code.push(0x72); // RETHROW opcode
+                }
+                
+                IRNode::ThrowConstAssignment { name_index, .. } => {
+                    // Mock: encode const assignment failure
+                    compiled.code.push(0x70); // THROW_CONST_ASSIGNMENT opcode
+                    compiled.code.push(*name_index as u8);
+                }
+                
                 IRNode::Add { .. } => {
                     // Mock: encode addition
                     compiled.code.push(0x10); // ADD opcode
@@ -97,6 +129,33 @@ impl CodeGenerator {
                     compiled.code.push(0x13); // DIV opcode
                 }
                 
+                IRNode::Compare { op, .. } => {
+                    // Mock: encode comparison with its operator
+                    compiled.code.push(0x14); // COMPARE opcode
+                    compiled.code.push(*op as u8);
+                }
+                
+                IRNode::Negate { .. } => {
+                    // Mock: encode numeric negation
+                    compiled.code.push(0x15); // NEG opcode
+                }
+                
+                IRNode::Not { .. } => {
+                    // Mock: encode logical negation
+                    compiled.code.push(0x16); // NOT opcode
+                }
+                
+                IRNode::Branch { .. } => {
+                    // Mock: encode conditional branch
+                    compiled.code.push(0x41); // BRANCH_IF_FALSE opcode
+                }
+                
+                IRNode::ShortCircuit { op, .. } => {
+                    // Mock: encode operand selection with its operator
+                    compiled.code.push(0x42); // SELECT opcode
+                    compiled.code.push(*op as u8);
+                }
+                
                 IRNode::LoadLocal { index, .. } => {
                     // Mock: encode local load
                     compiled.code.push(0x20); // LOAD_LOCAL opcode
@@ -109,12 +168,103 @@ impl CodeGenerator {
                     compiled.code.push(*index as u8);
                 }
                 
+                IRNode::LoadGlobal { index, .. } => {
+                    // Mock: encode global load
+                    compiled.code.push(0x22); // LOAD_GLOBAL opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::StoreGlobal { index, .. } => {
+                    // Mock: encode global store
+                    compiled.code.push(0x23); // STORE_GLOBAL opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::LoadGlobalName { name_index, .. } => {
+                    // Mock: encode global load by name
+                    compiled.code.push(0x27); // LOAD_GLOBAL_NAME opcode
+                    compiled.code.push(*name_index as u8);
+                }
+                
+                IRNode::StoreGlobalName { name_index, .. } => {
+                    // Mock: encode global store by name
+                    compiled.code.push(0x28); // STORE_GLOBAL_NAME opcode
+                    compiled.code.push(*name_index as u8);
+                }
+                
+                IRNode::LoadUpvalue { index, .. } => {
+                    // Mock: encode closure cell load
+                    compiled.code.push(0x24); // LOAD_UPVALUE opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::StoreUpvalue { index, .. } => {
+                    // Mock: encode closure cell store
+                    compiled.code.push(0x25); // STORE_UPVALUE opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::FreshCell { index, .. } => {
+                    // Mock: encode closure cell renewal
+                    compiled.code.push(0x26); // FRESH_CELL opcode
+                    compiled.code.push(*index as u8);
+                }
+                
+                IRNode::MakeClosure { func_id, .. } => {
+                    // Mock: encode closure creation
+                    compiled.code.push(0x31); // MAKE_CLOSURE opcode
+                    compiled.code.extend_from_slice(&(*func_id as u32).to_le_bytes());
+                }
+                
+                IRNode::CreateObject { entries, .. } => {
+                    // Mock: encode object creation with its property count
+                    compiled.code.push(0x60); // CREATE_OBJECT opcode
+                    compiled.code.push((entries.len() / 2) as u8);
+                }
+                
+                IRNode::CreateArray { elements, .. } => {
+                    // Mock: encode array creation with its length
+                    compiled.code.push(0x63); // CREATE_ARRAY opcode
+                    compiled.code.push(elements.len() as u8);
+                }
+                
+                IRNode::AppendSpread { .. } => {
+                    // Mock: encode spreading into an array
+                    compiled.code.push(0x64); // APPEND_SPREAD opcode
+                }
+                
+                IRNode::GetProperty { .. } => {
+                    // Mock: encode property load
+                    compiled.code.push(0x61); // GET_PROPERTY opcode
+                }
+                
+                IRNode::SetProperty { .. } => {
+                    // Mock: encode property store
+                    compiled.code.push(0x62); // SET_PROPERTY opcode
+                }
+                
                 IRNode::Call { args, .. } => {
                     // Mock: encode function call
                     compiled.code.push(0x30); // CALL opcode
                     compiled.code.push(args.len() as u8);
                 }
                 
+                IRNode::CallMethod { args, .. } => {
+                    // Mock: encode method call
+                    compiled.code.push(0x32); // CALL_METHOD opcode
+                    compiled.code.push(args.len() as u8);
+                }
+                
+                IRNode::CallWithSpread { .. } => {
+                    // Mock: encode function call with spread arguments
+                    compiled.code.push(0x33); // CALL_WITH_SPREAD opcode
+                }
+                
+                IRNode::CallMethodWithSpread { .. } => {
+                    // Mock: encode method call with spread arguments
+                    compiled.code.push(0x34); // CALL_METHOD_WITH_SPREAD opcode
+                }
+                
                 IRNode::Return { .. } => {
                     // Mock: encode return
                     compiled.code.push(0x40); // RETURN opcode
@@ -125,6 +275,7 @@ impl CodeGenerator {
                     compiled.code.push(0x50); // TYPE_GUARD opcode
                     compiled.code.push(match expected_type {
                         crate::ir::Type::Number => 0x01,
+                        crate::ir::Type::Boolean => 0x02,
                         crate::ir::Type::Unknown => 0x00,
                     });
                 }
@@ -170,7 +321,7 @@ mod tests {
         
         let compiled = codegen.generate(&ir, 0);
         
-        assert!(compiled.code.len() > 0);
+        assert!(!compiled.code.is_empty());
         assert_eq!(compiled.code[0], 0x01); // LOAD_CONST opcode
     }
     
@@ -186,7 +337,7 @@ mod tests {
         let compiled = codegen.generate(&ir, 0);
         
         // Should have code for two constants and one add
-        assert!(compiled.code.len() > 0);
+        assert!(!compiled.code.is_empty());
         assert!(compiled.code.contains(&0x10)); // ADD opcode
     }
     
@@ -203,6 +354,54 @@ mod tests {
         assert!(compiled.code.contains(&0x50)); // TYPE_GUARD opcode
     }
     
+    #[test]
+    fn test_generate_compare() {
+        let codegen = CodeGenerator::mock();
+        let mut ir = IR::new();
+        
+        let left = ir.add_constant(1.0);
+        let right = ir.add_constant(2.0);
+        let cmp = ir.add_compare(crate::ir::CompareOp::Less, left, right);
+        ir.add_branch(cmp);
+        
+        let compiled = codegen.generate(&ir, 0);
+        
+        assert!(compiled.code.contains(&0x14)); // COMPARE opcode
+        assert!(compiled.code.contains(&0x41)); // BRANCH_IF_FALSE opcode
+    }
+    
+    #[test]
+    fn test_generate_unary_and_short_circuit() {
+        let codegen = CodeGenerator::mock();
+        let mut ir = IR::new();
+        
+        let left = ir.add_load_local(0);
+        let neg = ir.add_negate(left);
+        let not = ir.add_not(left);
+        ir.add_short_circuit(crate::ir::ShortCircuitOp::Nullish, neg, not);
+        
+        let compiled = codegen.generate(&ir, 0);
+        
+        assert!(compiled.code.contains(&0x15)); // NEG opcode
+        assert!(compiled.code.contains(&0x16)); // NOT opcode
+        assert!(compiled.code.ends_with(&[0x42, crate::ir::ShortCircuitOp::Nullish as u8]));
+    }
+    
+    #[test]
+    fn test_generate_upvalues() {
+        let codegen = CodeGenerator::mock();
+        let mut ir = IR::new();
+        
+        let closure = ir.add_make_closure(3);
+        ir.add_store_upvalue(1, closure);
+        ir.add_load_upvalue(1);
+        ir.add_fresh_cell(1);
+        
+        let compiled = codegen.generate(&ir, 0);
+        
+        assert_eq!(compiled.code, vec![0x31, 3, 0, 0, 0, 0x25, 1, 0x24, 1, 0x26, 1]);
+    }
+    
     #[test]
     fn test_compiled_function() {
         let func = CompiledFunction::new(5);
@@ -127,11 +127,7 @@ pub struct DeoptState {
 impl DeoptState {
     /// Check if type guard should trigger deoptimization
     pub fn check_type_guard(value: &Value, expected_type: &str) -> Option<DeoptReason> {
-        let actual_type = match value {
-            Value::Number(_) => "number",
-            Value::Function(_) => "function",
-            Value::Undefined => "undefined",
-        };
+        let actual_type = value.type_name();
         
         if actual_type != expected_type {
             Some(DeoptReason::TypeGuardFailed {
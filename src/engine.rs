@@ -1,16 +1,19 @@
 // V8-RS Engine - Main coordinator
 
+use crate::ast::DeclKind;
 use crate::bytecode::BytecodeChunk;
 use crate::codegen::BytecodeGenerator;
 use crate::codegen_backend::{CodeGenerator, CompiledFunction};
-use crate::deopt::{DeoptInfo, DeoptManager, DeoptState};
-use crate::error::{Error, ParseError, RuntimeError};
+use crate::deopt::{DeoptInfo, DeoptManager};
+use crate::error::{CompileError, Error, ParseError, RuntimeError, StackFrame};
+use crate::heap::HeapStats;
 use crate::interpreter::Ignition;
 use crate::parser::Parser;
 use crate::profiler::HotspotProfiler;
 use crate::scope::Scope;
+use crate::source_map::SourceMap;
 use crate::turbofan::TurboFan;
-use crate::types::{FunctionId, Value};
+use crate::types::{FunctionId, Span, Value};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -18,19 +21,30 @@ use std::rc::Rc;
 /// Main engine that coordinates all components
 pub struct Engine {
     interpreter: Ignition,
+    /// Top-level declarations of every script run so far
     global_scope: Scope,
     profiler: Rc<RefCell<HotspotProfiler>>,
     jit: TurboFan,
     codegen: CodeGenerator,
     deopt_manager: DeoptManager,
     compiled_functions: HashMap<FunctionId, CompiledFunction>,
+    /// First function ID handed to the next bytecode generator
+    next_function_id: FunctionId,
+    /// Whether unresolved names are compile errors rather than runtime ReferenceErrors
+    early_errors: bool,
+    /// Source of the script each function was compiled from
+    sources: HashMap<FunctionId, Rc<SourceMap>>,
+    /// JavaScript stack of the last runtime error
+    stack_trace: Vec<StackFrame>,
+    /// Source of the code that raised the last runtime error
+    error_source: Option<Rc<SourceMap>>,
 }
 
 impl Engine {
     /// Create a new engine instance
     pub fn new() -> Self {
         let profiler = Rc::new(RefCell::new(HotspotProfiler::default()));
-        Self {
+        let mut engine = Self {
             interpreter: Ignition::with_profiler(profiler.clone()),
             global_scope: Scope::global(),
             profiler,
@@ -38,7 +52,30 @@ impl Engine {
             codegen: CodeGenerator::mock(),
             deopt_manager: DeoptManager::new(),
             compiled_functions: HashMap::new(),
-        }
+            next_function_id: 1,
+            early_errors: false,
+            sources: HashMap::new(),
+            stack_trace: Vec::new(),
+            error_source: None,
+        };
+        engine.define_constant("undefined", Value::Undefined);
+        engine.define_constant("NaN", Value::Number(f64::NAN));
+        engine.define_constant("Infinity", Value::Number(f64::INFINITY));
+        engine
+    }
+    
+    /// Predefine a read-only global variable
+    fn define_constant(&mut self, name: &str, value: Value) {
+        let slot = self.global_scope.declare(name.to_string());
+        self.global_scope.set_kind(name.to_string(), DeclKind::Const);
+        self.interpreter.define_global(name.to_string(), slot);
+        self.interpreter.set_global(slot, value);
+    }
+    
+    /// Report names no scope declares when a script is compiled, instead of
+    /// when the reference is evaluated
+    pub fn set_early_errors(&mut self, enabled: bool) {
+        self.early_errors = enabled;
     }
     
     /// Get a reference to the profiler
@@ -85,18 +122,89 @@ impl Engine {
         Ok(())
     }
     
+    /// Reclaim unreachable heap values, returning how many were freed
+    pub fn collect_garbage(&mut self) -> usize {
+        self.interpreter.collect_garbage()
+    }
+    
+    /// Heap-size statistics
+    pub fn heap_stats(&self) -> HeapStats {
+        self.interpreter.heap_stats()
+    }
+    
     /// Execute JavaScript source code
     pub fn execute(&mut self, source: &str) -> Result<Value, Error> {
+        self.execute_named("<anonymous>", source)
+    }
+    
+    /// Execute JavaScript source code, naming it `name` in stack traces
+    pub fn execute_named(&mut self, name: &str, source: &str) -> Result<Value, Error> {
+        self.stack_trace.clear();
+        self.error_source = None;
+        let source_map = Rc::new(SourceMap::new(name, source));
+        
         // Parse source code to AST
         let ast = self.parse(source)?;
         
         // Generate bytecode from AST
-        let bytecode = self.generate_bytecode(&ast);
+        let bytecode = self.generate_bytecode(&ast, &source_map)?;
         
-        // Interpret bytecode
-        let result = self.interpret(bytecode)?;
+        // Interpret bytecode, keeping the top-level variables for the next script
+        let result = self.interpret(bytecode);
+        self.interpreter.retain_globals(self.global_scope.declared_count());
+        if result.is_err() {
+            self.stack_trace = self.resolve_stack_trace(&source_map);
+            self.error_source = self.interpreter.stack_trace()
+                .first()
+                .map(|&(func_id, _)| self.source_of(func_id, &source_map).clone());
+        }
         
-        Ok(result)
+        Ok(result?)
+    }
+    
+    /// JavaScript stack of the last runtime error, innermost call first; empty
+    /// if the last execution did not fail at runtime
+    pub fn stack_trace(&self) -> &[StackFrame] {
+        &self.stack_trace
+    }
+    
+    /// Source the last runtime error's span refers to, which is the script or
+    /// function that raised it; `None` if the last execution did not fail at runtime
+    pub fn error_source(&self) -> Option<&SourceMap> {
+        self.error_source.as_deref()
+    }
+    
+    /// Source of the function `func_id`; top-level code belongs to `script`
+    fn source_of<'a>(&'a self, func_id: FunctionId, script: &'a Rc<SourceMap>) -> &'a Rc<SourceMap> {
+        match func_id {
+            0 => script,
+            id => self.sources.get(&id).unwrap_or(script),
+        }
+    }
+    
+    /// Turn the interpreter's stack trace into names and line/column positions.
+    /// Top-level code belongs to the script that was running.
+    fn resolve_stack_trace(&self, script: &Rc<SourceMap>) -> Vec<StackFrame> {
+        self.interpreter.stack_trace()
+            .iter()
+            .map(|&(func_id, span)| {
+                let function = match func_id {
+                    0 => None,
+                    id => self.interpreter.function(id).map(|info| match info.name.as_str() {
+                        "" => "<anonymous>".to_string(),
+                        name => name.to_string(),
+                    }),
+                };
+                let source = self.source_of(func_id, script);
+                let position = source.line_col(span.unwrap_or(Span::new(0, 0)).start);
+                StackFrame {
+                    function,
+                    file: source.name().to_string(),
+                    line: position.line,
+                    column: position.column,
+                }
+            })
+            .collect()
     }
     
     /// Parse source code into AST
@@ -105,10 +213,32 @@ impl Engine {
         parser.parse()
     }
     
-    /// Generate bytecode from AST
-    fn generate_bytecode(&mut self, ast: &crate::ast::AST) -> BytecodeChunk {
-        let mut generator = BytecodeGenerator::new(self.global_scope.clone());
-        generator.generate(&ast.root)
+    /// Generate bytecode from AST, registering any functions and globals it declares
+    fn generate_bytecode(&mut self, ast: &crate::ast::AST, source_map: &Rc<SourceMap>) -> Result<BytecodeChunk, CompileError> {
+        let mut generator = BytecodeGenerator::new(self.global_scope.clone())
+            .with_early_errors(self.early_errors);
+        generator.set_next_function_id(self.next_function_id);
+        let chunk = generator.generate(&ast.root);
+        if let Some(err) = generator.errors().first() {
+            return Err(err.clone());
+        }
+        
+        // A later script cannot know whether this one reached its declarations
+        self.global_scope = generator.scope().clone();
+        self.global_scope.forget_initialized();
+        self.next_function_id = generator.next_function_id();
+        for (id, function) in generator.take_functions() {
+            self.sources.insert(id, source_map.clone());
+            self.interpreter.register_function(function);
+        }
+        for (name, slot) in self.global_scope.variables() {
+            if self.global_scope.kind(name).is_some_and(DeclKind::is_lexical) {
+                self.interpreter.define_uninitialized_global(slot);
+            }
+            self.interpreter.define_global(name.to_string(), slot);
+        }
+        
+        Ok(chunk)
     }
     
     /// Interpret bytecode
@@ -129,8 +259,7 @@ mod tests {
     
     #[test]
     fn test_engine_creation() {
-        let engine = Engine::new();
-        assert!(true); // Engine created successfully
+        let _engine = Engine::new(); // Engine created successfully
     }
     
     #[test]
@@ -162,6 +291,33 @@ mod tests {
         assert!(matches!(result, Value::Number(_) | Value::Undefined));
     }
     
+    #[test]
+    fn test_execute_function_call() {
+        let mut engine = Engine::new();
+        let result = engine.execute("function add(a, b) { return a + b; } add(2, 3)").unwrap();
+        assert_eq!(result, Value::Number(5.0));
+    }
+    
+    #[test]
+    fn test_function_calls_are_profiled() {
+        let mut engine = Engine::new();
+        engine.execute("function one() { return 1; } one(); one(); one()").unwrap();
+        
+        // Script is function 0, `one` is the first declared function
+        let profiler = engine.profiler();
+        assert_eq!(profiler.borrow().get_count(0), 1);
+        assert_eq!(profiler.borrow().get_count(1), 3);
+    }
+    
+    #[test]
+    fn test_function_ids_unique_across_executions() {
+        let mut engine = Engine::new();
+        engine.execute("function a() { return 1; }").unwrap();
+        let result = engine.execute("function b() { return 2; } b()").unwrap();
+        assert_eq!(result, Value::Number(2.0));
+        assert_eq!(engine.profiler().borrow().get_count(2), 1);
+    }
+    
     #[test]
     fn test_execute_parse_error() {
         let mut engine = Engine::new();
@@ -175,4 +331,66 @@ mod tests {
         let result = engine.execute("10 / 0");
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_stack_trace_names_functions_and_positions() {
+        let mut engine = Engine::new();
+        engine.execute_named("lib.js", "function fail() {\n  return null.x;\n}").unwrap();
+        let source = "function outer() { return fail(); }\nouter()";
+        assert!(engine.execute_named("main.js", source).is_err());
+        
+        let trace: Vec<String> = engine.stack_trace().iter().map(StackFrame::to_string).collect();
+        assert_eq!(trace, [
+            "at fail (lib.js:2:10)",
+            "at outer (main.js:1:27)",
+            "at main.js:2:1",
+        ]);
+        
+        engine.execute("1").unwrap();
+        assert!(engine.stack_trace().is_empty());
+    }
+    
+    #[test]
+    fn test_error_source_is_the_raising_script() {
+        let mut engine = Engine::new();
+        engine.execute_named("lib.js", "function fail() {\n  return null.x;\n}").unwrap();
+        let err = engine.execute_named("<repl>", "fail()").unwrap_err();
+        let source = engine.error_source().expect("source of the failed script");
+        assert_eq!(source.name(), "lib.js");
+        assert!(source.render_error(&err).contains("2 |   return null.x;"));
+        
+        assert!(engine.execute_named("<repl>", "null.y").is_err());
+        assert_eq!(engine.error_source().map(SourceMap::name), Some("<repl>"));
+        engine.execute("1").unwrap();
+        assert!(engine.error_source().is_none());
+    }
+    
+    #[test]
+    fn test_finally_rethrows_the_original_error() {
+        let mut engine = Engine::new();
+        let source = "function fail() {\n  try {\n    return null.x;\n  } finally {\n    try { throw 1; } catch (e) {}\n  }\n}\nfail()";
+        let err = engine.execute_named("app.js", source).unwrap_err();
+        
+        let Error::RuntimeError(err @ RuntimeError::TypeError { .. }) = err else {
+            panic!("expected the TypeError, got {err}");
+        };
+        assert_eq!(err.span(), Some(Span::new(37, 43)));
+        let trace: Vec<String> = engine.stack_trace().iter().map(StackFrame::to_string).collect();
+        assert_eq!(trace, ["at fail (app.js:3:12)", "at app.js:8:1"]);
+    }
+    
+    #[test]
+    fn test_global_binding_stays_uninitialized_after_throw() {
+        let mut engine = Engine::new();
+        engine.execute("function boom() { throw 1; }\nfunction g() { return y; }").unwrap();
+        assert!(engine.execute("let y = boom();").is_err());
+        
+        for source in ["y", "[y]", "g()"] {
+            let err = engine.execute(source).unwrap_err();
+            assert!(err.to_string().contains("before initialization"), "{source}: {err}");
+        }
+        
+        engine.execute("let z = 1;").unwrap();
+        assert_eq!(engine.execute("z + 1").unwrap(), Value::Number(2.0));
+    }
 }
@@ -1,6 +1,6 @@
 // Error types for V8-RS
 
-use crate::types::Span;
+use crate::types::{Span, Value};
 use std::fmt;
 
 /// Top-level error type for the engine
@@ -32,22 +32,68 @@ pub enum ParseError {
     },
 }
 
-/// Errors that occur during runtime execution
+/// Errors that occur during runtime execution.
+///
+/// The optional spans locate the code that raised the error; Ignition fills
+/// them in from the chunk's position table.
 #[derive(Debug, Clone, PartialEq)]
 pub enum RuntimeError {
     /// Variable not defined
     UndefinedVariable {
         name: String,
+        span: Option<Span>,
+    },
+    /// Reference to a name no scope or global declares
+    ReferenceError {
+        name: String,
+        span: Span,
+    },
+    /// `let`/`const` variable read or written before its declaration ran
+    UninitializedVariable {
+        name: String,
+        span: Option<Span>,
     },
     /// Type mismatch
     TypeError {
         expected: String,
         found: String,
+        span: Option<Span>,
+    },
+    /// Assignment to a `const` variable
+    ConstAssignment {
+        name: String,
+        span: Option<Span>,
     },
     /// Stack overflow
-    StackOverflow,
+    StackOverflow {
+        span: Option<Span>,
+    },
     /// Division by zero
-    DivisionByZero,
+    DivisionByZero {
+        span: Option<Span>,
+    },
+    /// Array `length` set to a value that is not a valid array length
+    InvalidArrayLength {
+        span: Option<Span>,
+    },
+    /// Spread call with more arguments than a call can take
+    TooManyArguments {
+        span: Option<Span>,
+    },
+    /// String conversion or concatenation that would exceed the longest string
+    InvalidStringLength {
+        span: Option<Span>,
+    },
+    /// Value thrown by a `throw` statement, or an error value rethrown by a script
+    Thrown {
+        value: Value,
+        span: Option<Span>,
+    },
+    /// Malformed bytecode, such as an operand stack underflow. This is an
+    /// engine bug rather than a JavaScript error, so scripts cannot catch it.
+    Internal {
+        message: String,
+    },
 }
 
 /// Errors that occur during JIT compilation
@@ -61,9 +107,175 @@ pub enum CompileError {
     OptimizationFailed {
         reason: String,
     },
+    /// Name that does not resolve to any declaration, reported in early-error mode
+    UnresolvedReference {
+        name: String,
+        span: Span,
+    },
+}
+
+/// One call in the JavaScript stack of a runtime error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Name of the running function, `<anonymous>` if it has none, or `None`
+    /// for top-level script code
+    pub function: Option<String>,
+    pub file: String,
+    /// 1-based line of the code that was running
+    pub line: usize,
+    /// 1-based column of the code that was running
+    pub column: usize,
+}
+
+// Messages and locations, shared by `Display` and the source-snippet renderer
+
+impl Error {
+    /// The error's description, without its location
+    pub fn message(&self) -> String {
+        match self {
+            Error::ParseError(e) => format!("Parse error: {}", e.message()),
+            Error::RuntimeError(e) => format!("Runtime error: {}", e.message()),
+            Error::CompileError(e) => format!("Compile error: {}", e.message()),
+        }
+    }
+    
+    /// Where in the source the error was found, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::ParseError(e) => e.span(),
+            Error::RuntimeError(e) => e.span(),
+            Error::CompileError(e) => e.span(),
+        }
+    }
+}
+
+impl ParseError {
+    /// The error's description, without its location
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken { expected, found, .. } => {
+                format!("Expected '{}', found '{}'", expected, found)
+            }
+            ParseError::UnexpectedEOF => "Unexpected end of file".to_string(),
+            ParseError::InvalidSyntax { message, .. } => message.clone(),
+        }
+    }
+    
+    /// Where in the source the error was found, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::UnexpectedToken { span, .. } | ParseError::InvalidSyntax { span, .. } => Some(*span),
+            ParseError::UnexpectedEOF => None,
+        }
+    }
+}
+
+impl RuntimeError {
+    /// The error's description, without its location
+    pub fn message(&self) -> String {
+        match self {
+            RuntimeError::UndefinedVariable { name, .. } => format!("Undefined variable: {}", name),
+            RuntimeError::ReferenceError { name, .. } => format!("ReferenceError: {} is not defined", name),
+            RuntimeError::UninitializedVariable { name, .. } => {
+                format!("Cannot access '{}' before initialization", name)
+            }
+            RuntimeError::TypeError { expected, found, .. } => {
+                format!("Type error: expected {}, found {}", expected, found)
+            }
+            RuntimeError::ConstAssignment { name, .. } => format!("Assignment to constant variable '{}'", name),
+            RuntimeError::StackOverflow { .. } => "Stack overflow".to_string(),
+            RuntimeError::DivisionByZero { .. } => "Division by zero".to_string(),
+            RuntimeError::InvalidArrayLength { .. } => "RangeError: Invalid array length".to_string(),
+            RuntimeError::TooManyArguments { .. } => "RangeError: Too many arguments in function call".to_string(),
+            RuntimeError::InvalidStringLength { .. } => "RangeError: Invalid string length".to_string(),
+            RuntimeError::Thrown { value, .. } => format!("Uncaught {}", describe_thrown(value)),
+            RuntimeError::Internal { message } => format!("Internal error: {}", message),
+        }
+    }
+    
+    /// Where in the source the error was raised, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RuntimeError::ReferenceError { span, .. } => Some(*span),
+            RuntimeError::UndefinedVariable { span, .. }
+            | RuntimeError::UninitializedVariable { span, .. }
+            | RuntimeError::TypeError { span, .. }
+            | RuntimeError::ConstAssignment { span, .. }
+            | RuntimeError::StackOverflow { span }
+            | RuntimeError::DivisionByZero { span }
+            | RuntimeError::InvalidArrayLength { span }
+            | RuntimeError::TooManyArguments { span }
+            | RuntimeError::InvalidStringLength { span }
+            | RuntimeError::Thrown { span, .. } => *span,
+            RuntimeError::Internal { .. } => None,
+        }
+    }
+    
+    /// Whether a `catch` clause can receive the error
+    pub fn is_catchable(&self) -> bool {
+        !matches!(self, RuntimeError::Internal { .. })
+    }
+    
+    /// Locate the error at `span`, unless it already has a location
+    pub fn with_span(mut self, location: Span) -> Self {
+        match &mut self {
+            RuntimeError::ReferenceError { .. } | RuntimeError::Internal { .. } => {}
+            RuntimeError::UndefinedVariable { span, .. }
+            | RuntimeError::UninitializedVariable { span, .. }
+            | RuntimeError::TypeError { span, .. }
+            | RuntimeError::ConstAssignment { span, .. }
+            | RuntimeError::StackOverflow { span }
+            | RuntimeError::DivisionByZero { span }
+            | RuntimeError::InvalidArrayLength { span }
+            | RuntimeError::TooManyArguments { span }
+            | RuntimeError::InvalidStringLength { span }
+            | RuntimeError::Thrown { span, .. } => {
+                span.get_or_insert(location);
+            }
+        }
+        self
+    }
 }
 
-// Display implementations for better error messages
+impl CompileError {
+    /// The error's description, without its location
+    pub fn message(&self) -> String {
+        match self {
+            CompileError::UnsupportedFeature { feature } => format!("Unsupported feature: {}", feature),
+            CompileError::OptimizationFailed { reason } => format!("Optimization failed: {}", reason),
+            CompileError::UnresolvedReference { name, .. } => format!("'{}' is not defined", name),
+        }
+    }
+    
+    /// Where in the source the error was found, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CompileError::UnresolvedReference { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+/// An error object as `name: message`, or any other value as its string form
+fn describe_thrown(value: &Value) -> String {
+    // A value too long to convert is shown in its summarised display form
+    let string = |value: &Value| value.to_js_string().unwrap_or_else(|_| value.to_string());
+    if let Value::Object(obj) = value {
+        let obj = obj.borrow();
+        if let (Some(name), Some(message)) = (obj.get("name"), obj.get("message")) {
+            return format!("{}: {}", string(name), string(message));
+        }
+    }
+    string(value)
+}
+
+/// Write a message followed by its span's offsets, if any
+fn write_located(f: &mut fmt::Formatter<'_>, message: String, span: Option<Span>) -> fmt::Result {
+    match span {
+        Some(span) => write!(f, "{} at {}:{}", message, span.start, span.end),
+        None => write!(f, "{}", message),
+    }
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -77,48 +289,27 @@ impl fmt::Display for Error {
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ParseError::UnexpectedToken { expected, found, span } => {
-                write!(f, "Expected '{}', found '{}' at {}:{}", expected, found, span.start, span.end)
-            }
-            ParseError::UnexpectedEOF => {
-                write!(f, "Unexpected end of file")
-            }
-            ParseError::InvalidSyntax { message, span } => {
-                write!(f, "{} at {}:{}", message, span.start, span.end)
-            }
-        }
+        write_located(f, self.message(), self.span())
     }
 }
 
 impl fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            RuntimeError::UndefinedVariable { name } => {
-                write!(f, "Undefined variable: {}", name)
-            }
-            RuntimeError::TypeError { expected, found } => {
-                write!(f, "Type error: expected {}, found {}", expected, found)
-            }
-            RuntimeError::StackOverflow => {
-                write!(f, "Stack overflow")
-            }
-            RuntimeError::DivisionByZero => {
-                write!(f, "Division by zero")
-            }
-        }
+        write_located(f, self.message(), self.span())
     }
 }
 
 impl fmt::Display for CompileError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            CompileError::UnsupportedFeature { feature } => {
-                write!(f, "Unsupported feature: {}", feature)
-            }
-            CompileError::OptimizationFailed { reason } => {
-                write!(f, "Optimization failed: {}", reason)
-            }
+        write_located(f, self.message(), self.span())
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.function {
+            Some(name) => write!(f, "at {} ({}:{}:{})", name, self.file, self.line, self.column),
+            None => write!(f, "at {}:{}:{}", self.file, self.line, self.column),
         }
     }
 }
@@ -166,6 +357,7 @@ mod tests {
     fn test_runtime_error_creation() {
         let err = RuntimeError::UndefinedVariable {
             name: "x".to_string(),
+            span: None,
         };
         assert!(matches!(err, RuntimeError::UndefinedVariable { .. }));
     }
@@ -179,8 +371,34 @@ mod tests {
 
     #[test]
     fn test_error_display() {
-        let err = Error::RuntimeError(RuntimeError::DivisionByZero);
+        let err = Error::RuntimeError(RuntimeError::DivisionByZero { span: None });
         let display = format!("{}", err);
         assert!(display.contains("Division by zero"));
     }
+    
+    #[test]
+    fn test_runtime_error_with_span() {
+        let err = RuntimeError::DivisionByZero { span: None }.with_span(Span::new(3, 8));
+        assert_eq!(err.span(), Some(Span::new(3, 8)));
+        assert_eq!(err.to_string(), "Division by zero at 3:8");
+        
+        // An existing location is kept
+        let err = err.with_span(Span::new(0, 1));
+        assert_eq!(err.span(), Some(Span::new(3, 8)));
+    }
+
+    #[test]
+    fn test_reference_error_display() {
+        let err = RuntimeError::ReferenceError {
+            name: "missing".to_string(),
+            span: Span::new(4, 11),
+        };
+        assert_eq!(err.to_string(), "ReferenceError: missing is not defined at 4:11");
+
+        let err = CompileError::UnresolvedReference {
+            name: "missing".to_string(),
+            span: Span::new(4, 11),
+        };
+        assert_eq!(Error::from(err).to_string(), "Compile error: 'missing' is not defined at 4:11");
+    }
 }
@@ -0,0 +1,336 @@
+// Managed heap with a mark-and-sweep cycle collector
+//
+// Heap values are reference counted, so most garbage is freed as soon as the
+// last handle to it goes away. Reference cycles (an object holding itself, a
+// closure stored in the object it captures, ...) never reach a count of zero;
+// the collector finds them by marking everything reachable from the roots and
+// clearing the contents of whatever is left, which breaks the cycles.
+
+use crate::types::{ArrayRef, Closure, JsArray, JsObject, ObjectRef, Upvalue, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+/// Number of allocations before the first automatic collection
+const INITIAL_GC_THRESHOLD: usize = 1024;
+
+/// Heap-size statistics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeapStats {
+    /// Live plain objects
+    pub objects: usize,
+    /// Live arrays
+    pub arrays: usize,
+    /// Live closures
+    pub closures: usize,
+    /// Live closure cells
+    pub cells: usize,
+    /// Approximate bytes held by live heap values
+    pub bytes: usize,
+    /// Number of collections run so far
+    pub collections: usize,
+    /// Number of values reclaimed by the collector so far
+    pub freed: usize,
+}
+
+impl HeapStats {
+    /// Total number of live heap values
+    pub fn live(&self) -> usize {
+        self.objects + self.arrays + self.closures + self.cells
+    }
+}
+
+/// A weak reference to an allocation the heap tracks
+enum Entry {
+    Object(Weak<RefCell<JsObject>>),
+    Array(Weak<RefCell<JsArray>>),
+    Closure(Weak<Closure>),
+    Cell(Weak<RefCell<Value>>),
+}
+
+impl Entry {
+    fn upgrade(&self) -> Option<Node> {
+        match self {
+            Entry::Object(weak) => weak.upgrade().map(Node::Object),
+            Entry::Array(weak) => weak.upgrade().map(Node::Array),
+            Entry::Closure(weak) => weak.upgrade().map(Node::Closure),
+            Entry::Cell(weak) => weak.upgrade().map(Node::Cell),
+        }
+    }
+}
+
+/// A live heap value during a collection
+enum Node {
+    Object(ObjectRef),
+    Array(ArrayRef),
+    Closure(Rc<Closure>),
+    Cell(Upvalue),
+}
+
+impl Node {
+    fn address(&self) -> *const () {
+        match self {
+            Node::Object(obj) => Rc::as_ptr(obj) as *const (),
+            Node::Array(arr) => Rc::as_ptr(arr) as *const (),
+            Node::Closure(closure) => Rc::as_ptr(closure) as *const (),
+            Node::Cell(cell) => Rc::as_ptr(cell) as *const (),
+        }
+    }
+    
+    /// Handles to this value, not counting the one held by the node itself
+    fn handle_count(&self) -> usize {
+        let count = match self {
+            Node::Object(obj) => Rc::strong_count(obj),
+            Node::Array(arr) => Rc::strong_count(arr),
+            Node::Closure(closure) => Rc::strong_count(closure),
+            Node::Cell(cell) => Rc::strong_count(cell),
+        };
+        count - 1
+    }
+    
+    /// Addresses of the heap values this value refers to
+    fn children(&self) -> Vec<*const ()> {
+        match self {
+            Node::Object(obj) => obj.borrow().values().filter_map(value_address).collect(),
+            Node::Array(arr) => {
+                let arr = arr.borrow();
+                arr.entries().map(|(_, value)| value).chain(arr.named_values()).filter_map(value_address).collect()
+            }
+            Node::Closure(closure) => closure.upvalues.iter()
+                .map(|cell| Rc::as_ptr(cell) as *const ())
+                .collect(),
+            Node::Cell(cell) => value_address(&cell.borrow()).into_iter().collect(),
+        }
+    }
+    
+    /// Drop everything this value refers to
+    fn clear(&self) {
+        match self {
+            Node::Object(obj) => obj.borrow_mut().clear(),
+            Node::Array(arr) => arr.borrow_mut().clear(),
+            // Every cycle through a closure passes through one of its cells
+            Node::Closure(_) => {}
+            Node::Cell(cell) => *cell.borrow_mut() = Value::Undefined,
+        }
+    }
+    
+    /// Approximate size in bytes
+    fn size(&self) -> usize {
+        match self {
+            Node::Object(obj) => mem::size_of::<JsObject>() + obj.borrow().len() * mem::size_of::<(Rc<str>, Value)>(),
+            Node::Array(arr) => mem::size_of::<JsArray>() + arr.borrow().stored_len() * mem::size_of::<Value>(),
+            Node::Closure(closure) => mem::size_of::<Closure>() + closure.upvalues.len() * mem::size_of::<Upvalue>(),
+            Node::Cell(_) => mem::size_of::<RefCell<Value>>(),
+        }
+    }
+}
+
+/// Address of the heap value a `Value` refers to, if it is one
+fn value_address(value: &Value) -> Option<*const ()> {
+    match value {
+        Value::Object(obj) => Some(Rc::as_ptr(obj) as *const ()),
+        Value::Array(arr) => Some(Rc::as_ptr(arr) as *const ()),
+        Value::Function(closure) => Some(Rc::as_ptr(closure) as *const ()),
+        _ => None,
+    }
+}
+
+/// The engine's managed heap
+pub struct Heap {
+    entries: Vec<Entry>,
+    allocations_since_gc: usize,
+    threshold: usize,
+    collections: usize,
+    freed: usize,
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            allocations_since_gc: 0,
+            threshold: INITIAL_GC_THRESHOLD,
+            collections: 0,
+            freed: 0,
+        }
+    }
+    
+    /// Allocate a plain object
+    pub fn alloc_object(&mut self, object: JsObject) -> ObjectRef {
+        let obj = Rc::new(RefCell::new(object));
+        self.track(Entry::Object(Rc::downgrade(&obj)));
+        obj
+    }
+    
+    /// Allocate an array
+    pub fn alloc_array(&mut self, array: JsArray) -> ArrayRef {
+        let arr = Rc::new(RefCell::new(array));
+        self.track(Entry::Array(Rc::downgrade(&arr)));
+        arr
+    }
+    
+    /// Allocate a closure
+    pub fn alloc_closure(&mut self, closure: Closure) -> Rc<Closure> {
+        let closure = Rc::new(closure);
+        self.track(Entry::Closure(Rc::downgrade(&closure)));
+        closure
+    }
+    
+    /// Allocate a closure cell holding `undefined`
+    pub fn alloc_cell(&mut self) -> Upvalue {
+        let cell = Upvalue::default();
+        self.track(Entry::Cell(Rc::downgrade(&cell)));
+        cell
+    }
+    
+    fn track(&mut self, entry: Entry) {
+        self.entries.push(entry);
+        self.allocations_since_gc += 1;
+    }
+    
+    /// Whether enough has been allocated since the last collection to run another
+    pub fn should_collect(&self) -> bool {
+        self.allocations_since_gc >= self.threshold
+    }
+    
+    /// Collect unreachable cycles, returning the number of values reclaimed.
+    ///
+    /// Besides `roots` and `root_cells`, any value with a handle held outside
+    /// the heap (by the embedder, say) is kept alive.
+    pub fn collect<'a>(
+        &mut self,
+        roots: impl IntoIterator<Item = &'a Value>,
+        root_cells: impl IntoIterator<Item = &'a Upvalue>,
+    ) -> usize {
+        // Entries whose value was already freed by reference counting are dropped
+        let nodes: Vec<Node> = self.entries.iter().filter_map(Entry::upgrade).collect();
+        let index: HashMap<*const (), usize> = nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.address(), i))
+            .collect();
+        let children: Vec<Vec<usize>> = nodes.iter()
+            .map(|node| node.children().iter().filter_map(|addr| index.get(addr).copied()).collect())
+            .collect();
+        
+        // Handles not accounted for by other heap values live outside the heap
+        let mut internal = vec![0; nodes.len()];
+        for &child in children.iter().flatten() {
+            internal[child] += 1;
+        }
+        let mut worklist: Vec<usize> = (0..nodes.len())
+            .filter(|&i| nodes[i].handle_count() > internal[i])
+            .collect();
+        worklist.extend(roots.into_iter().filter_map(value_address).filter_map(|addr| index.get(&addr).copied()));
+        worklist.extend(root_cells.into_iter().filter_map(|cell| index.get(&(Rc::as_ptr(cell) as *const ())).copied()));
+        
+        // Mark
+        let mut marked = vec![false; nodes.len()];
+        while let Some(i) = worklist.pop() {
+            if !mem::replace(&mut marked[i], true) {
+                worklist.extend(&children[i]);
+            }
+        }
+        
+        // Sweep
+        let mut freed = 0;
+        for (node, &live) in nodes.iter().zip(&marked) {
+            if !live {
+                node.clear();
+                freed += 1;
+            }
+        }
+        self.entries = nodes.iter()
+            .zip(&marked)
+            .filter(|(_, &live)| live)
+            .map(|(node, _)| match node {
+                Node::Object(obj) => Entry::Object(Rc::downgrade(obj)),
+                Node::Array(arr) => Entry::Array(Rc::downgrade(arr)),
+                Node::Closure(closure) => Entry::Closure(Rc::downgrade(closure)),
+                Node::Cell(cell) => Entry::Cell(Rc::downgrade(cell)),
+            })
+            .collect();
+        
+        let live = self.entries.len();
+        self.threshold = (live * 2).max(INITIAL_GC_THRESHOLD);
+        self.allocations_since_gc = 0;
+        self.collections += 1;
+        self.freed += freed;
+        freed
+    }
+    
+    /// Current heap statistics
+    pub fn stats(&self) -> HeapStats {
+        let mut stats = HeapStats {
+            collections: self.collections,
+            freed: self.freed,
+            ..HeapStats::default()
+        };
+        for node in self.entries.iter().filter_map(Entry::upgrade) {
+            stats.bytes += node.size();
+            match node {
+                Node::Object(_) => stats.objects += 1,
+                Node::Array(_) => stats.arrays += 1,
+                Node::Closure(_) => stats.closures += 1,
+                Node::Cell(_) => stats.cells += 1,
+            }
+        }
+        stats
+    }
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_collect_self_cycle() {
+        let mut heap = Heap::new();
+        let obj = heap.alloc_object(JsObject::new());
+        obj.borrow_mut().set("self".into(), Value::Object(obj.clone()));
+        let weak = Rc::downgrade(&obj);
+        drop(obj);
+        
+        assert_eq!(heap.stats().objects, 1);
+        assert_eq!(heap.collect([], []), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(heap.stats().live(), 0);
+        assert_eq!(heap.stats().freed, 1);
+    }
+    
+    #[test]
+    fn test_collect_keeps_reachable() {
+        let mut heap = Heap::new();
+        let root = heap.alloc_array(JsArray::new(Vec::new()));
+        let child = heap.alloc_object(JsObject::new());
+        child.borrow_mut().set("owner".into(), Value::Array(root.clone()));
+        root.borrow_mut().push(Value::Object(child));
+        let root = Value::Array(root);
+        
+        // Held as a root, and through a handle outside the heap
+        assert_eq!(heap.collect([&root], []), 0);
+        assert_eq!(heap.collect([], []), 0);
+        assert_eq!(heap.stats().objects, 1);
+        assert_eq!(heap.stats().arrays, 1);
+    }
+    
+    #[test]
+    fn test_collect_closure_cycle() {
+        // A closure stored in the cell it captured
+        let mut heap = Heap::new();
+        let cell = heap.alloc_cell();
+        let closure = heap.alloc_closure(Closure::new(1, vec![cell.clone()]));
+        *cell.borrow_mut() = Value::Function(closure);
+        
+        assert_eq!(heap.collect([], [&cell]), 0);
+        drop(cell);
+        assert_eq!(heap.collect([], []), 2);
+        assert_eq!(heap.stats().live(), 0);
+    }
+}
@@ -1,31 +1,55 @@
 // Ignition bytecode interpreter
 
-use crate::bytecode::{BytecodeChunk, Instruction};
+use crate::bytecode::{BytecodeChunk, FunctionInfo, Instruction};
 use crate::error::RuntimeError;
-use crate::types::{FunctionId, Value};
+use crate::heap::{Heap, HeapStats};
+use crate::types::{check_string_length, ArrayRef, Closure, FunctionId, JsArray, JsObject, Span, Upvalue, Value};
 use crate::profiler::HotspotProfiler;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::cmp::Ordering;
+
+/// Maximum number of nested calls before reporting a stack overflow
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// Maximum number of arguments a spread call can pass
+const MAX_ARGUMENTS: usize = 65_535;
+
+/// An error delivered to an exception handler, kept so a `finally` block can
+/// rethrow it unchanged
+#[derive(Debug, Clone)]
+struct PendingError {
+    error: RuntimeError,
+    trace: Vec<(FunctionId, Option<Span>)>,
+}
 
 /// Call frame for function execution
 #[derive(Debug, Clone)]
 pub struct CallFrame {
-    pub chunk: BytecodeChunk,
+    pub chunk: Rc<BytecodeChunk>,
     pub ip: usize,
     pub stack: Vec<Value>,
     pub locals: Vec<Value>,
+    /// Cells captured by the running closure, followed by the frame's own cells
+    pub upvalues: Vec<Upvalue>,
     pub func_id: FunctionId,
+    /// Errors caught by this frame's handlers, by the stack depth their value was pushed at
+    pending_errors: HashMap<usize, PendingError>,
 }
 
 impl CallFrame {
-    pub fn new(chunk: BytecodeChunk, func_id: FunctionId) -> Self {
+    pub fn new(chunk: impl Into<Rc<BytecodeChunk>>, func_id: FunctionId) -> Self {
+        let chunk = chunk.into();
         let local_count = chunk.local_count;
         Self {
             chunk,
             ip: 0,
             stack: Vec::new(),
             locals: vec![Value::Undefined; local_count],
+            upvalues: Vec::new(),
             func_id,
+            pending_errors: HashMap::new(),
         }
     }
     
@@ -36,27 +60,53 @@ impl CallFrame {
     
     /// Pop a value from the stack
     pub fn pop(&mut self) -> Result<Value, RuntimeError> {
-        self.stack.pop().ok_or(RuntimeError::StackOverflow)
+        self.stack.pop().ok_or_else(stack_underflow)
     }
     
     /// Peek at the top of the stack
     pub fn peek(&self) -> Option<&Value> {
         self.stack.last()
     }
+    
+    /// The variable name held by the constant at `idx`
+    fn constant_name(&self, idx: usize) -> String {
+        self.chunk.constants.get(idx)
+            .map(Value::to_string)
+            .unwrap_or_default()
+    }
+    
+    /// Jump by `offset` keeping the top value if it satisfies `take_jump`, otherwise pop it
+    fn jump_or_pop(&mut self, offset: isize, take_jump: impl Fn(&Value) -> bool) -> Result<(), RuntimeError> {
+        let top = self.peek().ok_or_else(stack_underflow)?;
+        if take_jump(top) {
+            self.ip = ((self.ip as isize) + offset) as usize;
+        } else {
+            self.stack.pop();
+        }
+        Ok(())
+    }
 }
 
 /// Ignition interpreter
 pub struct Ignition {
     call_stack: Vec<CallFrame>,
     profiler: Rc<RefCell<HotspotProfiler>>,
+    /// Compiled user-defined functions, keyed by ID
+    functions: HashMap<FunctionId, FunctionInfo>,
+    heap: Heap,
+    /// Top-level variables left by the last script, picked up by the next one
+    globals: Vec<Value>,
+    /// Slots of the top-level variables, by name, for lookups that were not resolved statically
+    global_names: HashMap<String, usize>,
+    /// Function and source position of every frame when the last error escaped, innermost first
+    stack_trace: Vec<(FunctionId, Option<Span>)>,
+    /// Stack trace of the error a `Rethrow` is raising again
+    rethrown_trace: Option<Vec<(FunctionId, Option<Span>)>>,
 }
 
 impl Ignition {
     pub fn new() -> Self {
-        Self {
-            call_stack: Vec::new(),
-            profiler: Rc::new(RefCell::new(HotspotProfiler::default())),
-        }
+        Self::with_profiler(Rc::new(RefCell::new(HotspotProfiler::default())))
     }
     
     /// Create interpreter with a shared profiler
@@ -64,14 +114,49 @@ impl Ignition {
         Self {
             call_stack: Vec::new(),
             profiler,
+            functions: HashMap::new(),
+            heap: Heap::new(),
+            globals: Vec::new(),
+            global_names: HashMap::new(),
+            stack_trace: Vec::new(),
+            rethrown_trace: None,
         }
     }
     
+    /// Make a compiled function callable through `Value::Function(info.id)`
+    pub fn register_function(&mut self, info: FunctionInfo) {
+        self.functions.insert(info.id, info);
+    }
+    
+    /// Look up a registered function
+    pub fn function(&self, func_id: FunctionId) -> Option<&FunctionInfo> {
+        self.functions.get(&func_id)
+    }
+    
     /// Get a reference to the profiler
     pub fn profiler(&self) -> Rc<RefCell<HotspotProfiler>> {
         self.profiler.clone()
     }
     
+    /// Run the garbage collector, returning the number of heap values reclaimed.
+    ///
+    /// The roots are every frame's stack, locals and cells, and the constant
+    /// pools of all loaded code, and the top-level variables kept between scripts.
+    pub fn collect_garbage(&mut self) -> usize {
+        let frames = &self.call_stack;
+        let values = frames.iter()
+            .flat_map(|frame| frame.stack.iter().chain(&frame.locals).chain(&frame.chunk.constants));
+        let constants = self.functions.values().flat_map(|info| &info.chunk.constants);
+        let values = values.chain(&self.globals);
+        let cells = frames.iter().flat_map(|frame| &frame.upvalues);
+        self.heap.collect(values.chain(constants), cells)
+    }
+    
+    /// Heap statistics
+    pub fn heap_stats(&self) -> HeapStats {
+        self.heap.stats()
+    }
+    
     /// Execute a bytecode chunk
     pub fn execute(&mut self, chunk: BytecodeChunk) -> Result<Value, RuntimeError> {
         let func_id = 0; // Default function ID for main execution
@@ -83,24 +168,114 @@ impl Ignition {
         // Record execution in profiler
         self.profiler.borrow_mut().record_execution(func_id);
         
-        let frame = CallFrame::new(chunk, func_id);
+        let base_depth = self.call_stack.len();
+        let mut frame = CallFrame::new(chunk, func_id);
+        if base_depth == 0 {
+            // A top-level script starts with the variables earlier scripts declared
+            let mut globals = std::mem::take(&mut self.globals);
+            globals.resize(globals.len().max(frame.locals.len()), Value::Undefined);
+            frame.locals = globals;
+        }
         self.call_stack.push(frame);
         
-        self.run()
+        let result = self.run(base_depth);
+        if result.is_err() {
+            // Discard the frames of the failed execution so the interpreter stays usable
+            while self.call_stack.len() > base_depth {
+                self.pop_frame();
+            }
+        }
+        result
+    }
+    
+    /// Drop top-level variable slots past the first `count`, which only the
+    /// last script's blocks used
+    pub fn retain_globals(&mut self, count: usize) {
+        self.globals.truncate(count);
+    }
+    
+    /// Set the top-level variable in `slot`
+    pub fn set_global(&mut self, slot: usize, value: Value) {
+        if slot >= self.globals.len() {
+            self.globals.resize(slot + 1, Value::Undefined);
+        }
+        self.globals[slot] = value;
+    }
+    
+    /// Start a new top-level `let`/`const` variable in `slot` uninitialised, so
+    /// it stays unreadable if the script declaring it fails first
+    pub fn define_uninitialized_global(&mut self, slot: usize) {
+        if slot >= self.globals.len() {
+            self.globals.resize(slot + 1, Value::Undefined);
+            self.globals[slot] = Value::Hole;
+        }
+    }
+    
+    /// Make the top-level variable in `slot` reachable by name
+    pub fn define_global(&mut self, name: String, slot: usize) {
+        self.global_names.insert(name, slot);
+    }
+    
+    /// Function and source position of each frame the last uncaught error
+    /// unwound, innermost first
+    pub fn stack_trace(&self) -> &[(FunctionId, Option<Span>)] {
+        &self.stack_trace
+    }
+    
+    /// Top-level variables kept from the last script, by slot
+    pub fn globals(&self) -> &[Value] {
+        &self.globals
     }
     
-    /// Main execution loop
-    fn run(&mut self) -> Result<Value, RuntimeError> {
+    /// Slot of the global named by constant `name_idx` of the current frame
+    fn global_slot(&self, name_idx: usize, span: Span) -> Result<usize, RuntimeError> {
+        let name = self.call_stack.last()
+            .map(|frame| frame.constant_name(name_idx))
+            .unwrap_or_default();
+        match self.global_names.get(&name) {
+            Some(&slot) => Ok(slot),
+            None => Err(RuntimeError::ReferenceError { name, span }),
+        }
+    }
+    
+    /// Pop the innermost frame, keeping the script frame's variables for the next script
+    fn pop_frame(&mut self) {
+        if let Some(frame) = self.call_stack.pop() {
+            if self.call_stack.is_empty() {
+                self.globals = frame.locals;
+            }
+        }
+    }
+    
+    /// Main execution loop, running until the call stack unwinds to `base_depth`
+    fn run(&mut self, base_depth: usize) -> Result<Value, RuntimeError> {
         loop {
             let frame = self.call_stack.last_mut()
-                .ok_or(RuntimeError::StackOverflow)?;
+                .ok_or_else(|| internal_error("no call frame"))?;
             
-            if frame.ip >= frame.chunk.instructions.len() {
-                // End of instructions
-                let result = frame.pop().unwrap_or(Value::Undefined);
-                self.call_stack.pop();
+            // Running off the end of a chunk behaves like `Return`
+            let instruction = match frame.chunk.instructions.get(frame.ip) {
+                Some(instruction) => instruction.clone(),
+                None => Instruction::Return,
+            };
+            frame.ip += 1;
+            
+            if self.heap.should_collect() {
+                self.collect_garbage();
+            }
+            
+            let returned = match self.dispatch(instruction) {
+                Ok(returned) => returned,
+                Err(err) => {
+                    self.handle_exception(err, base_depth)?;
+                    continue;
+                }
+            };
+            
+            if let Some(result) = returned {
+                self.pop_frame();
                 
-                if self.call_stack.is_empty() {
+                if self.call_stack.len() == base_depth {
                     return Ok(result);
                 }
                 
@@ -108,26 +283,152 @@ impl Ignition {
                 if let Some(caller) = self.call_stack.last_mut() {
                     caller.push(result);
                 }
-                continue;
             }
-            
-            let instruction = frame.chunk.instructions[frame.ip].clone();
-            frame.ip += 1;
-            
-            self.dispatch(instruction)?;
         }
     }
     
-    /// Dispatch a single instruction
-    fn dispatch(&mut self, instruction: Instruction) -> Result<(), RuntimeError> {
+    /// Unwind to the innermost exception handler covering the failed
+    /// instruction, popping frames down to the one `run` started with.
+    ///
+    /// The handler's frame resumes at its target with the error, as a JS
+    /// value, on the stack. Without a handler the original error is returned.
+    fn handle_exception(&mut self, err: RuntimeError, base_depth: usize) -> Result<(), RuntimeError> {
+        // Locate the error at the code that raised it
+        let raised_at = self.call_stack.last()
+            .and_then(|frame| frame.chunk.position_at(frame.ip.saturating_sub(1)));
+        let err = match raised_at {
+            Some(span) => err.with_span(span),
+            None => err,
+        };
+        
+        // Each frame is stopped at the instruction that failed or made the call
+        let trace = self.rethrown_trace.take().unwrap_or_else(|| {
+            self.call_stack.iter()
+                .rev()
+                .map(|frame| (frame.func_id, frame.chunk.position_at(frame.ip.saturating_sub(1))))
+                .collect()
+        });
+        
+        let depth = (base_depth..self.call_stack.len()).rev().find(|&depth| {
+            let frame = &self.call_stack[depth];
+            err.is_catchable() && frame.chunk.handler_for(frame.ip.saturating_sub(1)).is_some()
+        });
+        let Some(depth) = depth else {
+            self.stack_trace = trace;
+            return Err(err);
+        };
+        
+        while self.call_stack.len() > depth + 1 {
+            self.pop_frame();
+        }
+        let value = self.exception_value(err.clone());
+        let frame = self.call_stack.last_mut().expect("handler frame");
+        let handler = *frame.chunk.handler_for(frame.ip.saturating_sub(1)).expect("exception handler");
+        frame.stack.truncate(handler.stack_depth);
+        frame.push(value);
+        frame.pending_errors.insert(handler.stack_depth, PendingError { error: err, trace });
+        frame.ip = handler.target;
+        Ok(())
+    }
+    
+    /// The JS value a catch clause receives for an error
+    fn exception_value(&mut self, err: RuntimeError) -> Value {
+        let (name, message) = match err {
+            RuntimeError::Thrown { value, .. } => return value,
+            RuntimeError::UndefinedVariable { name, .. } | RuntimeError::ReferenceError { name, .. } => {
+                ("ReferenceError", format!("{} is not defined", name))
+            }
+            RuntimeError::UninitializedVariable { .. } => ("ReferenceError", err.message()),
+            RuntimeError::TypeError { ref expected, ref found, .. } => {
+                ("TypeError", format!("expected {}, found {}", expected, found))
+            }
+            RuntimeError::ConstAssignment { .. } => ("TypeError", err.message()),
+            RuntimeError::StackOverflow { .. } => ("RangeError", "Maximum call stack size exceeded".to_string()),
+            RuntimeError::DivisionByZero { .. } => ("RangeError", err.message()),
+            RuntimeError::InvalidArrayLength { .. } => ("RangeError", "Invalid array length".to_string()),
+            RuntimeError::TooManyArguments { .. } => ("RangeError", "Too many arguments in function call".to_string()),
+            RuntimeError::InvalidStringLength { .. } => ("RangeError", "Invalid string length".to_string()),
+            RuntimeError::Internal { .. } => ("Error", err.message()),
+        };
+        
+        Value::Object(self.heap.alloc_object(JsObject::error(name, &message)))
+    }
+    
+    /// Push a frame for calling `callee` with `args`
+    fn call_function(&mut self, callee: Value, mut args: Vec<Value>) -> Result<(), RuntimeError> {
+        let Value::Function(closure) = callee else {
+            return Err(RuntimeError::TypeError {
+                expected: "function".to_string(),
+                found: callee.type_name().to_string(),
+                span: None,
+            });
+        };
+        
+        let func_id = closure.func_id;
+        let info = self.functions.get(&func_id)
+            .ok_or_else(|| RuntimeError::UndefinedVariable {
+                name: format!("function_{}", func_id),
+                span: None,
+            })?;
+        let (chunk, arity, cell_count) = (info.chunk.clone(), info.arity, info.cell_count);
+        let (rest, arguments) = (info.rest, info.arguments);
+        
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::StackOverflow { span: None });
+        }
+        
+        self.profiler.borrow_mut().record_execution(func_id);
+        
+        let mut frame = CallFrame::new(chunk, func_id);
+        // The `arguments` object, an array of every argument, follows the parameters
+        if arguments {
+            let all = self.heap.alloc_array(JsArray::new(args.clone()));
+            frame.locals[arity + usize::from(rest)] = Value::Array(all);
+        }
+        // Missing arguments stay undefined; extra arguments go to the rest
+        // parameter, or are dropped without one
+        let extra = args.split_off(arity.min(args.len()));
+        if rest {
+            frame.locals[arity] = Value::Array(self.heap.alloc_array(JsArray::new(extra)));
+        }
+        for (local, arg) in frame.locals.iter_mut().zip(args) {
+            *local = arg;
+        }
+        frame.upvalues = closure.upvalues.iter()
+            .cloned()
+            .chain((0..cell_count).map(|_| self.heap.alloc_cell()))
+            .collect();
+        self.call_stack.push(frame);
+        
+        Ok(())
+    }
+    
+    /// Call the method `key` of `receiver`; built-in array methods push their result directly
+    fn call_method(&mut self, receiver: Value, key: Value, args: Vec<Value>) -> Result<(), RuntimeError> {
+        if let Value::Array(arr) = &receiver {
+            if let Some(result) = call_array_method(&mut self.heap, arr, &key.to_property_key()?, &args)? {
+                let frame = self.call_stack.last_mut()
+                    .ok_or_else(|| internal_error("no call frame"))?;
+                frame.push(result);
+                return Ok(());
+            }
+        }
+        let callee = get_property(&receiver, &key)?;
+        self.call_function(callee, args)
+    }
+    
+    /// Dispatch a single instruction.
+    ///
+    /// Returns `Some(value)` when the current frame returns `value`.
+    fn dispatch(&mut self, instruction: Instruction) -> Result<Option<Value>, RuntimeError> {
         let frame = self.call_stack.last_mut()
-            .ok_or(RuntimeError::StackOverflow)?;
+            .ok_or_else(|| internal_error("no call frame"))?;
         
         match instruction {
             Instruction::LoadConst(idx) => {
                 let value = frame.chunk.constants.get(idx)
                     .cloned()
-                    .ok_or(RuntimeError::StackOverflow)?;
+                    .ok_or_else(|| internal_error("constant index out of range"))?;
                 frame.push(value);
             }
             
@@ -136,6 +437,7 @@ impl Ignition {
                     .cloned()
                     .ok_or(RuntimeError::UndefinedVariable {
                         name: format!("local_{}", idx),
+                        span: None,
                     })?;
                 frame.push(value);
             }
@@ -147,6 +449,192 @@ impl Ignition {
                 }
             }
             
+            Instruction::LoadGlobal(idx) => {
+                let value = self.call_stack.first()
+                    .and_then(|script| script.locals.get(idx))
+                    .cloned()
+                    .ok_or(RuntimeError::UndefinedVariable {
+                        name: format!("global_{}", idx),
+                        span: None,
+                    })?;
+                self.call_stack.last_mut().unwrap().push(value);
+            }
+            
+            Instruction::StoreGlobal(idx) => {
+                let value = frame.pop()?;
+                if let Some(slot) = self.call_stack.first_mut().and_then(|script| script.locals.get_mut(idx)) {
+                    *slot = value;
+                }
+            }
+            
+            Instruction::LoadGlobalName(name_idx, span) => {
+                let slot = self.global_slot(name_idx, span)?;
+                let value = self.call_stack.first()
+                    .and_then(|script| script.locals.get(slot))
+                    .cloned()
+                    .unwrap_or_default();
+                let frame = self.call_stack.last_mut().unwrap();
+                if value == Value::Hole {
+                    return Err(RuntimeError::UninitializedVariable {
+                        name: frame.constant_name(name_idx),
+                        span: None,
+                    });
+                }
+                frame.push(value);
+            }
+            
+            Instruction::StoreGlobalName(name_idx, span) => {
+                let slot = self.global_slot(name_idx, span)?;
+                let value = self.call_stack.last_mut().unwrap().pop()?;
+                if let Some(script) = self.call_stack.first_mut() {
+                    if script.locals.len() <= slot {
+                        script.locals.resize(slot + 1, Value::Undefined);
+                    }
+                    script.locals[slot] = value;
+                }
+            }
+            
+            Instruction::LoadUpvalue(idx) => {
+                let value = frame.upvalues.get(idx)
+                    .map(|cell| cell.borrow().clone())
+                    .ok_or(RuntimeError::UndefinedVariable {
+                        name: format!("upvalue_{}", idx),
+                        span: None,
+                    })?;
+                frame.push(value);
+            }
+            
+            Instruction::StoreUpvalue(idx) => {
+                let value = frame.pop()?;
+                if let Some(cell) = frame.upvalues.get(idx) {
+                    *cell.borrow_mut() = value;
+                }
+            }
+            
+            Instruction::FreshCell(idx) => {
+                let value = frame.upvalues.get(idx)
+                    .map(|cell| cell.borrow().clone())
+                    .unwrap_or_default();
+                while frame.upvalues.len() <= idx {
+                    frame.upvalues.push(self.heap.alloc_cell());
+                }
+                let cell = self.heap.alloc_cell();
+                *cell.borrow_mut() = value;
+                frame.upvalues[idx] = cell;
+            }
+            
+            Instruction::LoadHole => {
+                frame.push(Value::Hole);
+            }
+            
+            Instruction::CheckInitialized(name_idx) => {
+                if matches!(frame.peek(), Some(Value::Hole)) {
+                    return Err(RuntimeError::UninitializedVariable {
+                        name: frame.constant_name(name_idx),
+                        span: None,
+                    });
+                }
+            }
+            
+            Instruction::Throw => {
+                let value = frame.pop()?;
+                return Err(RuntimeError::Thrown { value, span: None });
+            }
+            
+            Instruction::Rethrow => {
+                let value = frame.pop()?;
+                let Some(pending) = frame.pending_errors.remove(&frame.stack.len()) else {
+                    return Err(RuntimeError::Thrown { value, span: None });
+                };
+                self.rethrown_trace = Some(pending.trace);
+                return Err(pending.error);
+            }
+            
+            Instruction::ThrowConstAssignment(name_idx) => {
+                return Err(RuntimeError::ConstAssignment {
+                    name: frame.constant_name(name_idx),
+                    span: None,
+                });
+            }
+            
+            Instruction::MakeClosure(func_id) => {
+                let info = self.functions.get(&func_id)
+                    .ok_or_else(|| RuntimeError::UndefinedVariable {
+                        name: format!("function_{}", func_id),
+                        span: None,
+                    })?;
+                let upvalues = info.captures.iter()
+                    .map(|&idx| frame.upvalues.get(idx).cloned().unwrap_or_default())
+                    .collect();
+                frame.push(Value::Function(self.heap.alloc_closure(Closure::new(func_id, upvalues))));
+            }
+            
+            Instruction::CreateObject(count) => {
+                if frame.stack.len() < count * 2 {
+                    return Err(stack_underflow());
+                }
+                let entries = frame.stack.split_off(frame.stack.len() - count * 2);
+                let mut object = JsObject::new();
+                for pair in entries.chunks(2) {
+                    object.set(pair[0].to_property_key()?, pair[1].clone());
+                }
+                frame.push(Value::Object(self.heap.alloc_object(object)));
+            }
+            
+            Instruction::CreateArray(count) => {
+                if frame.stack.len() < count {
+                    return Err(stack_underflow());
+                }
+                let elements = frame.stack.split_off(frame.stack.len() - count);
+                frame.push(Value::Array(self.heap.alloc_array(JsArray::new(elements))));
+            }
+            
+            Instruction::AppendSpread => {
+                let iterable = frame.pop()?;
+                let Some(Value::Array(array)) = frame.stack.last() else {
+                    return Err(stack_underflow());
+                };
+                let room = MAX_ARGUMENTS.saturating_sub(array.borrow().len());
+                let elements = match &iterable {
+                    Value::Array(arr) => array_arguments(&arr.borrow(), room)?,
+                    Value::String(s) if s.chars().count() > room => {
+                        return Err(RuntimeError::TooManyArguments { span: None });
+                    }
+                    Value::String(s) => s.chars().map(|ch| Value::String(ch.to_string().into())).collect(),
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            expected: "iterable".to_string(),
+                            found: iterable.type_name().to_string(),
+                            span: None,
+                        });
+                    }
+                };
+                let mut array = array.borrow_mut();
+                for element in elements {
+                    array.push(element);
+                }
+            }
+            
+            Instruction::GetProperty => {
+                let key = frame.pop()?;
+                let object = frame.pop()?;
+                frame.push(get_property(&object, &key)?);
+            }
+            
+            Instruction::SetProperty => {
+                let value = frame.pop()?;
+                let key = frame.pop()?;
+                let object = frame.pop()?;
+                match &object {
+                    Value::Object(obj) => obj.borrow_mut().set(key.to_property_key()?, value.clone()),
+                    Value::Array(arr) => set_array_property(arr, &key, &value)?,
+                    // Writes to properties of other primitives are silently dropped
+                    Value::Null | Value::Undefined => return Err(property_type_error(&object)),
+                    _ => {}
+                }
+                frame.push(value);
+            }
+            
             Instruction::Add => {
                 let right = frame.pop()?;
                 let left = frame.pop()?;
@@ -155,10 +643,18 @@ impl Ignition {
                     (Value::Number(l), Value::Number(r)) => {
                         frame.push(Value::Number(l + r));
                     }
+                    // Concatenate when either operand is a string or an object
+                    (l @ (Value::String(_) | Value::Object(_)), r) | (l, r @ (Value::String(_) | Value::Object(_))) => {
+                        let (l, r) = (l.to_js_string()?, r.to_js_string()?);
+                        check_string_length(l.len() + r.len())?;
+                        let joined = l + &r;
+                        frame.push(Value::String(joined.into()));
+                    }
                     _ => {
                         return Err(RuntimeError::TypeError {
                             expected: "number".to_string(),
                             found: "other".to_string(),
+                            span: None,
                         });
                     }
                 }
@@ -176,6 +672,7 @@ impl Ignition {
                         return Err(RuntimeError::TypeError {
                             expected: "number".to_string(),
                             found: "other".to_string(),
+                            span: None,
                         });
                     }
                 }
@@ -193,6 +690,7 @@ impl Ignition {
                         return Err(RuntimeError::TypeError {
                             expected: "number".to_string(),
                             found: "other".to_string(),
+                            span: None,
                         });
                     }
                 }
@@ -205,7 +703,7 @@ impl Ignition {
                 match (left, right) {
                     (Value::Number(l), Value::Number(r)) => {
                         if r == 0.0 {
-                            return Err(RuntimeError::DivisionByZero);
+                            return Err(RuntimeError::DivisionByZero { span: None });
                         }
                         frame.push(Value::Number(l / r));
                     }
@@ -213,18 +711,108 @@ impl Ignition {
                         return Err(RuntimeError::TypeError {
                             expected: "number".to_string(),
                             found: "other".to_string(),
+                            span: None,
                         });
                     }
                 }
             }
             
+            Instruction::Equal => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                frame.push(Value::Boolean(left.loose_equals(&right)));
+            }
+            
+            Instruction::NotEqual => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                frame.push(Value::Boolean(!left.loose_equals(&right)));
+            }
+            
+            Instruction::StrictEqual => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                frame.push(Value::Boolean(left.strict_equals(&right)));
+            }
+            
+            Instruction::StrictNotEqual => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                frame.push(Value::Boolean(!left.strict_equals(&right)));
+            }
+            
+            Instruction::Less => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                let result = left.compare(&right) == Some(Ordering::Less);
+                frame.push(Value::Boolean(result));
+            }
+            
+            Instruction::LessEqual => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                let result = matches!(left.compare(&right), Some(Ordering::Less | Ordering::Equal));
+                frame.push(Value::Boolean(result));
+            }
+            
+            Instruction::Greater => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                let result = left.compare(&right) == Some(Ordering::Greater);
+                frame.push(Value::Boolean(result));
+            }
+            
+            Instruction::GreaterEqual => {
+                let right = frame.pop()?;
+                let left = frame.pop()?;
+                let result = matches!(left.compare(&right), Some(Ordering::Greater | Ordering::Equal));
+                frame.push(Value::Boolean(result));
+            }
+            
+            Instruction::Pop => {
+                frame.pop()?;
+            }
+            
+            Instruction::Dup => {
+                let value = frame.peek().cloned().ok_or_else(stack_underflow)?;
+                frame.push(value);
+            }
+            
+            Instruction::Dup2 => {
+                if frame.stack.len() < 2 {
+                    return Err(stack_underflow());
+                }
+                let top = frame.stack[frame.stack.len() - 2..].to_vec();
+                frame.stack.extend(top);
+            }
+            
+            Instruction::MoveUnder(depth) => {
+                if frame.stack.len() < depth + 1 {
+                    return Err(stack_underflow());
+                }
+                let value = frame.pop()?;
+                let index = frame.stack.len() - depth;
+                frame.stack.insert(index, value);
+            }
+            
+            Instruction::ToNumber => {
+                let value = frame.pop()?;
+                frame.push(Value::Number(value.to_number()));
+            }
+            
+            Instruction::Negate => {
+                let value = frame.pop()?;
+                frame.push(Value::Number(-value.to_number()));
+            }
+            
+            Instruction::Not => {
+                let value = frame.pop()?;
+                frame.push(Value::Boolean(!value.is_truthy()));
+            }
+            
             Instruction::Return => {
                 let result = frame.pop().unwrap_or(Value::Undefined);
-                self.call_stack.pop();
-                
-                if let Some(caller) = self.call_stack.last_mut() {
-                    caller.push(result);
-                }
+                return Ok(Some(result));
             }
             
             Instruction::Jump(offset) => {
@@ -234,20 +822,28 @@ impl Ignition {
             
             Instruction::JumpIfFalse(offset) => {
                 let frame = self.call_stack.last_mut().unwrap();
-                let cond = frame.peek().cloned().unwrap_or(Value::Undefined);
-                
-                // For simplicity, treat 0 as false, everything else as true
-                let is_false = match cond {
-                    Value::Number(n) => n == 0.0,
-                    Value::Undefined => true,
-                    _ => false,
-                };
+                let cond = frame.pop()?;
                 
-                if is_false {
+                if !cond.is_truthy() {
                     frame.ip = ((frame.ip as isize) + offset) as usize;
                 }
             }
             
+            Instruction::JumpIfTrueOrPop(offset) => {
+                let frame = self.call_stack.last_mut().unwrap();
+                frame.jump_or_pop(offset, Value::is_truthy)?;
+            }
+            
+            Instruction::JumpIfFalseOrPop(offset) => {
+                let frame = self.call_stack.last_mut().unwrap();
+                frame.jump_or_pop(offset, |value| !value.is_truthy())?;
+            }
+            
+            Instruction::JumpIfNotNullishOrPop(offset) => {
+                let frame = self.call_stack.last_mut().unwrap();
+                frame.jump_or_pop(offset, |value| !value.is_nullish())?;
+            }
+            
             Instruction::Print => {
                 // Print instruction: pop value from stack and print it
                 if let Some(value) = frame.stack.pop() {
@@ -255,17 +851,44 @@ impl Ignition {
                     // Push undefined back (print returns undefined)
                     frame.stack.push(Value::Undefined);
                 } else {
-                    return Err(RuntimeError::StackOverflow);
+                    return Err(stack_underflow());
+                }
+            }
+            
+            Instruction::Call(arg_count) => {
+                if frame.stack.len() < arg_count + 1 {
+                    return Err(stack_underflow());
+                }
+                let args = frame.stack.split_off(frame.stack.len() - arg_count);
+                let callee = frame.pop()?;
+                self.call_function(callee, args)?;
+            }
+            
+            Instruction::CallMethod(arg_count) => {
+                if frame.stack.len() < arg_count + 2 {
+                    return Err(stack_underflow());
                 }
+                let args = frame.stack.split_off(frame.stack.len() - arg_count);
+                let key = frame.pop()?;
+                let receiver = frame.pop()?;
+                self.call_method(receiver, key, args)?;
+            }
+            
+            Instruction::CallWithSpread => {
+                let args = spread_arguments(frame.pop()?)?;
+                let callee = frame.pop()?;
+                self.call_function(callee, args)?;
             }
             
-            Instruction::Call(_arg_count) => {
-                // Simplified: just continue execution
-                // Full implementation would handle function calls
+            Instruction::CallMethodWithSpread => {
+                let args = spread_arguments(frame.pop()?)?;
+                let key = frame.pop()?;
+                let receiver = frame.pop()?;
+                self.call_method(receiver, key, args)?;
             }
         }
         
-        Ok(())
+        Ok(None)
     }
 }
 
@@ -275,9 +898,161 @@ impl Default for Ignition {
     }
 }
 
+/// Read `object[key]`, following JavaScript semantics for primitives
+fn get_property(object: &Value, key: &Value) -> Result<Value, RuntimeError> {
+    if let Value::Array(arr) = object {
+        let arr = arr.borrow();
+        return Ok(match key.to_array_index() {
+            Some(index) => arr.get(index).cloned().unwrap_or_default(),
+            None => match &*key.to_property_key()? {
+                "length" => Value::Number(arr.len() as f64),
+                name => arr.get_named(name).cloned().unwrap_or_default(),
+            },
+        });
+    }
+    
+    let key = key.to_property_key()?;
+    match object {
+        Value::Object(obj) => Ok(obj.borrow().get(&key).cloned().unwrap_or_default()),
+        Value::String(s) => {
+            if &*key == "length" {
+                return Ok(Value::Number(s.encode_utf16().count() as f64));
+            }
+            // Index into the string's UTF-16 code units
+            let unit = key.parse::<usize>().ok()
+                .filter(|index| index.to_string() == *key)
+                .and_then(|index| s.encode_utf16().nth(index));
+            Ok(unit
+                .map(|unit| Value::String(String::from_utf16_lossy(&[unit]).into()))
+                .unwrap_or_default())
+        }
+        Value::Null | Value::Undefined => Err(property_type_error(object)),
+        _ => Ok(Value::Undefined),
+    }
+}
+
+/// Write `arr[key] = value`
+fn set_array_property(arr: &ArrayRef, key: &Value, value: &Value) -> Result<(), RuntimeError> {
+    if let Some(index) = key.to_array_index() {
+        arr.borrow_mut().set(index, value.clone());
+        return Ok(());
+    }
+    
+    // The key may be this very array, so it is converted before borrowing it mutably
+    let key = key.to_property_key()?;
+    let mut arr = arr.borrow_mut();
+    if &*key == "length" {
+        // A length is an integer in 0..2^32
+        let len = value.to_number();
+        if !(len >= 0.0 && len.fract() == 0.0 && len <= u32::MAX as f64) {
+            return Err(RuntimeError::InvalidArrayLength { span: None });
+        }
+        arr.set_len(len as usize);
+    } else {
+        arr.set_named(key, value.clone());
+    }
+    Ok(())
+}
+
+/// Error for an instruction that needs more operands than the stack holds
+fn stack_underflow() -> RuntimeError {
+    internal_error("operand stack underflow")
+}
+
+fn internal_error(message: &str) -> RuntimeError {
+    RuntimeError::Internal { message: message.to_string() }
+}
+
+/// The elements of `arr` as at most `limit` call arguments, with holes read as `undefined`
+fn array_arguments(arr: &JsArray, limit: usize) -> Result<Vec<Value>, RuntimeError> {
+    if arr.len() > limit {
+        return Err(RuntimeError::TooManyArguments { span: None });
+    }
+    let mut values = vec![Value::Undefined; arr.len()];
+    for (index, value) in arr.entries() {
+        values[index] = value.clone();
+    }
+    Ok(values)
+}
+
+/// The arguments of a spread call, collected into an array by `AppendSpread`
+fn spread_arguments(args: Value) -> Result<Vec<Value>, RuntimeError> {
+    match args {
+        Value::Array(arr) => array_arguments(&arr.borrow(), MAX_ARGUMENTS),
+        _ => Err(RuntimeError::TypeError {
+            expected: "array".to_string(),
+            found: args.type_name().to_string(),
+            span: None,
+        }),
+    }
+}
+
+/// Call a built-in array method, or return `None` if there is no such method
+fn call_array_method(heap: &mut Heap, arr: &ArrayRef, name: &str, args: &[Value]) -> Result<Option<Value>, RuntimeError> {
+    // Only methods that modify the array borrow it mutably
+    let result = match name {
+        "push" => {
+            let mut arr = arr.borrow_mut();
+            // The new length must still be a valid array length
+            if arr.len() + args.len() > u32::MAX as usize {
+                return Err(RuntimeError::InvalidArrayLength { span: None });
+            }
+            for arg in args {
+                arr.push(arg.clone());
+            }
+            Value::Number(arr.len() as f64)
+        }
+        "pop" => arr.borrow_mut().pop().unwrap_or_default(),
+        "slice" => {
+            let arr = arr.borrow();
+            let len = arr.len();
+            let start = relative_index(args.first(), len, 0);
+            let end = relative_index(args.get(1), len, len).max(start);
+            Value::Array(heap.alloc_array(arr.slice(start, end)))
+        }
+        "indexOf" => {
+            let arr = arr.borrow();
+            let len = arr.len();
+            let search = args.first().cloned().unwrap_or_default();
+            let from = relative_index(args.get(1), len, 0);
+            let index = arr.entries()
+                .find(|&(index, element)| index >= from && element.strict_equals(&search))
+                .map_or(-1.0, |(index, _)| index as f64);
+            Value::Number(index)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+/// Resolve a possibly negative index argument against `len`, clamped to `0..=len`
+fn relative_index(arg: Option<&Value>, len: usize, default: usize) -> usize {
+    let n = match arg {
+        None | Some(Value::Undefined) => return default,
+        Some(value) => value.to_number(),
+    };
+    if n.is_nan() {
+        0
+    } else if n < 0.0 {
+        (len as f64 + n.trunc()).max(0.0) as usize
+    } else {
+        n.trunc().min(len as f64) as usize
+    }
+}
+
+/// Error for reading or writing a property of `null` or `undefined`
+fn property_type_error(object: &Value) -> RuntimeError {
+    RuntimeError::TypeError {
+        expected: "object".to_string(),
+        found: object.to_string(),
+        span: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bytecode::ExceptionHandler;
     
     #[test]
     fn test_call_frame_creation() {
@@ -329,6 +1104,159 @@ mod tests {
         assert_eq!(result, Value::Number(30.0));
     }
     
+    #[test]
+    fn test_execute_string_concat() {
+        let mut chunk = BytecodeChunk::new();
+        let idx1 = chunk.add_constant(Value::String("n = ".into()));
+        let idx2 = chunk.add_constant(Value::Number(4.0));
+        chunk.emit(Instruction::LoadConst(idx1));
+        chunk.emit(Instruction::LoadConst(idx2));
+        chunk.emit(Instruction::Add);
+        
+        let mut interpreter = Ignition::new();
+        let result = interpreter.execute(chunk).unwrap();
+        
+        assert_eq!(result, Value::String("n = 4".into()));
+    }
+    
+    #[test]
+    fn test_execute_comparison() {
+        let mut chunk = BytecodeChunk::new();
+        let idx1 = chunk.add_constant(Value::Number(1.0));
+        let idx2 = chunk.add_constant(Value::Number(2.0));
+        chunk.emit(Instruction::LoadConst(idx1));
+        chunk.emit(Instruction::LoadConst(idx2));
+        chunk.emit(Instruction::Less);
+        
+        let mut interpreter = Ignition::new();
+        let result = interpreter.execute(chunk).unwrap();
+        
+        assert_eq!(result, Value::Boolean(true));
+    }
+    
+    #[test]
+    fn test_execute_jump_if_false_pops_condition() {
+        let mut chunk = BytecodeChunk::new();
+        let cond = chunk.add_constant(Value::Boolean(false));
+        let value = chunk.add_constant(Value::Number(7.0));
+        chunk.emit(Instruction::LoadConst(cond));
+        chunk.emit(Instruction::JumpIfFalse(1));
+        chunk.emit(Instruction::LoadConst(cond));
+        chunk.emit(Instruction::LoadConst(value));
+        chunk.emit(Instruction::Pop);
+        
+        let mut interpreter = Ignition::new();
+        let result = interpreter.execute(chunk).unwrap();
+        
+        // Nothing is left behind once the condition is consumed
+        assert_eq!(result, Value::Undefined);
+    }
+    
+    #[test]
+    fn test_execute_object_properties() {
+        // let o = { n: 1 }; o.n++ leaves the old value, then reads the new one
+        let mut chunk = BytecodeChunk::new();
+        chunk.set_local_count(1);
+        let key = chunk.add_constant(Value::String("n".into()));
+        let one = chunk.add_constant(Value::Number(1.0));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::CreateObject(1));
+        chunk.emit(Instruction::StoreLocal(0));
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::Dup2);
+        chunk.emit(Instruction::GetProperty);
+        chunk.emit(Instruction::Dup);
+        chunk.emit(Instruction::MoveUnder(3));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::Add);
+        chunk.emit(Instruction::SetProperty);
+        chunk.emit(Instruction::Pop);
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::GetProperty);
+        chunk.emit(Instruction::Add);
+        
+        let mut interpreter = Ignition::new();
+        assert_eq!(interpreter.execute(chunk).unwrap(), Value::Number(3.0));
+    }
+    
+    #[test]
+    fn test_execute_array_methods() {
+        // [1, 2, 3].slice(-2).indexOf(3)
+        let mut chunk = BytecodeChunk::new();
+        let values: Vec<_> = [1.0, 2.0, 3.0, -2.0].iter()
+            .map(|n| chunk.add_constant(Value::Number(*n)))
+            .collect();
+        let slice = chunk.add_constant(Value::String("slice".into()));
+        let index_of = chunk.add_constant(Value::String("indexOf".into()));
+        for &idx in &values[..3] {
+            chunk.emit(Instruction::LoadConst(idx));
+        }
+        chunk.emit(Instruction::CreateArray(3));
+        chunk.emit(Instruction::LoadConst(slice));
+        chunk.emit(Instruction::LoadConst(values[3]));
+        chunk.emit(Instruction::CallMethod(1));
+        chunk.emit(Instruction::LoadConst(index_of));
+        chunk.emit(Instruction::LoadConst(values[2]));
+        chunk.emit(Instruction::CallMethod(1));
+        
+        let mut interpreter = Ignition::new();
+        assert_eq!(interpreter.execute(chunk).unwrap(), Value::Number(1.0));
+    }
+    
+    #[test]
+    fn test_execute_uninitialized_binding() {
+        let mut chunk = BytecodeChunk::new();
+        chunk.set_local_count(1);
+        let name = chunk.add_constant(Value::String("x".into()));
+        chunk.emit(Instruction::LoadHole);
+        chunk.emit(Instruction::StoreLocal(0));
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::CheckInitialized(name));
+        
+        let mut interpreter = Ignition::new();
+        assert_eq!(
+            interpreter.execute(chunk),
+            Err(RuntimeError::UninitializedVariable { name: "x".to_string(), span: None })
+        );
+    }
+    
+    #[test]
+    fn test_execute_property_of_undefined() {
+        let mut chunk = BytecodeChunk::new();
+        let key = chunk.add_constant(Value::String("x".into()));
+        let undefined = chunk.add_constant(Value::Undefined);
+        chunk.emit(Instruction::LoadConst(undefined));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::GetProperty);
+        
+        let mut interpreter = Ignition::new();
+        assert!(matches!(
+            interpreter.execute(chunk),
+            Err(RuntimeError::TypeError { found, .. }) if found == "undefined"
+        ));
+    }
+    
+    #[test]
+    fn test_execute_after_error() {
+        let mut failing = BytecodeChunk::new();
+        let idx1 = failing.add_constant(Value::Number(1.0));
+        let idx2 = failing.add_constant(Value::Number(0.0));
+        failing.emit(Instruction::LoadConst(idx1));
+        failing.emit(Instruction::LoadConst(idx2));
+        failing.emit(Instruction::Div);
+        
+        let mut ok = BytecodeChunk::new();
+        let idx = ok.add_constant(Value::Number(3.0));
+        ok.emit(Instruction::LoadConst(idx));
+        
+        let mut interpreter = Ignition::new();
+        assert!(interpreter.execute(failing).is_err());
+        assert_eq!(interpreter.execute(ok).unwrap(), Value::Number(3.0));
+    }
+    
     #[test]
     fn test_execute_arithmetic() {
         let mut chunk = BytecodeChunk::new();
@@ -349,6 +1277,147 @@ mod tests {
         assert_eq!(result, Value::Number(30.0));
     }
     
+    #[test]
+    fn test_execute_call() {
+        // function double(x) { return x + x; }
+        let mut body = BytecodeChunk::new();
+        body.set_local_count(1);
+        body.emit(Instruction::LoadLocal(0));
+        body.emit(Instruction::LoadLocal(0));
+        body.emit(Instruction::Add);
+        body.emit(Instruction::Return);
+        
+        let mut chunk = BytecodeChunk::new();
+        let arg = chunk.add_constant(Value::Number(21.0));
+        chunk.emit(Instruction::MakeClosure(1));
+        chunk.emit(Instruction::LoadConst(arg));
+        chunk.emit(Instruction::Call(1));
+        
+        let mut interpreter = Ignition::new();
+        interpreter.register_function(FunctionInfo::new(1, "double".to_string(), 1, body));
+        let result = interpreter.execute(chunk).unwrap();
+        
+        assert_eq!(result, Value::Number(42.0));
+        assert_eq!(interpreter.profiler().borrow().get_count(1), 1);
+    }
+    
+    #[test]
+    fn test_closure_shares_cell_with_frame() {
+        // function bump() { n += 1 }, closing over the caller's cell 0
+        let mut body = BytecodeChunk::new();
+        let one = body.add_constant(Value::Number(1.0));
+        body.emit(Instruction::LoadUpvalue(0));
+        body.emit(Instruction::LoadConst(one));
+        body.emit(Instruction::Add);
+        body.emit(Instruction::StoreUpvalue(0));
+        
+        let mut chunk = BytecodeChunk::new();
+        chunk.set_local_count(1);
+        let zero = chunk.add_constant(Value::Number(0.0));
+        chunk.emit(Instruction::LoadConst(zero));
+        chunk.emit(Instruction::StoreUpvalue(0));
+        chunk.emit(Instruction::MakeClosure(1));
+        chunk.emit(Instruction::StoreLocal(0));
+        for _ in 0..2 {
+            chunk.emit(Instruction::LoadLocal(0));
+            chunk.emit(Instruction::Call(0));
+            chunk.emit(Instruction::Pop);
+        }
+        chunk.emit(Instruction::LoadUpvalue(0));
+        
+        let mut interpreter = Ignition::new();
+        interpreter.register_function(FunctionInfo::new(1, "bump".to_string(), 0, body).with_upvalues(vec![0], 0));
+        
+        // The script frame owns one cell, as a function frame with cell_count 1 would
+        let mut frame = CallFrame::new(chunk, 0);
+        frame.upvalues.push(Upvalue::default());
+        interpreter.call_stack.push(frame);
+        
+        assert_eq!(interpreter.run(0).unwrap(), Value::Number(2.0));
+    }
+    
+    #[test]
+    fn test_globals_survive_between_scripts() {
+        let mut first = BytecodeChunk::new();
+        first.set_local_count(2);
+        let value = first.add_constant(Value::Number(7.0));
+        first.emit(Instruction::LoadConst(value));
+        first.emit(Instruction::StoreLocal(0));
+        first.emit(Instruction::LoadConst(value));
+        first.emit(Instruction::StoreLocal(1));
+        
+        let mut interpreter = Ignition::new();
+        interpreter.execute(first).unwrap();
+        assert_eq!(interpreter.globals(), [Value::Number(7.0), Value::Number(7.0)]);
+        
+        // Slot 1 belonged to a block, so the next script starts it afresh
+        interpreter.retain_globals(1);
+        let mut second = BytecodeChunk::new();
+        second.set_local_count(2);
+        second.emit(Instruction::LoadLocal(0));
+        second.emit(Instruction::LoadLocal(1));
+        second.emit(Instruction::StrictEqual);
+        
+        assert_eq!(interpreter.execute(second).unwrap(), Value::Boolean(false));
+        assert_eq!(interpreter.globals()[0], Value::Number(7.0));
+    }
+    
+    #[test]
+    fn test_global_lookup_by_name() {
+        let mut chunk = BytecodeChunk::new();
+        chunk.set_local_count(1);
+        let name = chunk.add_constant(Value::String("answer".into()));
+        let value = chunk.add_constant(Value::Number(42.0));
+        chunk.emit(Instruction::LoadConst(value));
+        chunk.emit(Instruction::StoreGlobalName(name, Span::new(0, 6)));
+        chunk.emit(Instruction::LoadGlobalName(name, Span::new(10, 16)));
+        
+        let mut interpreter = Ignition::new();
+        interpreter.define_global("answer".to_string(), 0);
+        assert_eq!(interpreter.execute(chunk.clone()).unwrap(), Value::Number(42.0));
+        
+        let mut interpreter = Ignition::new();
+        let err = interpreter.execute(chunk).unwrap_err();
+        assert_eq!(err, RuntimeError::ReferenceError {
+            name: "answer".to_string(),
+            span: Span::new(0, 6),
+        });
+    }
+    
+    #[test]
+    fn test_fresh_cell_detaches_earlier_closures() {
+        // function read() { return n }, closing over the script's cell 0
+        let mut body = BytecodeChunk::new();
+        body.emit(Instruction::LoadUpvalue(0));
+        body.emit(Instruction::Return);
+        
+        let mut chunk = BytecodeChunk::new();
+        chunk.set_local_count(1);
+        let one = chunk.add_constant(Value::Number(1.0));
+        let two = chunk.add_constant(Value::Number(2.0));
+        // The script frame starts without cells; FreshCell creates the one it needs
+        chunk.emit(Instruction::FreshCell(0));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::StoreUpvalue(0));
+        chunk.emit(Instruction::MakeClosure(1));
+        chunk.emit(Instruction::StoreLocal(0));
+        chunk.emit(Instruction::FreshCell(0));
+        chunk.emit(Instruction::LoadUpvalue(0));
+        chunk.emit(Instruction::LoadConst(two));
+        chunk.emit(Instruction::Add);
+        chunk.emit(Instruction::StoreUpvalue(0));
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::Call(0));
+        chunk.emit(Instruction::LoadUpvalue(0));
+        chunk.emit(Instruction::Add);
+        
+        let mut interpreter = Ignition::new();
+        interpreter.register_function(FunctionInfo::new(1, "read".to_string(), 0, body).with_upvalues(vec![0], 0));
+        
+        // The closure still sees 1; the frame's new cell holds the copy updated to 3
+        assert_eq!(interpreter.execute(chunk).unwrap(), Value::Number(4.0));
+    }
+    
     #[test]
     fn test_execute_division_by_zero() {
         let mut chunk = BytecodeChunk::new();
@@ -363,6 +1432,105 @@ mod tests {
         let result = interpreter.execute(chunk);
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), RuntimeError::DivisionByZero));
+        assert!(matches!(result.unwrap_err(), RuntimeError::DivisionByZero { .. }));
+    }
+    
+    #[test]
+    fn test_spread_call_fills_rest_and_arguments() {
+        // Function 1 (a, ...rest) returns rest.length * 10 + arguments.length
+        let mut body = BytecodeChunk::new();
+        let length = body.add_constant(Value::String("length".into()));
+        let ten = body.add_constant(Value::Number(10.0));
+        body.emit(Instruction::LoadLocal(1));
+        body.emit(Instruction::LoadConst(length));
+        body.emit(Instruction::GetProperty);
+        body.emit(Instruction::LoadConst(ten));
+        body.emit(Instruction::Mul);
+        body.emit(Instruction::LoadLocal(2));
+        body.emit(Instruction::LoadConst(length));
+        body.emit(Instruction::GetProperty);
+        body.emit(Instruction::Add);
+        body.emit(Instruction::Return);
+        body.set_local_count(3);
+        
+        // f(1, ..."abc")
+        let mut chunk = BytecodeChunk::new();
+        let one = chunk.add_constant(Value::Number(1.0));
+        let abc = chunk.add_constant(Value::String("abc".into()));
+        chunk.emit(Instruction::MakeClosure(1));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::CreateArray(1));
+        chunk.emit(Instruction::LoadConst(abc));
+        chunk.emit(Instruction::AppendSpread);
+        chunk.emit(Instruction::CallWithSpread);
+        
+        let mut interpreter = Ignition::new();
+        let info = FunctionInfo::new(1, "f".to_string(), 1, body).with_rest(true).with_arguments(true);
+        interpreter.register_function(info);
+        assert_eq!(interpreter.execute(chunk).unwrap(), Value::Number(34.0));
+    }
+    
+    #[test]
+    fn test_throw_unwinds_to_handler_in_caller() {
+        // Function 1 throws 7; the script catches it and adds 1
+        let mut body = BytecodeChunk::new();
+        let seven = body.add_constant(Value::Number(7.0));
+        body.emit(Instruction::LoadConst(seven));
+        body.emit(Instruction::Throw);
+        
+        let mut chunk = BytecodeChunk::new();
+        let one = chunk.add_constant(Value::Number(1.0));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::MakeClosure(1));
+        chunk.emit(Instruction::Call(0));
+        chunk.emit(Instruction::Return);
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::Add);
+        chunk.add_handler(ExceptionHandler { start: 1, end: 3, target: 4, stack_depth: 0 });
+        
+        let mut interpreter = Ignition::new();
+        interpreter.register_function(FunctionInfo::new(1, "thrower".to_string(), 0, body));
+        assert_eq!(interpreter.execute(chunk).unwrap(), Value::Number(8.0));
+    }
+    
+    #[test]
+    fn test_internal_errors_become_error_objects() {
+        let mut chunk = BytecodeChunk::new();
+        let one = chunk.add_constant(Value::Number(1.0));
+        let zero = chunk.add_constant(Value::Number(0.0));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::LoadConst(zero));
+        chunk.emit(Instruction::Div);
+        chunk.emit(Instruction::Return);
+        chunk.add_handler(ExceptionHandler { start: 0, end: 3, target: 4, stack_depth: 0 });
+        
+        let mut interpreter = Ignition::new();
+        let Value::Object(error) = interpreter.execute(chunk).unwrap() else {
+            panic!("Expected an error object");
+        };
+        assert_eq!(error.borrow().get("name"), Some(&Value::String("RangeError".into())));
+        
+        // Without a handler the error is reported as before
+        let mut chunk = BytecodeChunk::new();
+        let value = chunk.add_constant(Value::String("oops".into()));
+        chunk.emit(Instruction::LoadConst(value));
+        chunk.emit(Instruction::Throw);
+        let err = Ignition::new().execute(chunk).unwrap_err();
+        assert_eq!(err.to_string(), "Uncaught oops");
+    }
+    
+    #[test]
+    fn test_stack_underflow_is_not_catchable() {
+        // A handler covers the bad `Pop`, but malformed bytecode is not a JS error
+        let mut chunk = BytecodeChunk::new();
+        chunk.emit(Instruction::Pop);
+        chunk.emit(Instruction::Return);
+        chunk.emit(Instruction::Return);
+        chunk.add_handler(ExceptionHandler { start: 0, end: 1, target: 2, stack_depth: 0 });
+        
+        let err = Ignition::new().execute(chunk).unwrap_err();
+        assert_eq!(err, RuntimeError::Internal { message: "operand stack underflow".to_string() });
+        assert!(!err.is_catchable());
+        assert_eq!(err.to_string(), "Internal error: operand stack underflow");
     }
 }
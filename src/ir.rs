@@ -1,5 +1,7 @@
 // TurboFan IR (Intermediate Representation)
 
+use crate::types::FunctionId;
+
 /// Node ID for IR nodes
 pub type NodeId = usize;
 
@@ -7,9 +9,34 @@ pub type NodeId = usize;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Number,
+    Boolean,
     Unknown,
 }
 
+/// Comparison operators for `Compare` nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    StrictEqual,
+    StrictNotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+/// Short-circuiting operators for `ShortCircuit` nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortCircuitOp {
+    /// `left && right`
+    And,
+    /// `left || right`
+    Or,
+    /// `left ?? right`
+    Nullish,
+}
+
 /// IR Node representing operations in SSA form
 #[derive(Debug, Clone, PartialEq)]
 pub enum IRNode {
@@ -18,6 +45,35 @@ pub enum IRNode {
         value: f64,
         id: NodeId,
     },
+    /// Non-numeric constant, referenced by its index in the chunk's constant pool
+    HeapConstant {
+        index: usize,
+        id: NodeId,
+    },
+    /// The marker held by an uninitialised `let`/`const` binding
+    Hole {
+        id: NodeId,
+    },
+    /// Deoptimize if the value is the hole, otherwise pass it through
+    CheckHole {
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Throw a value
+    Throw {
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Throw the pending exception of a `finally` block again
+    Rethrow {
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Fail an assignment to a `const`
+    ThrowConstAssignment {
+        name_index: usize,
+        id: NodeId,
+    },
     /// Addition operation
     Add {
         left: NodeId,
@@ -42,6 +98,35 @@ pub enum IRNode {
         right: NodeId,
         id: NodeId,
     },
+    /// Numeric negation
+    Negate {
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Logical negation producing a boolean
+    Not {
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Either operand, selected by the short-circuit operator
+    ShortCircuit {
+        op: ShortCircuitOp,
+        left: NodeId,
+        right: NodeId,
+        id: NodeId,
+    },
+    /// Comparison producing a boolean
+    Compare {
+        op: CompareOp,
+        left: NodeId,
+        right: NodeId,
+        id: NodeId,
+    },
+    /// Conditional branch taken when the condition is falsy
+    Branch {
+        cond: NodeId,
+        id: NodeId,
+    },
     /// Load local variable
     LoadLocal {
         index: usize,
@@ -53,12 +138,104 @@ pub enum IRNode {
         value: NodeId,
         id: NodeId,
     },
+    /// Load top-level (script) variable
+    LoadGlobal {
+        index: usize,
+        id: NodeId,
+    },
+    /// Store top-level (script) variable
+    StoreGlobal {
+        index: usize,
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Load a global by name
+    LoadGlobalName {
+        name_index: usize,
+        id: NodeId,
+    },
+    /// Store a global by name
+    StoreGlobalName {
+        name_index: usize,
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Load a variable from a closure cell
+    LoadUpvalue {
+        index: usize,
+        id: NodeId,
+    },
+    /// Store a variable to a closure cell
+    StoreUpvalue {
+        index: usize,
+        value: NodeId,
+        id: NodeId,
+    },
+    /// Give the frame a new closure cell holding a copy of the old one's value
+    FreshCell {
+        index: usize,
+        id: NodeId,
+    },
+    /// Create a closure of a function
+    MakeClosure {
+        func_id: FunctionId,
+        id: NodeId,
+    },
+    /// Object literal from alternating key/value nodes
+    CreateObject {
+        entries: Vec<NodeId>,
+        id: NodeId,
+    },
+    /// Array literal from element nodes
+    CreateArray {
+        elements: Vec<NodeId>,
+        id: NodeId,
+    },
+    /// Append the elements of `iterable` to `array`
+    AppendSpread {
+        array: NodeId,
+        iterable: NodeId,
+        id: NodeId,
+    },
+    /// Property read `object[key]`
+    GetProperty {
+        object: NodeId,
+        key: NodeId,
+        id: NodeId,
+    },
+    /// Property write `object[key] = value`
+    SetProperty {
+        object: NodeId,
+        key: NodeId,
+        value: NodeId,
+        id: NodeId,
+    },
     /// Function call
     Call {
         callee: NodeId,
         args: Vec<NodeId>,
         id: NodeId,
     },
+    /// Method call `object[key](args)`
+    CallMethod {
+        object: NodeId,
+        key: NodeId,
+        args: Vec<NodeId>,
+        id: NodeId,
+    },
+    /// Function call with the arguments in an array
+    CallWithSpread {
+        callee: NodeId,
+        args: NodeId,
+        id: NodeId,
+    },
+    /// Method call with the arguments in an array
+    CallMethodWithSpread {
+        object: NodeId,
+        key: NodeId,
+        args: NodeId,
+        id: NodeId,
+    },
     /// Return statement
     Return {
         value: NodeId,
@@ -77,13 +254,40 @@ impl IRNode {
     pub fn id(&self) -> NodeId {
         match self {
             IRNode::Constant { id, .. } => *id,
+            IRNode::HeapConstant { id, .. } => *id,
+            IRNode::Hole { id, .. } => *id,
+            IRNode::CheckHole { id, .. } => *id,
+            IRNode::Throw { id, .. } => *id,
+            IRNode::Rethrow { id, .. } => *id,
+            IRNode::ThrowConstAssignment { id, .. } => *id,
             IRNode::Add { id, .. } => *id,
             IRNode::Sub { id, .. } => *id,
             IRNode::Mul { id, .. } => *id,
             IRNode::Div { id, .. } => *id,
+            IRNode::Negate { id, .. } => *id,
+            IRNode::Not { id, .. } => *id,
+            IRNode::ShortCircuit { id, .. } => *id,
+            IRNode::Compare { id, .. } => *id,
+            IRNode::Branch { id, .. } => *id,
             IRNode::LoadLocal { id, .. } => *id,
             IRNode::StoreLocal { id, .. } => *id,
+            IRNode::LoadGlobal { id, .. } => *id,
+            IRNode::StoreGlobal { id, .. } => *id,
+            IRNode::LoadGlobalName { id, .. } => *id,
+            IRNode::StoreGlobalName { id, .. } => *id,
+            IRNode::LoadUpvalue { id, .. } => *id,
+            IRNode::StoreUpvalue { id, .. } => *id,
+            IRNode::FreshCell { id, .. } => *id,
+            IRNode::MakeClosure { id, .. } => *id,
+            IRNode::CreateObject { id, .. } => *id,
+            IRNode::CreateArray { id, .. } => *id,
+            IRNode::AppendSpread { id, .. } => *id,
+            IRNode::GetProperty { id, .. } => *id,
+            IRNode::SetProperty { id, .. } => *id,
             IRNode::Call { id, .. } => *id,
+            IRNode::CallMethod { id, .. } => *id,
+            IRNode::CallWithSpread { id, .. } => *id,
+            IRNode::CallMethodWithSpread { id, .. } => *id,
             IRNode::Return { id, .. } => *id,
             IRNode::TypeGuard { id, .. } => *id,
         }
@@ -148,6 +352,41 @@ impl IR {
         id
     }
     
+    /// Add a numeric negation node
+    pub fn add_negate(&mut self, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Negate { value, id });
+        id
+    }
+    
+    /// Add a logical negation node
+    pub fn add_not(&mut self, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Not { value, id });
+        id
+    }
+    
+    /// Add a short-circuit node
+    pub fn add_short_circuit(&mut self, op: ShortCircuitOp, left: NodeId, right: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::ShortCircuit { op, left, right, id });
+        id
+    }
+    
+    /// Add a comparison node
+    pub fn add_compare(&mut self, op: CompareOp, left: NodeId, right: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Compare { op, left, right, id });
+        id
+    }
+    
+    /// Add a conditional branch node
+    pub fn add_branch(&mut self, cond: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Branch { cond, id });
+        id
+    }
+    
     /// Add a load local node
     pub fn add_load_local(&mut self, index: usize) -> NodeId {
         let id = self.next_id();
@@ -162,6 +401,139 @@ impl IR {
         id
     }
     
+    /// Add a load global node
+    pub fn add_load_global(&mut self, index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::LoadGlobal { index, id });
+        id
+    }
+    
+    /// Add a store global node
+    pub fn add_store_global(&mut self, index: usize, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::StoreGlobal { index, value, id });
+        id
+    }
+    
+    /// Add a load global by name node
+    pub fn add_load_global_name(&mut self, name_index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::LoadGlobalName { name_index, id });
+        id
+    }
+    
+    /// Add a store global by name node
+    pub fn add_store_global_name(&mut self, name_index: usize, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::StoreGlobalName { name_index, value, id });
+        id
+    }
+    
+    /// Add a load upvalue node
+    pub fn add_load_upvalue(&mut self, index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::LoadUpvalue { index, id });
+        id
+    }
+    
+    /// Add a store upvalue node
+    pub fn add_store_upvalue(&mut self, index: usize, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::StoreUpvalue { index, value, id });
+        id
+    }
+    
+    /// Add a fresh closure cell node
+    pub fn add_fresh_cell(&mut self, index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::FreshCell { index, id });
+        id
+    }
+    
+    /// Add a closure creation node
+    pub fn add_make_closure(&mut self, func_id: FunctionId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::MakeClosure { func_id, id });
+        id
+    }
+    
+    /// Add a constant pool reference node
+    pub fn add_heap_constant(&mut self, index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::HeapConstant { index, id });
+        id
+    }
+    
+    /// Add a hole node
+    pub fn add_hole(&mut self) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Hole { id });
+        id
+    }
+    
+    /// Add a hole check node
+    pub fn add_check_hole(&mut self, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CheckHole { value, id });
+        id
+    }
+    
+    /// Add a throw node
+    pub fn add_throw(&mut self, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Throw { value, id });
+        id
+    }
+    
+    /// Add a rethrow node
+    pub fn add_rethrow(&mut self, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::Rethrow { value, id });
+        id
+    }
+    
+    /// Add a `const` assignment failure node
+    pub fn add_throw_const_assignment(&mut self, name_index: usize) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::ThrowConstAssignment { name_index, id });
+        id
+    }
+    
+    /// Add an object creation node
+    pub fn add_create_object(&mut self, entries: Vec<NodeId>) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CreateObject { entries, id });
+        id
+    }
+    
+    /// Add an array creation node
+    pub fn add_create_array(&mut self, elements: Vec<NodeId>) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CreateArray { elements, id });
+        id
+    }
+    
+    /// Add a node appending an iterable's elements to an array
+    pub fn add_append_spread(&mut self, array: NodeId, iterable: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::AppendSpread { array, iterable, id });
+        id
+    }
+    
+    /// Add a property read node
+    pub fn add_get_property(&mut self, object: NodeId, key: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::GetProperty { object, key, id });
+        id
+    }
+    
+    /// Add a property write node
+    pub fn add_set_property(&mut self, object: NodeId, key: NodeId, value: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::SetProperty { object, key, value, id });
+        id
+    }
+    
     /// Add a call node
     pub fn add_call(&mut self, callee: NodeId, args: Vec<NodeId>) -> NodeId {
         let id = self.next_id();
@@ -169,6 +541,27 @@ impl IR {
         id
     }
     
+    /// Add a method call node
+    pub fn add_call_method(&mut self, object: NodeId, key: NodeId, args: Vec<NodeId>) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CallMethod { object, key, args, id });
+        id
+    }
+    
+    /// Add a call node whose arguments are in an array
+    pub fn add_call_with_spread(&mut self, callee: NodeId, args: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CallWithSpread { callee, args, id });
+        id
+    }
+    
+    /// Add a method call node whose arguments are in an array
+    pub fn add_call_method_with_spread(&mut self, object: NodeId, key: NodeId, args: NodeId) -> NodeId {
+        let id = self.next_id();
+        self.nodes.push(IRNode::CallMethodWithSpread { object, key, args, id });
+        id
+    }
+    
     /// Add a return node
     pub fn add_return(&mut self, value: NodeId) -> NodeId {
         let id = self.next_id();
@@ -274,6 +667,35 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_compare_and_branch() {
+        let mut ir = IR::new();
+        let left = ir.add_load_local(0);
+        let right = ir.add_constant(10.0);
+        let cmp = ir.add_compare(CompareOp::Less, left, right);
+        let branch = ir.add_branch(cmp);
+        
+        assert!(matches!(ir.get_node(cmp).unwrap(), IRNode::Compare { op: CompareOp::Less, .. }));
+        assert!(matches!(ir.get_node(branch).unwrap(), IRNode::Branch { cond, .. } if *cond == cmp));
+    }
+    
+    #[test]
+    fn test_unary_and_short_circuit() {
+        let mut ir = IR::new();
+        let left = ir.add_load_local(0);
+        let neg = ir.add_negate(left);
+        let not = ir.add_not(neg);
+        let right = ir.add_constant(1.0);
+        let or = ir.add_short_circuit(ShortCircuitOp::Or, not, right);
+        
+        assert!(matches!(ir.get_node(neg).unwrap(), IRNode::Negate { value, .. } if *value == left));
+        assert!(matches!(ir.get_node(not).unwrap(), IRNode::Not { value, .. } if *value == neg));
+        assert!(matches!(
+            ir.get_node(or).unwrap(),
+            IRNode::ShortCircuit { op: ShortCircuitOp::Or, left: l, right: r, .. } if *l == not && *r == right
+        ));
+    }
+    
     #[test]
     fn test_load_store_local() {
         let mut ir = IR::new();
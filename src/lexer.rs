@@ -7,48 +7,163 @@ use crate::types::Span;
 pub enum TokenKind {
     // Literals
     Number(f64),
+    String(String),
     Identifier(String),
+    /// Template without substitutions: `` `text` ``
+    NoSubstitutionTemplate(TemplatePart),
+    /// Start of a template, up to its first substitution: `` `text${ ``
+    TemplateHead(TemplatePart),
+    /// Text between two substitutions: `}text${`
+    TemplateMiddle(TemplatePart),
+    /// End of a template after its last substitution: `` }text` ``
+    TemplateTail(TemplatePart),
     
     // Keywords
     Let,
+    Const,
+    Var,
     Function,
     If,
     Else,
     For,
+    While,
+    Do,
+    Break,
+    Continue,
     Return,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    True,
+    False,
+    Null,
     
     // Operators
     Plus,
     Minus,
     Star,
     Slash,
+    PlusPlus,
+    MinusMinus,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
     Equal,
     EqualEqual,
+    EqualEqualEqual,
+    BangEqual,
+    BangEqualEqual,
     Less,
+    LessEqual,
     Greater,
+    GreaterEqual,
+    Bang,
+    AmpAmp,
+    PipePipe,
+    QuestionQuestion,
+    /// `=>` of an arrow function
+    Arrow,
     
     // Delimiters
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Semicolon,
+    Colon,
     Comma,
+    Dot,
+    /// `...` of a rest parameter or spread argument
+    Ellipsis,
     
     // Special
+    /// Malformed input, carrying a description of the lexical error
+    Error(String),
     Eof,
 }
 
+impl TokenKind {
+    /// The source text of a keyword token, which may still be used as a property name
+    pub fn keyword_name(&self) -> Option<&'static str> {
+        let name = match self {
+            TokenKind::Let => "let",
+            TokenKind::Const => "const",
+            TokenKind::Var => "var",
+            TokenKind::Function => "function",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::For => "for",
+            TokenKind::While => "while",
+            TokenKind::Do => "do",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Return => "return",
+            TokenKind::Throw => "throw",
+            TokenKind::Try => "try",
+            TokenKind::Catch => "catch",
+            TokenKind::Finally => "finally",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Null => "null",
+            _ => return None,
+        };
+        Some(name)
+    }
+    
+    /// Whether the token can end an operand: an identifier, a literal, `)` or `]`.
+    /// Keywords, including reserved words read as identifiers, cannot.
+    pub fn ends_operand(&self) -> bool {
+        match self {
+            TokenKind::Identifier(name) => !is_reserved_word(name),
+            TokenKind::Number(_)
+            | TokenKind::String(_)
+            | TokenKind::NoSubstitutionTemplate(_)
+            | TokenKind::TemplateTail(_)
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null
+            | TokenKind::RightParen
+            | TokenKind::RightBracket => true,
+            _ => false,
+        }
+    }
+}
+
+/// Reserved words without a token of their own, which are lexed as identifiers
+fn is_reserved_word(name: &str) -> bool {
+    matches!(
+        name,
+        "case" | "default" | "delete" | "in" | "instanceof" | "new" | "switch" | "typeof" | "void" | "yield"
+    )
+}
+
+/// The text of one piece of a template literal
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplatePart {
+    /// Text with escapes decoded, or `None` if it has an invalid escape, which
+    /// only tagged templates allow
+    pub cooked: Option<String>,
+    /// Text as written, with line terminators normalised to `\n`
+    pub raw: String,
+}
+
 /// A token with its kind and location
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
+    /// Whether a line terminator (possibly inside a comment) comes between this
+    /// token and the previous one
+    pub newline_before: bool,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, span: Span) -> Self {
-        Self { kind, span }
+        Self { kind, span, newline_before: false }
     }
 }
 
@@ -57,16 +172,26 @@ pub struct Lexer {
     source: Vec<char>,
     position: usize,
     current_char: Option<char>,
+    /// Number of `{` currently open
+    brace_depth: usize,
+    /// Brace depth at each open template substitution; the `}` that returns to
+    /// it resumes the template
+    template_stack: Vec<usize>,
+    /// Where the last token scanned ends, if it ends an operand
+    operand_end: Option<usize>,
 }
 
 impl Lexer {
     pub fn new(source: String) -> Self {
         let chars: Vec<char> = source.chars().collect();
-        let current_char = chars.get(0).copied();
+        let current_char = chars.first().copied();
         Self {
             source: chars,
             position: 0,
             current_char,
+            brace_depth: 0,
+            template_stack: Vec::new(),
+            operand_end: None,
         }
     }
     
@@ -76,38 +201,357 @@ impl Lexer {
         self.current_char = self.source.get(self.position).copied();
     }
     
-    /// Peek at the next character without advancing
+    /// Skip whitespace and comments, returning whether a line terminator was
+    /// crossed, or an error token for an unterminated block comment
+    fn skip_whitespace(&mut self) -> Result<bool, Token> {
+        let mut newline = false;
+        while let Some(ch) = self.current_char {
+            match (ch, self.peek()) {
+                (ch, _) if ch.is_whitespace() => {
+                    newline |= is_line_terminator(ch);
+                    self.advance();
+                }
+                ('/', Some('/')) => {
+                    // The terminator ending the comment is left for the next round
+                    while self.current_char.is_some_and(|ch| !is_line_terminator(ch)) {
+                        self.advance();
+                    }
+                }
+                ('/', Some('*')) => {
+                    let start = self.position;
+                    self.advance();
+                    self.advance();
+                    loop {
+                        match (self.current_char, self.peek()) {
+                            (Some('*'), Some('/')) => {
+                                self.advance();
+                                self.advance();
+                                break;
+                            }
+                            (Some(ch), _) => {
+                                newline |= is_line_terminator(ch);
+                                self.advance();
+                            }
+                            (None, _) => {
+                                let mut token = Token::new(
+                                    TokenKind::Error("Unterminated block comment".to_string()),
+                                    Span::new(start, self.position),
+                                );
+                                token.newline_before = newline;
+                                return Err(token);
+                            }
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(newline)
+    }
+    
+    /// Look at the character after the current one
     fn peek(&self) -> Option<char> {
         self.source.get(self.position + 1).copied()
     }
     
-    /// Skip whitespace characters
-    fn skip_whitespace(&mut self) {
-        while let Some(ch) = self.current_char {
-            if ch.is_whitespace() {
+    /// Scan a number literal: decimal with optional fraction and exponent, or a
+    /// `0x`/`0o`/`0b` integer, with `_` separators allowed between digits
+    fn scan_number(&mut self) -> Token {
+        let start = self.position;
+        let kind = match self.scan_numeric_value() {
+            Ok(value) => TokenKind::Number(value),
+            Err(message) => {
+                // Skip the rest of the malformed literal
+                while self.current_char.is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == '.') {
+                    self.advance();
+                }
+                TokenKind::Error(message)
+            }
+        };
+        Token::new(kind, Span::new(start, self.position))
+    }
+    
+    fn scan_numeric_value(&mut self) -> Result<f64, String> {
+        let radix = match (self.current_char, self.peek()) {
+            (Some('0'), Some('x' | 'X')) => Some((16, "hexadecimal")),
+            (Some('0'), Some('o' | 'O')) => Some((8, "octal")),
+            (Some('0'), Some('b' | 'B')) => Some((2, "binary")),
+            _ => None,
+        };
+        
+        let value = if let Some((radix, name)) = radix {
+            self.advance();
+            self.advance();
+            let digits = self.scan_digits(radix)?;
+            if digits.is_empty() {
+                return Err(format!("Missing {} digits", name));
+            }
+            if let Some(ch) = self.current_char.filter(char::is_ascii_digit) {
+                return Err(format!("Invalid digit '{}' in {} literal", ch, name));
+            }
+            digits.chars()
+                .filter_map(|digit| digit.to_digit(radix))
+                .fold(0.0, |value, digit| value * radix as f64 + digit as f64)
+        } else {
+            let mut text = String::new();
+            if self.current_char == Some('0') {
                 self.advance();
+                match self.current_char {
+                    Some(ch) if ch.is_ascii_digit() => {
+                        return Err("Legacy octal literals are not allowed; use the 0o prefix".to_string());
+                    }
+                    Some('_') => {
+                        return Err("Numeric separators are not allowed after a leading 0".to_string());
+                    }
+                    _ => text.push('0'),
+                }
             } else {
-                break;
+                text.push_str(&self.scan_digits(10)?);
+            }
+            
+            if self.current_char == Some('.') {
+                self.advance();
+                let fraction = self.scan_digits(10)?;
+                if text.is_empty() && fraction.is_empty() {
+                    return Err("Expected digits in number literal".to_string());
+                }
+                text.push('.');
+                text.push_str(&fraction);
+            }
+            
+            if let Some('e' | 'E') = self.current_char {
+                self.advance();
+                text.push('e');
+                if let Some(sign @ ('+' | '-')) = self.current_char {
+                    text.push(sign);
+                    self.advance();
+                }
+                let exponent = self.scan_digits(10)?;
+                if exponent.is_empty() {
+                    return Err("Missing digits in exponent".to_string());
+                }
+                text.push_str(&exponent);
+            }
+            
+            if text.starts_with('.') {
+                text.insert(0, '0');
             }
+            text.parse::<f64>().map_err(|_| format!("Invalid number literal '{}'", text))?
+        };
+        
+        match self.current_char {
+            Some('n') => Err("BigInt literals are not supported".to_string()),
+            Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '$' => {
+                Err("Identifier starts immediately after number literal".to_string())
+            }
+            _ => Ok(value),
         }
     }
     
-    /// Scan a number literal
-    fn scan_number(&mut self) -> Token {
-        let start = self.position;
-        let mut num_str = String::new();
-        
+    /// Scan digits of `radix`, dropping single `_` separators between them
+    fn scan_digits(&mut self, radix: u32) -> Result<String, String> {
+        let mut digits = String::new();
         while let Some(ch) = self.current_char {
-            if ch.is_ascii_digit() || ch == '.' {
-                num_str.push(ch);
-                self.advance();
+            if ch.is_digit(radix) {
+                digits.push(ch);
+            } else if ch == '_' {
+                let next_is_digit = self.peek().is_some_and(|next| next.is_digit(radix));
+                if digits.is_empty() || !next_is_digit {
+                    return Err("Numeric separators are only allowed between digits".to_string());
+                }
             } else {
                 break;
             }
+            self.advance();
         }
+        Ok(digits)
+    }
+    
+    /// Scan a string literal delimited by `quote`, decoding escape sequences
+    fn scan_string(&mut self, quote: char) -> Token {
+        let start = self.position;
+        self.advance(); // opening quote
+        let mut value = String::new();
         
-        let value = num_str.parse::<f64>().unwrap_or(0.0);
-        Token::new(TokenKind::Number(value), Span::new(start, self.position))
+        loop {
+            match self.current_char {
+                None | Some('\n') | Some('\r') => {
+                    return Token::new(
+                        TokenKind::Error("Unterminated string literal".to_string()),
+                        Span::new(start, self.position),
+                    );
+                }
+                Some(ch) if ch == quote => {
+                    self.advance();
+                    break;
+                }
+                Some('\\') => {
+                    self.advance();
+                    if let Err(message) = self.scan_escape(&mut value) {
+                        // Skip to the closing quote so the rest of the line lexes sensibly
+                        while let Some(ch) = self.current_char {
+                            if ch == quote || ch == '\n' {
+                                break;
+                            }
+                            self.advance();
+                        }
+                        if self.current_char == Some(quote) {
+                            self.advance();
+                        }
+                        return Token::new(TokenKind::Error(message), Span::new(start, self.position));
+                    }
+                }
+                Some(ch) => {
+                    value.push(ch);
+                    self.advance();
+                }
+            }
+        }
+        
+        Token::new(TokenKind::String(value), Span::new(start, self.position))
+    }
+    
+    /// Scan a template piece starting at its opening `` ` `` or at the `}`
+    /// closing a substitution, up to the next `${` or the closing `` ` ``
+    fn scan_template(&mut self) -> Token {
+        let start = self.position;
+        let opening = self.current_char == Some('`');
+        if !opening {
+            self.template_stack.pop();
+        }
+        self.advance();
+        
+        let mut cooked = Some(String::new());
+        let mut raw = String::new();
+        let substitution = loop {
+            match self.current_char {
+                None => {
+                    return Token::new(
+                        TokenKind::Error("Unterminated template literal".to_string()),
+                        Span::new(start, self.position),
+                    );
+                }
+                Some('`') => {
+                    self.advance();
+                    break false;
+                }
+                Some('$') if self.peek() == Some('{') => {
+                    self.advance();
+                    self.advance();
+                    break true;
+                }
+                Some('\\') => {
+                    let escape_start = self.position;
+                    self.advance();
+                    let mut decoded = String::new();
+                    match self.scan_escape(&mut decoded) {
+                        Ok(()) => {
+                            if let Some(cooked) = &mut cooked {
+                                cooked.push_str(&decoded);
+                            }
+                        }
+                        Err(_) => cooked = None,
+                    }
+                    let escape: String = self.source[escape_start..self.position].iter().collect();
+                    raw.push_str(&escape.replace("\r\n", "\n").replace('\r', "\n"));
+                }
+                Some(ch) => {
+                    self.advance();
+                    // `\r\n` and `\r` both read as `\n`
+                    let ch = if ch == '\r' {
+                        if self.current_char == Some('\n') {
+                            self.advance();
+                        }
+                        '\n'
+                    } else {
+                        ch
+                    };
+                    if let Some(cooked) = &mut cooked {
+                        cooked.push(ch);
+                    }
+                    raw.push(ch);
+                }
+            }
+        };
+        
+        if substitution {
+            self.template_stack.push(self.brace_depth);
+        }
+        let part = TemplatePart { cooked, raw };
+        let kind = match (opening, substitution) {
+            (true, false) => TokenKind::NoSubstitutionTemplate(part),
+            (true, true) => TokenKind::TemplateHead(part),
+            (false, true) => TokenKind::TemplateMiddle(part),
+            (false, false) => TokenKind::TemplateTail(part),
+        };
+        Token::new(kind, Span::new(start, self.position))
+    }
+    
+    /// Decode the escape sequence following a backslash into `out`
+    fn scan_escape(&mut self, out: &mut String) -> Result<(), String> {
+        let Some(ch) = self.current_char else {
+            return Err("Unterminated string literal".to_string());
+        };
+        self.advance();
+        
+        match ch {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            'r' => out.push('\r'),
+            'b' => out.push('\u{8}'),
+            'f' => out.push('\u{c}'),
+            'v' => out.push('\u{b}'),
+            '0' if !self.current_char.is_some_and(|c| c.is_ascii_digit()) => out.push('\0'),
+            'x' => {
+                let code = self.scan_hex_digits(2).ok_or("Invalid hexadecimal escape sequence")?;
+                out.push(char::from_u32(code).ok_or("Invalid hexadecimal escape sequence")?);
+            }
+            'u' => {
+                let code = if self.current_char == Some('{') {
+                    self.advance();
+                    let mut code: u32 = 0;
+                    let mut digits = 0;
+                    while let Some(d) = self.current_char.and_then(|c| c.to_digit(16)) {
+                        code = code.saturating_mul(16).saturating_add(d);
+                        digits += 1;
+                        self.advance();
+                    }
+                    if digits == 0 || self.current_char != Some('}') {
+                        return Err("Invalid Unicode escape sequence".to_string());
+                    }
+                    self.advance();
+                    code
+                } else {
+                    self.scan_hex_digits(4).ok_or("Invalid Unicode escape sequence")?
+                };
+                // Lone surrogates cannot be represented in a Rust string
+                out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            // Line continuation: the escaped line terminator is dropped
+            '\r' => {
+                if self.current_char == Some('\n') {
+                    self.advance();
+                }
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            '1'..='9' | '0' => {
+                return Err("Octal escape sequences are not allowed".to_string());
+            }
+            other => out.push(other),
+        }
+        
+        Ok(())
+    }
+    
+    /// Read exactly `count` hexadecimal digits
+    fn scan_hex_digits(&mut self, count: usize) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..count {
+            let digit = self.current_char?.to_digit(16)?;
+            code = code * 16 + digit;
+            self.advance();
+        }
+        Some(code)
     }
     
     /// Scan an identifier or keyword
@@ -126,11 +570,24 @@ impl Lexer {
         
         let kind = match ident.as_str() {
             "let" => TokenKind::Let,
+            "const" => TokenKind::Const,
+            "var" => TokenKind::Var,
             "function" => TokenKind::Function,
             "if" => TokenKind::If,
             "else" => TokenKind::Else,
             "for" => TokenKind::For,
+            "while" => TokenKind::While,
+            "do" => TokenKind::Do,
+            "break" => TokenKind::Break,
+            "continue" => TokenKind::Continue,
             "return" => TokenKind::Return,
+            "throw" => TokenKind::Throw,
+            "try" => TokenKind::Try,
+            "catch" => TokenKind::Catch,
+            "finally" => TokenKind::Finally,
+            "true" => TokenKind::True,
+            "false" => TokenKind::False,
+            "null" => TokenKind::Null,
             _ => TokenKind::Identifier(ident),
         };
         
@@ -139,16 +596,30 @@ impl Lexer {
     
     /// Get the next token
     pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
-        
+        match self.skip_whitespace() {
+            Ok(newline_before) => {
+                let mut token = self.scan_token();
+                token.newline_before = newline_before;
+                self.operand_end = token.kind.ends_operand().then_some(token.span.end);
+                token
+            }
+            Err(error) => error,
+        }
+    }
+    
+    /// Scan the token starting at the current character
+    fn scan_token(&mut self) -> Token {
         let Some(ch) = self.current_char else {
             return Token::new(TokenKind::Eof, Span::new(self.position, self.position));
         };
         
         let start = self.position;
         
-        // Numbers
-        if ch.is_ascii_digit() {
+        // Numbers, including ones that start with a decimal point. Straight
+        // after an operand `.1` cannot be valid either way, so it stays a member
+        // access, which reports the mistake more clearly.
+        let follows_operand = self.operand_end == Some(start);
+        if ch.is_ascii_digit() || (ch == '.' && !follows_operand && self.peek().is_some_and(|next| next.is_ascii_digit())) {
             return self.scan_number();
         }
         
@@ -157,40 +628,127 @@ impl Lexer {
             return self.scan_identifier();
         }
         
+        // String literals
+        if ch == '"' || ch == '\'' {
+            return self.scan_string(ch);
+        }
+        
+        // Template literals, and the rest of one after a substitution
+        if ch == '`' || (ch == '}' && self.template_stack.last() == Some(&self.brace_depth)) {
+            return self.scan_template();
+        }
+        
         // Operators and delimiters
         let kind = match ch {
             '+' => {
                 self.advance();
-                TokenKind::Plus
+                match self.current_char {
+                    Some('+') => {
+                        self.advance();
+                        TokenKind::PlusPlus
+                    }
+                    Some('=') => {
+                        self.advance();
+                        TokenKind::PlusEqual
+                    }
+                    _ => TokenKind::Plus,
+                }
             }
             '-' => {
                 self.advance();
-                TokenKind::Minus
+                match self.current_char {
+                    Some('-') => {
+                        self.advance();
+                        TokenKind::MinusMinus
+                    }
+                    Some('=') => {
+                        self.advance();
+                        TokenKind::MinusEqual
+                    }
+                    _ => TokenKind::Minus,
+                }
             }
             '*' => {
                 self.advance();
-                TokenKind::Star
+                if self.current_char == Some('=') {
+                    self.advance();
+                    TokenKind::StarEqual
+                } else {
+                    TokenKind::Star
+                }
             }
             '/' => {
                 self.advance();
-                TokenKind::Slash
+                if self.current_char == Some('=') {
+                    self.advance();
+                    TokenKind::SlashEqual
+                } else {
+                    TokenKind::Slash
+                }
             }
             '=' => {
                 self.advance();
                 if self.current_char == Some('=') {
                     self.advance();
-                    TokenKind::EqualEqual
+                    if self.current_char == Some('=') {
+                        self.advance();
+                        TokenKind::EqualEqualEqual
+                    } else {
+                        TokenKind::EqualEqual
+                    }
+                } else if self.current_char == Some('>') {
+                    self.advance();
+                    TokenKind::Arrow
                 } else {
                     TokenKind::Equal
                 }
             }
+            '!' => {
+                self.advance();
+                if self.current_char == Some('=') {
+                    self.advance();
+                    if self.current_char == Some('=') {
+                        self.advance();
+                        TokenKind::BangEqualEqual
+                    } else {
+                        TokenKind::BangEqual
+                    }
+                } else {
+                    TokenKind::Bang
+                }
+            }
+            '&' if self.source.get(self.position + 1) == Some(&'&') => {
+                self.advance();
+                self.advance();
+                TokenKind::AmpAmp
+            }
+            '|' if self.source.get(self.position + 1) == Some(&'|') => {
+                self.advance();
+                self.advance();
+                TokenKind::PipePipe
+            }
+            '?' if self.source.get(self.position + 1) == Some(&'?') => {
+                self.advance();
+                self.advance();
+                TokenKind::QuestionQuestion
+            }
             '<' => {
                 self.advance();
-                TokenKind::Less
+                if self.current_char == Some('=') {
+                    self.advance();
+                    TokenKind::LessEqual
+                } else {
+                    TokenKind::Less
+                }
             }
             '>' => {
                 self.advance();
-                TokenKind::Greater
+                if self.current_char == Some('=') {
+                    self.advance();
+                    TokenKind::GreaterEqual
+                } else {
+                    TokenKind::Greater
+                }
             }
             '(' => {
                 self.advance();
@@ -202,20 +760,44 @@ impl Lexer {
             }
             '{' => {
                 self.advance();
+                self.brace_depth += 1;
                 TokenKind::LeftBrace
             }
             '}' => {
                 self.advance();
+                self.brace_depth = self.brace_depth.saturating_sub(1);
                 TokenKind::RightBrace
             }
             ';' => {
                 self.advance();
                 TokenKind::Semicolon
             }
+            ':' => {
+                self.advance();
+                TokenKind::Colon
+            }
             ',' => {
                 self.advance();
                 TokenKind::Comma
             }
+            '.' => {
+                self.advance();
+                if self.current_char == Some('.') && self.peek() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    TokenKind::Ellipsis
+                } else {
+                    TokenKind::Dot
+                }
+            }
+            '[' => {
+                self.advance();
+                TokenKind::LeftBracket
+            }
+            ']' => {
+                self.advance();
+                TokenKind::RightBracket
+            }
             _ => {
                 self.advance();
                 // For unsupported characters, return an identifier with the char
@@ -244,11 +826,18 @@ impl Lexer {
     }
 }
 
+/// Whether `ch` is a line terminator, which ends `//` comments and allows
+/// semicolons to be inserted
+pub(crate) fn is_line_terminator(ch: char) -> bool {
+    matches!(ch, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_tokenize_numbers() {
         let mut lexer = Lexer::new("42 3.14".to_string());
         let tokens = lexer.tokenize();
@@ -258,6 +847,83 @@ mod tests {
         assert_eq!(tokens[1].kind, TokenKind::Number(3.14));
     }
     
+    #[test]
+    fn test_tokenize_numeric_literal_forms() {
+        let mut lexer = Lexer::new("1e3 2.5E-2 .5 5. 0 0.1 1_000_000 0xFF 0o17 0B101 0x1_0 1.5e+2".to_string());
+        let values: Vec<TokenKind> = lexer.tokenize().into_iter().map(|t| t.kind).collect();
+        
+        assert_eq!(values, vec![
+            TokenKind::Number(1000.0),
+            TokenKind::Number(0.025),
+            TokenKind::Number(0.5),
+            TokenKind::Number(5.0),
+            TokenKind::Number(0.0),
+            TokenKind::Number(0.1),
+            TokenKind::Number(1_000_000.0),
+            TokenKind::Number(255.0),
+            TokenKind::Number(15.0),
+            TokenKind::Number(5.0),
+            TokenKind::Number(16.0),
+            TokenKind::Number(150.0),
+            TokenKind::Eof,
+        ]);
+        
+        // A dot after a literal is still member access when no digit follows
+        let mut lexer = Lexer::new("a.b".to_string());
+        assert_eq!(lexer.tokenize()[1].kind, TokenKind::Dot);
+    }
+    
+    #[test]
+    fn test_tokenize_malformed_numbers() {
+        for source in ["1__0", "1_", "0_1", "1._5", "1e", "1e+", "0x", "0b12", "0o8", "012", "3in", "10n", "1_e5"] {
+            let mut lexer = Lexer::new(source.to_string());
+            let tokens = lexer.tokenize();
+            
+            assert!(matches!(tokens[0].kind, TokenKind::Error(_)), "{} should not lex", source);
+            // The whole literal is consumed, so lexing resumes after it
+            assert_eq!(tokens[0].span, Span::new(0, source.len()), "{}", source);
+            assert_eq!(tokens[1].kind, TokenKind::Eof);
+        }
+        
+        let mut lexer = Lexer::new("0x1G".to_string());
+        assert_eq!(lexer.tokenize()[0].kind, TokenKind::Error("Identifier starts immediately after number literal".to_string()));
+    }
+    
+    #[test]
+    fn test_tokenize_skips_comments() {
+        let mut lexer = Lexer::new("a // line comment / not division\n/* block\n*/ b /* inline */ / c /**/".to_string());
+        let tokens = lexer.tokenize();
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+        
+        assert_eq!(kinds, vec![
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::Slash,
+            TokenKind::Identifier("c".to_string()),
+            TokenKind::Eof,
+        ]);
+        assert_eq!(tokens[1].span, Span::new(45, 46));
+    }
+    
+    #[test]
+    fn test_tokenize_unterminated_block_comment() {
+        let mut lexer = Lexer::new("x /* never closed".to_string());
+        let tokens = lexer.tokenize();
+        
+        assert_eq!(tokens[1].kind, TokenKind::Error("Unterminated block comment".to_string()));
+        assert_eq!(tokens[1].span, Span::new(2, 17));
+        assert_eq!(tokens[2].kind, TokenKind::Eof);
+    }
+    
+    #[test]
+    fn test_tokenize_newline_before() {
+        let mut lexer = Lexer::new("return\nvalue; a /* \u{2028} */ b // c\r\nd /* same line */ e".to_string());
+        let flags: Vec<bool> = lexer.tokenize().iter().map(|t| t.newline_before).collect();
+        
+        // return, value, ;, a, b, d, e, EOF
+        assert_eq!(flags, vec![false, true, false, false, true, true, false, false]);
+    }
+    
     #[test]
     fn test_tokenize_identifiers() {
         let mut lexer = Lexer::new("foo bar_123".to_string());
@@ -268,6 +934,17 @@ mod tests {
         assert_eq!(tokens[1].kind, TokenKind::Identifier("bar_123".to_string()));
     }
     
+    #[test]
+    fn test_tokenize_declaration_keywords() {
+        let mut lexer = Lexer::new("let const var constant".to_string());
+        let tokens = lexer.tokenize();
+        
+        assert_eq!(tokens[0].kind, TokenKind::Let);
+        assert_eq!(tokens[1].kind, TokenKind::Const);
+        assert_eq!(tokens[2].kind, TokenKind::Var);
+        assert_eq!(tokens[3].kind, TokenKind::Identifier("constant".to_string()));
+    }
+    
     #[test]
     fn test_tokenize_keywords() {
         let mut lexer = Lexer::new("let function if else for return".to_string());
@@ -282,6 +959,42 @@ mod tests {
         assert_eq!(tokens[5].kind, TokenKind::Return);
     }
     
+    #[test]
+    fn test_tokenize_member_access() {
+        let mut lexer = Lexer::new("obj.if[key]".to_string());
+        let tokens = lexer.tokenize();
+        
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Identifier("obj".to_string()),
+            TokenKind::Dot,
+            TokenKind::If,
+            TokenKind::LeftBracket,
+            TokenKind::Identifier("key".to_string()),
+            TokenKind::RightBracket,
+            TokenKind::Eof,
+        ]);
+        assert_eq!(TokenKind::If.keyword_name(), Some("if"));
+        assert_eq!(TokenKind::Dot.keyword_name(), None);
+    }
+    
+    #[test]
+    fn test_tokenize_loop_keywords() {
+        let mut lexer = Lexer::new("while do break continue outer:".to_string());
+        let tokens = lexer.tokenize();
+        
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::While,
+            TokenKind::Do,
+            TokenKind::Break,
+            TokenKind::Continue,
+            TokenKind::Identifier("outer".to_string()),
+            TokenKind::Colon,
+            TokenKind::Eof,
+        ]);
+    }
+    
     #[test]
     fn test_tokenize_operators() {
         let mut lexer = Lexer::new("+ - * / = == < >".to_string());
@@ -296,6 +1009,166 @@ mod tests {
         assert_eq!(tokens[5].kind, TokenKind::EqualEqual);
     }
     
+    #[test]
+    fn test_tokenize_assignment_operators() {
+        let mut lexer = Lexer::new("+= -= *= /= ++ -- + -".to_string());
+        let tokens = lexer.tokenize();
+        
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::PlusEqual,
+            TokenKind::MinusEqual,
+            TokenKind::StarEqual,
+            TokenKind::SlashEqual,
+            TokenKind::PlusPlus,
+            TokenKind::MinusMinus,
+            TokenKind::Plus,
+            TokenKind::Minus,
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_comparison_operators() {
+        let mut lexer = Lexer::new("< <= > >= == != === !== true false".to_string());
+        let tokens = lexer.tokenize();
+        
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Less,
+            TokenKind::LessEqual,
+            TokenKind::Greater,
+            TokenKind::GreaterEqual,
+            TokenKind::EqualEqual,
+            TokenKind::BangEqual,
+            TokenKind::EqualEqualEqual,
+            TokenKind::BangEqualEqual,
+            TokenKind::True,
+            TokenKind::False,
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_arrow() {
+        let mut lexer = Lexer::new("(a) => a ==> =>=".to_string());
+        let kinds: Vec<TokenKind> = lexer.tokenize().into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::LeftParen,
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::RightParen,
+            TokenKind::Arrow,
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::EqualEqual,
+            TokenKind::Greater,
+            TokenKind::Arrow,
+            TokenKind::Equal,
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_ellipsis() {
+        let mut lexer = Lexer::new("f(...a, b.c) .. .5".to_string());
+        let kinds: Vec<TokenKind> = lexer.tokenize().into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Identifier("f".to_string()),
+            TokenKind::LeftParen,
+            TokenKind::Ellipsis,
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::Comma,
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::Dot,
+            TokenKind::Identifier("c".to_string()),
+            TokenKind::RightParen,
+            TokenKind::Dot,
+            TokenKind::Dot,
+            TokenKind::Number(0.5),
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_leading_decimal_point_after_tokens() {
+        let kinds = |source: &str| -> Vec<TokenKind> {
+            Lexer::new(source.to_string()).tokenize().into_iter().map(|t| t.kind).collect()
+        };
+        
+        // After a keyword `.5` is a number, after an operand a member access
+        assert_eq!(kinds("return.5"), [TokenKind::Return, TokenKind::Number(0.5), TokenKind::Eof]);
+        assert_eq!(kinds("typeof.5")[1], TokenKind::Number(0.5));
+        assert_eq!(kinds("x.5"), [
+            TokenKind::Identifier("x".to_string()),
+            TokenKind::Dot,
+            TokenKind::Number(5.0),
+            TokenKind::Eof,
+        ]);
+        assert_eq!(kinds("a[0].5")[4], TokenKind::Dot);
+        assert_eq!(kinds("(.5)")[1], TokenKind::Number(0.5));
+    }
+    
+    #[test]
+    fn test_tokenize_logical_operators() {
+        let mut lexer = Lexer::new("!a && b || c ?? null !== !!d".to_string());
+        let tokens = lexer.tokenize();
+        
+        let kinds: Vec<TokenKind> = tokens.into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Bang,
+            TokenKind::Identifier("a".to_string()),
+            TokenKind::AmpAmp,
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::PipePipe,
+            TokenKind::Identifier("c".to_string()),
+            TokenKind::QuestionQuestion,
+            TokenKind::Null,
+            TokenKind::BangEqualEqual,
+            TokenKind::Bang,
+            TokenKind::Bang,
+            TokenKind::Identifier("d".to_string()),
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_strings() {
+        let mut lexer = Lexer::new(r#"'single' "double" "" 'it\'s'"#.to_string());
+        let tokens = lexer.tokenize();
+        
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].kind, TokenKind::String("single".to_string()));
+        assert_eq!(tokens[1].kind, TokenKind::String("double".to_string()));
+        assert_eq!(tokens[2].kind, TokenKind::String(String::new()));
+        assert_eq!(tokens[3].kind, TokenKind::String("it's".to_string()));
+        assert_eq!(tokens[0].span, Span::new(0, 8));
+    }
+    
+    #[test]
+    fn test_tokenize_string_escapes() {
+        let mut lexer = Lexer::new(r#""a\nb\t\\ \x41\u0042\u{1F600}""#.to_string());
+        let tokens = lexer.tokenize();
+        
+        assert_eq!(tokens[0].kind, TokenKind::String("a\nb\t\\ AB\u{1F600}".to_string()));
+    }
+    
+    #[test]
+    fn test_tokenize_unterminated_string() {
+        let mut lexer = Lexer::new("'abc".to_string());
+        let tokens = lexer.tokenize();
+        
+        assert!(matches!(tokens[0].kind, TokenKind::Error(_)));
+        assert_eq!(tokens[1].kind, TokenKind::Eof);
+    }
+    
+    #[test]
+    fn test_tokenize_invalid_escape() {
+        let mut lexer = Lexer::new(r#""\xZZ" 1"#.to_string());
+        let tokens = lexer.tokenize();
+        
+        assert!(matches!(tokens[0].kind, TokenKind::Error(_)));
+        assert_eq!(tokens[1].kind, TokenKind::Number(1.0));
+    }
+    
     #[test]
     fn test_tokenize_expression() {
         let mut lexer = Lexer::new("let x = 10 + 20;".to_string());
@@ -311,4 +1184,39 @@ mod tests {
         assert_eq!(tokens[6].kind, TokenKind::Semicolon);
         assert_eq!(tokens[7].kind, TokenKind::Eof);
     }
+    
+    #[test]
+    fn test_tokenize_templates() {
+        let part = |cooked: &str, raw: &str| TemplatePart { cooked: Some(cooked.to_string()), raw: raw.to_string() };
+        let mut lexer = Lexer::new("`a${ {b: 1} }c${d}\\n\r\n` `plain`".to_string());
+        let kinds: Vec<TokenKind> = lexer.tokenize().into_iter().map(|t| t.kind).collect();
+        
+        assert_eq!(kinds, vec![
+            TokenKind::TemplateHead(part("a", "a")),
+            TokenKind::LeftBrace,
+            TokenKind::Identifier("b".to_string()),
+            TokenKind::Colon,
+            TokenKind::Number(1.0),
+            TokenKind::RightBrace,
+            TokenKind::TemplateMiddle(part("c", "c")),
+            TokenKind::Identifier("d".to_string()),
+            TokenKind::TemplateTail(part("\n\n", "\\n\n")),
+            TokenKind::NoSubstitutionTemplate(part("plain", "plain")),
+            TokenKind::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_template_errors() {
+        // An invalid escape leaves only the raw text
+        let tokens = Lexer::new(r"`\unicode`".to_string()).tokenize();
+        assert_eq!(tokens[0].kind, TokenKind::NoSubstitutionTemplate(TemplatePart {
+            cooked: None,
+            raw: r"\unicode".to_string(),
+        }));
+        
+        let tokens = Lexer::new("`abc ${x}".to_string()).tokenize();
+        assert_eq!(tokens[2].kind, TokenKind::Error("Unterminated template literal".to_string()));
+        assert_eq!(tokens[2].span, Span::new(8, 9));
+    }
 }
@@ -2,6 +2,7 @@
 // Core library modules
 
 pub mod types;
+pub mod heap;
 pub mod error;
 pub mod lexer;
 pub mod ast;
@@ -15,21 +16,24 @@ pub mod ir;
 pub mod turbofan;
 pub mod codegen_backend;
 pub mod deopt;
+pub mod source_map;
 pub mod engine;
 
 // Re-export commonly used types
-pub use types::{Value, Span, FunctionId};
-pub use error::{Error, ParseError, RuntimeError, CompileError};
-pub use lexer::{Lexer, Token, TokenKind};
-pub use ast::{AST, ASTNode, BinOp};
+pub use types::{Value, Span, FunctionId, Closure, Upvalue, JsObject, ObjectRef, JsArray, ArrayRef};
+pub use heap::{Heap, HeapStats};
+pub use error::{Error, ParseError, RuntimeError, CompileError, StackFrame};
+pub use lexer::{Lexer, TemplatePart, Token, TokenKind};
+pub use ast::{AST, ASTNode, AssignOp, BinOp, DeclKind, LogicalOp, Param, UnaryOp, UpdateOp};
 pub use parser::Parser;
-pub use scope::{Scope, ScopeType};
-pub use bytecode::{Instruction, BytecodeChunk};
+pub use scope::{Binding, Scope, ScopeType};
+pub use bytecode::{Instruction, BytecodeChunk, ExceptionHandler, FunctionInfo, PositionTable};
 pub use codegen::BytecodeGenerator;
 pub use interpreter::{Ignition, CallFrame};
 pub use profiler::HotspotProfiler;
-pub use ir::{IR, IRNode, NodeId, Type as IRType};
+pub use ir::{IR, IRNode, NodeId, CompareOp, ShortCircuitOp, Type as IRType};
 pub use turbofan::TurboFan;
 pub use codegen_backend::{CodeGenerator, CodegenBackend, CompiledFunction};
 pub use deopt::{DeoptInfo, DeoptManager, DeoptReason, DeoptState};
+pub use source_map::{LineCol, SourceMap};
 pub use engine::Engine;
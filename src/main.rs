@@ -1,4 +1,4 @@
-use v8_rs::Engine;
+use v8_rs::{Engine, Error, Parser, SourceMap};
 use std::env;
 use std::fs;
 use std::io::{self, Write};
@@ -51,9 +51,9 @@ fn run_repl() {
                     break;
                 }
                 
-                match engine.execute(input) {
+                match engine.execute_named("<repl>", input) {
                     Ok(result) => println!("{}", result),
-                    Err(err) => eprintln!("Error: {}", err),
+                    Err(err) => report_error(&engine, "<repl>", input, err),
                 }
             }
             Err(err) => {
@@ -76,18 +76,43 @@ fn run_file(filename: &str) {
     
     // 执行代码
     let mut engine = Engine::new();
-    match engine.execute(&source) {
+    match engine.execute_named(filename, &source) {
         Ok(_result) => {
             // 文件模式：不打印结果，只执行代码
             // 如果需要输出，应该在 JS 代码中使用 console.log()
         }
         Err(err) => {
-            eprintln!("Error: {}", err);
+            report_error(&engine, filename, &source, err);
             process::exit(1);
         }
     }
 }
 
+fn report_error(engine: &Engine, name: &str, source: &str, err: Error) {
+    // 语法错误：用恢复模式重新解析，一次报告所有问题
+    let mut errors: Vec<Error> = match err {
+        Error::ParseError(_) => {
+            let (_, errors) = Parser::new(source.to_string()).parse_with_recovery();
+            errors.into_iter().map(Error::from).collect()
+        }
+        _ => Vec::new(),
+    };
+    if errors.is_empty() {
+        errors.push(err);
+    }
+    
+    // 运行时错误的位置属于抛出它的脚本，可能不是刚输入的这一段
+    let current = SourceMap::new(name, source);
+    let map = engine.error_source().unwrap_or(&current);
+    let rendered: Vec<String> = errors.iter().map(|err| map.render_error(err)).collect();
+    eprint!("{}", rendered.join("\n"));
+    
+    // 运行时错误：附上 JavaScript 调用栈
+    for frame in engine.stack_trace() {
+        eprintln!("    {}", frame);
+    }
+}
+
 fn print_usage(program: &str) {
     eprintln!("Usage:");
     eprintln!("  {}              Start REPL (interactive mode)", program);
@@ -1,15 +1,26 @@
 // Recursive descent parser for JavaScript
 
-use crate::ast::{AST, ASTNode, BinOp};
+use crate::ast::{AST, ASTNode, AssignOp, BinOp, DeclKind, LogicalOp, Param, UnaryOp, UpdateOp};
 use crate::error::ParseError;
-use crate::lexer::{Lexer, Token, TokenKind};
-use crate::types::Span;
+use crate::lexer::{Lexer, TemplatePart, Token, TokenKind};
+use crate::types::{Span, Value};
+use std::collections::HashMap;
 
 /// Parser for converting tokens into AST
 pub struct Parser {
     tokens: Vec<Token>,
+    /// Position of the `)` closing the `(` at each position, if it is closed
+    closing_parens: HashMap<usize, usize>,
     position: usize,
     eof_token: Token,
+    /// Labels in scope, with whether each labels an iteration statement
+    label_stack: Vec<(String, bool)>,
+    /// Number of enclosing loops in the current function
+    loop_depth: usize,
+    /// Whether statement errors are collected instead of ending the parse
+    recovering: bool,
+    /// Diagnostics collected while recovering
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
@@ -17,9 +28,14 @@ impl Parser {
         let mut lexer = Lexer::new(source);
         let tokens = lexer.tokenize();
         Self {
+            closing_parens: match_parens(&tokens),
             tokens,
             position: 0,
             eof_token: Token::new(TokenKind::Eof, Span::new(0, 0)),
+            label_stack: Vec::new(),
+            loop_depth: 0,
+            recovering: false,
+            errors: Vec::new(),
         }
     }
     
@@ -58,41 +74,156 @@ impl Parser {
         }
     }
     
+    /// Consume the `;` ending a statement, or insert one automatically where
+    /// the next token is `}`, the end of input, or on a new line
+    fn consume_semicolon(&mut self) -> Result<(), ParseError> {
+        match self.current().kind {
+            TokenKind::Semicolon => {
+                self.advance();
+                Ok(())
+            }
+            TokenKind::RightBrace | TokenKind::Eof => Ok(()),
+            _ if self.current().newline_before => Ok(()),
+            _ => self.expect(TokenKind::Semicolon).map(|_| ()),
+        }
+    }
+    
     /// Parse the entire program
     pub fn parse(&mut self) -> Result<AST, ParseError> {
         let mut statements = Vec::new();
         
         while !matches!(self.current().kind, TokenKind::Eof) {
-            statements.push(self.parse_statement()?);
+            statements.push(self.parse_list_item(false)?);
         }
         
         Ok(AST::new(ASTNode::Program(statements)))
     }
     
+    /// Parse the entire program, resynchronising at statement boundaries after
+    /// each error. Returns the partial AST, with `ASTNode::Error` in place of
+    /// every statement that failed to parse, and all diagnostics in source order.
+    pub fn parse_with_recovery(&mut self) -> (AST, Vec<ParseError>) {
+        self.recovering = true;
+        let result = self.parse();
+        self.recovering = false;
+        
+        let mut errors = std::mem::take(&mut self.errors);
+        let ast = result.unwrap_or_else(|err| {
+            errors.push(err);
+            AST::new(ASTNode::Program(Vec::new()))
+        });
+        (ast, errors)
+    }
+    
+    /// Parse a statement of a program or block. When recovering, a statement
+    /// that fails to parse becomes an error node and its diagnostic is kept.
+    fn parse_list_item(&mut self, in_block: bool) -> Result<ASTNode, ParseError> {
+        let start = self.position;
+        let labels = self.label_stack.len();
+        let loop_depth = self.loop_depth;
+        
+        match self.parse_statement() {
+            Err(err) if self.recovering => {
+                self.errors.push(err);
+                self.label_stack.truncate(labels);
+                self.loop_depth = loop_depth;
+                self.synchronize(start, in_block);
+                
+                let start_span = self.tokens.get(start).map_or(self.current().span, |t| t.span);
+                let end = self.tokens.get(self.position.saturating_sub(1))
+                    .map_or(start_span.end, |t| t.span.end);
+                Ok(ASTNode::Error { span: Span::new(start_span.start, end.max(start_span.start)) })
+            }
+            result => result,
+        }
+    }
+    
+    /// Skip tokens after an error in the statement starting at token `start`,
+    /// stopping after a `;` or `}`, or before a keyword that starts a statement.
+    /// Braces opened while skipping are skipped as a whole, and inside a block
+    /// the closing `}` is left for the block.
+    fn synchronize(&mut self, start: usize, in_block: bool) {
+        let mut depth = 0;
+        // Always make progress, even when the first token was the problem
+        let mut skip_next = self.position == start;
+        
+        loop {
+            if !skip_next {
+                match self.current().kind {
+                    TokenKind::Eof => return,
+                    TokenKind::Let
+                    | TokenKind::Const
+                    | TokenKind::Var
+                    | TokenKind::Function
+                    | TokenKind::If
+                    | TokenKind::For
+                    | TokenKind::While
+                    | TokenKind::Do
+                    | TokenKind::Break
+                    | TokenKind::Continue
+                    | TokenKind::Return
+                    | TokenKind::Throw
+                    | TokenKind::Try
+                        if depth == 0 => return,
+                    TokenKind::RightBrace if depth == 0 && in_block => return,
+                    _ => {}
+                }
+            }
+            skip_next = false;
+            
+            match self.advance().kind {
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                TokenKind::RightBrace | TokenKind::Semicolon if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+    
     /// Parse a statement
     fn parse_statement(&mut self) -> Result<ASTNode, ParseError> {
         match &self.current().kind {
-            TokenKind::Let => self.parse_let_decl(),
+            TokenKind::Let | TokenKind::Const | TokenKind::Var => {
+                let decl = self.parse_var_decl()?;
+                self.consume_semicolon()?;
+                Ok(decl)
+            }
             TokenKind::Function => self.parse_function_decl(),
             TokenKind::If => self.parse_if_stmt(),
             TokenKind::For => self.parse_for_stmt(),
+            TokenKind::While => self.parse_while_stmt(),
+            TokenKind::Do => self.parse_do_while_stmt(),
+            TokenKind::Break => self.parse_break_stmt(),
+            TokenKind::Continue => self.parse_continue_stmt(),
+            TokenKind::Identifier(_) if matches!(self.peek().kind, TokenKind::Colon) => {
+                self.parse_labeled_stmt()
+            }
             TokenKind::Return => self.parse_return_stmt(),
+            TokenKind::Throw => self.parse_throw_stmt(),
+            TokenKind::Try => self.parse_try_stmt(),
             TokenKind::LeftBrace => self.parse_block_stmt(),
             _ => {
                 // Expression statement
                 let expr = self.parse_expression()?;
-                if matches!(self.current().kind, TokenKind::Semicolon) {
-                    self.advance();
-                }
+                self.consume_semicolon()?;
                 Ok(expr)
             }
         }
     }
     
-    /// Parse let declaration: let x = expr;
-    fn parse_let_decl(&mut self) -> Result<ASTNode, ParseError> {
+    /// Parse variable declaration, without its terminator: (let | const | var) x [= expr]
+    fn parse_var_decl(&mut self) -> Result<ASTNode, ParseError> {
         let start = self.current().span.start;
-        self.expect(TokenKind::Let)?;
+        let kind = match self.advance().kind {
+            TokenKind::Const => DeclKind::Const,
+            TokenKind::Var => DeclKind::Var,
+            _ => DeclKind::Let,
+        };
         
         let name = match &self.current().kind {
             TokenKind::Identifier(n) => {
@@ -109,18 +240,24 @@ impl Parser {
             }
         };
         
-        self.expect(TokenKind::Equal)?;
-        let init = Box::new(self.parse_expression()?);
-        
-        if matches!(self.current().kind, TokenKind::Semicolon) {
+        let init = if matches!(self.current().kind, TokenKind::Equal) {
             self.advance();
-        }
+            Some(Box::new(self.parse_expression()?))
+        } else if kind == DeclKind::Const {
+            return Err(ParseError::InvalidSyntax {
+                message: "Missing initializer in const declaration".to_string(),
+                span: self.current().span,
+            });
+        } else {
+            None
+        };
         
         let end = self.tokens.get(self.position.saturating_sub(1))
             .map(|t| t.span.end)
             .unwrap_or(start);
         
-        Ok(ASTNode::LetDecl {
+        Ok(ASTNode::VarDecl {
+            kind,
             name,
             init,
             span: Span::new(start, end),
@@ -147,39 +284,141 @@ impl Parser {
             }
         };
         
+        let params = self.parse_params()?;
+        let body = Box::new(self.parse_function_body()?);
+        let end = self.tokens.get(self.position.saturating_sub(1))
+            .map(|t| t.span.end)
+            .unwrap_or(start);
+        
+        Ok(ASTNode::FunctionDecl {
+            name,
+            params,
+            body,
+            span: Span::new(start, end),
+        })
+    }
+    
+    /// Parse function expression: function name?(params) { body }
+    fn parse_function_expr(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.expect(TokenKind::Function)?.span;
+        
+        let name = match &self.current().kind {
+            TokenKind::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                Some(name)
+            }
+            _ => None,
+        };
+        let params = self.parse_params()?;
+        let body = Box::new(self.parse_function_body()?);
+        let span = start_span.merge(body.span());
+        
+        Ok(ASTNode::FunctionExpr { name, params, body, span })
+    }
+    
+    /// Whether an arrow function starts here: `x =>` or a parenthesised list followed by `=>`
+    fn at_arrow_function(&self) -> bool {
+        match self.current().kind {
+            TokenKind::Identifier(_) => matches!(self.peek().kind, TokenKind::Arrow),
+            TokenKind::LeftParen => {
+                let next = self.closing_parens.get(&self.position)
+                    .and_then(|&close| self.tokens.get(close + 1));
+                matches!(next.map(|t| &t.kind), Some(TokenKind::Arrow))
+            }
+            _ => false,
+        }
+    }
+    
+    /// Parse arrow function: param => body | (params) => body
+    fn parse_arrow_function(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.current().span;
+        let params = match &self.current().kind {
+            TokenKind::Identifier(name) => {
+                let params = vec![Param::new(name.clone())];
+                self.advance();
+                params
+            }
+            _ => self.parse_params()?,
+        };
+        
+        let arrow = self.expect(TokenKind::Arrow)?;
+        if arrow.newline_before {
+            return Err(ParseError::InvalidSyntax {
+                message: "Illegal newline before =>".to_string(),
+                span: arrow.span,
+            });
+        }
+        
+        // A block body, or a single expression that is returned
+        let body = match self.current().kind {
+            TokenKind::LeftBrace => self.parse_function_body()?,
+            _ => self.parse_assignment()?,
+        };
+        let span = start_span.merge(body.span());
+        
+        Ok(ASTNode::ArrowFunction {
+            params,
+            body: Box::new(body),
+            span,
+        })
+    }
+    
+    /// Parse a parameter list: (a, b = 1, ...rest)
+    fn parse_params(&mut self) -> Result<Vec<Param>, ParseError> {
         self.expect(TokenKind::LeftParen)?;
         
         let mut params = Vec::new();
         while !matches!(self.current().kind, TokenKind::RightParen) {
-            if let TokenKind::Identifier(param) = &self.current().kind {
-                params.push(param.clone());
-                self.advance();
-                
-                if matches!(self.current().kind, TokenKind::Comma) {
-                    self.advance();
-                }
-            } else {
+            let rest_span = self.check(&TokenKind::Ellipsis).then(|| self.advance().span);
+            let TokenKind::Identifier(name) = &self.current().kind else {
                 return Err(ParseError::UnexpectedToken {
                     expected: "parameter".to_string(),
                     found: format!("{:?}", self.current().kind),
                     span: self.current().span,
                 });
+            };
+            let mut param = Param::new(name.clone());
+            self.advance();
+            
+            if let Some(rest_span) = rest_span {
+                if self.check(&TokenKind::Equal) {
+                    return Err(ParseError::InvalidSyntax {
+                        message: "Rest parameter may not have a default initializer".to_string(),
+                        span: self.current().span,
+                    });
+                }
+                if !self.check(&TokenKind::RightParen) {
+                    return Err(ParseError::InvalidSyntax {
+                        message: "Rest parameter must be last formal parameter".to_string(),
+                        span: rest_span,
+                    });
+                }
+                param.rest = true;
+            } else if self.check(&TokenKind::Equal) {
+                self.advance();
+                param.default = Some(self.parse_assignment()?);
+            }
+            params.push(param);
+            
+            if !matches!(self.current().kind, TokenKind::RightParen) {
+                self.expect(TokenKind::Comma)?;
             }
         }
         
         self.expect(TokenKind::RightParen)?;
-        
-        let body = Box::new(self.parse_block_stmt()?);
-        let end = self.tokens.get(self.position.saturating_sub(1))
-            .map(|t| t.span.end)
-            .unwrap_or(start);
-        
-        Ok(ASTNode::FunctionDecl {
-            name,
-            params,
-            body,
-            span: Span::new(start, end),
-        })
+        Ok(params)
+    }
+    
+    /// Parse a function body block
+    fn parse_function_body(&mut self) -> Result<ASTNode, ParseError> {
+        // Labels and loops do not reach into a function body
+        let labels = std::mem::take(&mut self.label_stack);
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block_stmt();
+        self.label_stack = labels;
+        self.loop_depth = loop_depth;
+        body
     }
     
     /// Parse if statement: if (cond) { then } else { else }
@@ -219,14 +458,25 @@ impl Parser {
         
         self.expect(TokenKind::LeftParen)?;
         
-        let init = Box::new(self.parse_statement()?);
+        // No semicolon is ever inserted inside the header
+        let init = if matches!(self.current().kind, TokenKind::Let | TokenKind::Const | TokenKind::Var) {
+            self.parse_var_decl()?
+        } else {
+            self.parse_expression()?
+        };
+        let init = Box::new(init);
+        self.expect(TokenKind::Semicolon)?;
         let cond = Box::new(self.parse_expression()?);
         self.expect(TokenKind::Semicolon)?;
         let update = Box::new(self.parse_expression()?);
         
         self.expect(TokenKind::RightParen)?;
         
-        let body = Box::new(self.parse_block_stmt()?);
+        self.loop_depth += 1;
+        let body = self.parse_block_stmt();
+        self.loop_depth -= 1;
+        
+        let body = Box::new(body?);
         let end = self.tokens.get(self.position.saturating_sub(1))
             .map(|t| t.span.end)
             .unwrap_or(start);
@@ -240,17 +490,194 @@ impl Parser {
         })
     }
     
-    /// Parse return statement: return expr;
-    fn parse_return_stmt(&mut self) -> Result<ASTNode, ParseError> {
+    /// Parse while loop: while (cond) body
+    fn parse_while_stmt(&mut self) -> Result<ASTNode, ParseError> {
         let start = self.current().span.start;
-        self.expect(TokenKind::Return)?;
+        self.expect(TokenKind::While)?;
         
-        let value = Box::new(self.parse_expression()?);
+        self.expect(TokenKind::LeftParen)?;
+        let cond = Box::new(self.parse_expression()?);
+        self.expect(TokenKind::RightParen)?;
+        
+        let body = Box::new(self.parse_loop_body()?);
+        let end = self.tokens.get(self.position.saturating_sub(1))
+            .map(|t| t.span.end)
+            .unwrap_or(start);
+        
+        Ok(ASTNode::WhileStmt {
+            cond,
+            body,
+            span: Span::new(start, end),
+        })
+    }
+    
+    /// Parse do-while loop: do body while (cond);
+    fn parse_do_while_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current().span.start;
+        self.expect(TokenKind::Do)?;
+        
+        let body = Box::new(self.parse_loop_body()?);
+        
+        self.expect(TokenKind::While)?;
+        self.expect(TokenKind::LeftParen)?;
+        let cond = Box::new(self.parse_expression()?);
+        self.expect(TokenKind::RightParen)?;
         
+        // A semicolon is inserted after a do-while whenever one is missing
         if matches!(self.current().kind, TokenKind::Semicolon) {
             self.advance();
         }
         
+        let end = self.tokens.get(self.position.saturating_sub(1))
+            .map(|t| t.span.end)
+            .unwrap_or(start);
+        
+        Ok(ASTNode::DoWhileStmt {
+            body,
+            cond,
+            span: Span::new(start, end),
+        })
+    }
+    
+    /// Parse the body of a loop, where unlabelled break/continue are allowed
+    fn parse_loop_body(&mut self) -> Result<ASTNode, ParseError> {
+        self.loop_depth += 1;
+        let body = self.parse_statement();
+        self.loop_depth -= 1;
+        body
+    }
+    
+    /// Parse the optional label after `break` or `continue`, which must be on the same line
+    fn parse_jump_label(&mut self) -> Option<String> {
+        if self.current().newline_before {
+            return None;
+        }
+        if let TokenKind::Identifier(name) = &self.current().kind {
+            let name = name.clone();
+            self.advance();
+            Some(name)
+        } else {
+            None
+        }
+    }
+    
+    /// Parse break statement: break label?;
+    fn parse_break_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.expect(TokenKind::Break)?.span;
+        let label = self.parse_jump_label();
+        let span = start_span.merge(self.tokens[self.position - 1].span);
+        
+        match &label {
+            Some(name) if !self.label_stack.iter().any(|(l, _)| l == name) => {
+                return Err(ParseError::InvalidSyntax {
+                    message: format!("Undefined label '{}'", name),
+                    span,
+                });
+            }
+            None if self.loop_depth == 0 => {
+                return Err(ParseError::InvalidSyntax {
+                    message: "Illegal break statement".to_string(),
+                    span,
+                });
+            }
+            _ => {}
+        }
+        
+        self.consume_semicolon()?;
+        
+        Ok(ASTNode::BreakStmt { label, span })
+    }
+    
+    /// Parse continue statement: continue label?;
+    fn parse_continue_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.expect(TokenKind::Continue)?.span;
+        let label = self.parse_jump_label();
+        let span = start_span.merge(self.tokens[self.position - 1].span);
+        
+        match &label {
+            Some(name) => match self.label_stack.iter().rev().find(|(l, _)| l == name) {
+                None => {
+                    return Err(ParseError::InvalidSyntax {
+                        message: format!("Undefined label '{}'", name),
+                        span,
+                    });
+                }
+                Some((_, false)) => {
+                    return Err(ParseError::InvalidSyntax {
+                        message: format!("Illegal continue statement: '{}' does not denote an iteration statement", name),
+                        span,
+                    });
+                }
+                Some((_, true)) => {}
+            },
+            None if self.loop_depth == 0 => {
+                return Err(ParseError::InvalidSyntax {
+                    message: "Illegal continue statement: no surrounding iteration statement".to_string(),
+                    span,
+                });
+            }
+            None => {}
+        }
+        
+        self.consume_semicolon()?;
+        
+        Ok(ASTNode::ContinueStmt { label, span })
+    }
+    
+    /// Parse labelled statement: label: statement
+    fn parse_labeled_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.current().span;
+        let label = match &self.advance().kind {
+            TokenKind::Identifier(name) => name.clone(),
+            _ => unreachable!("labelled statement starts with an identifier"),
+        };
+        self.expect(TokenKind::Colon)?;
+        
+        if self.label_stack.iter().any(|(l, _)| *l == label) {
+            return Err(ParseError::InvalidSyntax {
+                message: format!("Label '{}' has already been declared", label),
+                span: start_span,
+            });
+        }
+        
+        let labels_loop = self.at_iteration_statement();
+        self.label_stack.push((label.clone(), labels_loop));
+        let body = self.parse_statement();
+        self.label_stack.pop();
+        
+        let body = Box::new(body?);
+        let span = start_span.merge(body.span());
+        
+        Ok(ASTNode::LabeledStmt { label, body, span })
+    }
+    
+    /// Whether the upcoming statement, after any further labels, is a loop
+    fn at_iteration_statement(&self) -> bool {
+        let mut pos = self.position;
+        loop {
+            let kind = self.tokens.get(pos).map(|t| &t.kind);
+            let next = self.tokens.get(pos + 1).map(|t| &t.kind);
+            match (kind, next) {
+                (Some(TokenKind::Identifier(_)), Some(TokenKind::Colon)) => pos += 2,
+                (Some(TokenKind::For | TokenKind::While | TokenKind::Do), _) => return true,
+                _ => return false,
+            }
+        }
+    }
+    
+    /// Parse return statement: return expr?;
+    fn parse_return_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current().span.start;
+        self.expect(TokenKind::Return)?;
+        
+        // The value must start on the same line as `return`
+        let value = match self.current().kind {
+            TokenKind::Semicolon | TokenKind::RightBrace | TokenKind::Eof => None,
+            _ if self.current().newline_before => None,
+            _ => Some(Box::new(self.parse_expression()?)),
+        };
+        self.consume_semicolon()?;
+        
         let end = self.tokens.get(self.position.saturating_sub(1))
             .map(|t| t.span.end)
             .unwrap_or(start);
@@ -261,6 +688,79 @@ impl Parser {
         })
     }
     
+    /// Parse throw statement: throw expr;
+    fn parse_throw_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.expect(TokenKind::Throw)?.span;
+        
+        // Unlike `return`, a line break here is an error rather than an inserted `;`
+        if self.current().newline_before {
+            return Err(ParseError::InvalidSyntax {
+                message: "Illegal newline after throw".to_string(),
+                span: start_span,
+            });
+        }
+        let value = Box::new(self.parse_expression()?);
+        self.consume_semicolon()?;
+        
+        let span = start_span.merge(self.tokens[self.position - 1].span);
+        Ok(ASTNode::ThrowStmt { value, span })
+    }
+    
+    /// Parse try statement: try block (catch ((param))? block)? (finally block)?
+    ///
+    /// At least one of the `catch` and `finally` clauses must be present.
+    fn parse_try_stmt(&mut self) -> Result<ASTNode, ParseError> {
+        let start_span = self.expect(TokenKind::Try)?.span;
+        let block = Box::new(self.parse_block_stmt()?);
+        
+        let mut param = None;
+        let mut handler = None;
+        if matches!(self.current().kind, TokenKind::Catch) {
+            self.advance();
+            if matches!(self.current().kind, TokenKind::LeftParen) {
+                self.advance();
+                match &self.current().kind {
+                    TokenKind::Identifier(name) => {
+                        param = Some(name.clone());
+                        self.advance();
+                    }
+                    _ => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "identifier".to_string(),
+                            found: format!("{:?}", self.current().kind),
+                            span: self.current().span,
+                        });
+                    }
+                }
+                self.expect(TokenKind::RightParen)?;
+            }
+            handler = Some(Box::new(self.parse_block_stmt()?));
+        }
+        
+        let finalizer = if matches!(self.current().kind, TokenKind::Finally) {
+            self.advance();
+            Some(Box::new(self.parse_block_stmt()?))
+        } else {
+            None
+        };
+        
+        if handler.is_none() && finalizer.is_none() {
+            return Err(ParseError::InvalidSyntax {
+                message: "Missing catch or finally after try".to_string(),
+                span: self.current().span,
+            });
+        }
+        
+        let span = start_span.merge(self.tokens[self.position - 1].span);
+        Ok(ASTNode::TryStmt {
+            block,
+            param,
+            handler,
+            finalizer,
+            span,
+        })
+    }
+    
     /// Parse block statement: { statements }
     fn parse_block_stmt(&mut self) -> Result<ASTNode, ParseError> {
         let start = self.current().span.start;
@@ -268,7 +768,7 @@ impl Parser {
         
         let mut statements = Vec::new();
         while !matches!(self.current().kind, TokenKind::RightBrace | TokenKind::Eof) {
-            statements.push(self.parse_statement()?);
+            statements.push(self.parse_list_item(true)?);
         }
         
         self.expect(TokenKind::RightBrace)?;
@@ -284,74 +784,365 @@ impl Parser {
     
     /// Parse expression
     fn parse_expression(&mut self) -> Result<ASTNode, ParseError> {
-        self.parse_additive()
+        self.parse_assignment()
     }
     
-    /// Parse additive expression: term ((+|-) term)*
-    fn parse_additive(&mut self) -> Result<ASTNode, ParseError> {
-        let mut left = self.parse_multiplicative()?;
-        
-        while matches!(self.current().kind, TokenKind::Plus | TokenKind::Minus) {
-            let op = match self.current().kind {
-                TokenKind::Plus => BinOp::Add,
-                TokenKind::Minus => BinOp::Sub,
-                _ => unreachable!(),
-            };
-            self.advance();
-            
-            let right = self.parse_multiplicative()?;
-            let span = left.span().merge(right.span());
-            
-            left = ASTNode::BinaryExpr {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-                span,
-            };
+    /// Parse assignment expression: target (=|+=|-=|*=|/=) assignment
+    ///
+    /// Assignment is right-associative, so `a = b = 1` assigns `b` first.
+    fn parse_assignment(&mut self) -> Result<ASTNode, ParseError> {
+        if self.at_arrow_function() {
+            return self.parse_arrow_function();
         }
+        let target = self.parse_logical_or()?;
         
-        Ok(left)
+        let op = match self.current().kind {
+            TokenKind::Equal => AssignOp::Assign,
+            TokenKind::PlusEqual => AssignOp::AddAssign,
+            TokenKind::MinusEqual => AssignOp::SubAssign,
+            TokenKind::StarEqual => AssignOp::MulAssign,
+            TokenKind::SlashEqual => AssignOp::DivAssign,
+            _ => return Ok(target),
+        };
+        
+        self.check_assignment_target(&target)?;
+        self.advance();
+        
+        let value = self.parse_assignment()?;
+        let span = target.span().merge(value.span());
+        
+        Ok(ASTNode::AssignExpr {
+            op,
+            target: Box::new(target),
+            value: Box::new(value),
+            span,
+        })
     }
     
-    /// Parse multiplicative expression: primary ((*|/) primary)*
-    fn parse_multiplicative(&mut self) -> Result<ASTNode, ParseError> {
-        let mut left = self.parse_call()?;
+    /// Parse logical OR / nullish coalescing: and (|| and)* | equality (?? equality)*
+    ///
+    /// `??` cannot be mixed with `&&` or `||` without parentheses.
+    fn parse_logical_or(&mut self) -> Result<ASTNode, ParseError> {
+        let head = self.parse_equality()?;
+        if matches!(self.current().kind, TokenKind::QuestionQuestion) {
+            return self.parse_nullish(head);
+        }
         
-        while matches!(self.current().kind, TokenKind::Star | TokenKind::Slash) {
-            let op = match self.current().kind {
-                TokenKind::Star => BinOp::Mul,
-                TokenKind::Slash => BinOp::Div,
-                _ => unreachable!(),
-            };
+        let mut left = self.parse_logical_and(head)?;
+        while matches!(self.current().kind, TokenKind::PipePipe) {
             self.advance();
-            
-            let right = self.parse_call()?;
-            let span = left.span().merge(right.span());
-            
-            left = ASTNode::BinaryExpr {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-                span,
-            };
+            let head = self.parse_equality()?;
+            let right = self.parse_logical_and(head)?;
+            left = Self::logical(LogicalOp::Or, left, right);
         }
         
+        self.reject_mixed_nullish()?;
         Ok(left)
     }
     
-    /// Parse call expression: primary(args)
-    fn parse_call(&mut self) -> Result<ASTNode, ParseError> {
+    /// Parse the rest of a logical AND chain starting at `left`
+    fn parse_logical_and(&mut self, mut left: ASTNode) -> Result<ASTNode, ParseError> {
+        while matches!(self.current().kind, TokenKind::AmpAmp) {
+            self.advance();
+            let right = self.parse_equality()?;
+            left = Self::logical(LogicalOp::And, left, right);
+        }
+        Ok(left)
+    }
+    
+    /// Parse the rest of a nullish coalescing chain starting at `left`
+    fn parse_nullish(&mut self, mut left: ASTNode) -> Result<ASTNode, ParseError> {
+        while matches!(self.current().kind, TokenKind::QuestionQuestion) {
+            self.advance();
+            let right = self.parse_equality()?;
+            left = Self::logical(LogicalOp::Nullish, left, right);
+        }
+        
+        self.reject_mixed_nullish()?;
+        Ok(left)
+    }
+    
+    /// Error if a `??` chain runs into `&&`/`||`, or vice versa
+    fn reject_mixed_nullish(&self) -> Result<(), ParseError> {
+        match self.current().kind {
+            TokenKind::AmpAmp | TokenKind::PipePipe | TokenKind::QuestionQuestion => {
+                Err(ParseError::InvalidSyntax {
+                    message: "Cannot mix '??' with '&&' or '||' without parentheses".to_string(),
+                    span: self.current().span,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Build a logical expression node spanning both operands
+    fn logical(op: LogicalOp, left: ASTNode, right: ASTNode) -> ASTNode {
+        let span = left.span().merge(right.span());
+        ASTNode::LogicalExpr {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            span,
+        }
+    }
+    
+    /// Ensure an expression can appear on the left of an assignment or update
+    fn check_assignment_target(&self, target: &ASTNode) -> Result<(), ParseError> {
+        match target {
+            ASTNode::Identifier { .. } | ASTNode::MemberExpr { .. } => Ok(()),
+            _ => Err(ParseError::InvalidSyntax {
+                message: "Invalid assignment target".to_string(),
+                span: target.span(),
+            }),
+        }
+    }
+    
+    /// Parse equality expression: relational ((==|!=|===|!==) relational)*
+    fn parse_equality(&mut self) -> Result<ASTNode, ParseError> {
+        let mut left = self.parse_relational()?;
+        
+        loop {
+            let op = match self.current().kind {
+                TokenKind::EqualEqual => BinOp::Equal,
+                TokenKind::BangEqual => BinOp::NotEqual,
+                TokenKind::EqualEqualEqual => BinOp::StrictEqual,
+                TokenKind::BangEqualEqual => BinOp::StrictNotEqual,
+                _ => break,
+            };
+            self.advance();
+            
+            let right = self.parse_relational()?;
+            let span = left.span().merge(right.span());
+            
+            left = ASTNode::BinaryExpr {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+                span,
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    /// Parse relational expression: additive ((<|>|<=|>=) additive)*
+    fn parse_relational(&mut self) -> Result<ASTNode, ParseError> {
+        let mut left = self.parse_additive()?;
+        
+        loop {
+            let op = match self.current().kind {
+                TokenKind::Less => BinOp::Less,
+                TokenKind::LessEqual => BinOp::LessEqual,
+                TokenKind::Greater => BinOp::Greater,
+                TokenKind::GreaterEqual => BinOp::GreaterEqual,
+                _ => break,
+            };
+            self.advance();
+            
+            let right = self.parse_additive()?;
+            let span = left.span().merge(right.span());
+            
+            left = ASTNode::BinaryExpr {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+                span,
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    /// Parse additive expression: term ((+|-) term)*
+    fn parse_additive(&mut self) -> Result<ASTNode, ParseError> {
+        let mut left = self.parse_multiplicative()?;
+        
+        while matches!(self.current().kind, TokenKind::Plus | TokenKind::Minus) {
+            let op = match self.current().kind {
+                TokenKind::Plus => BinOp::Add,
+                TokenKind::Minus => BinOp::Sub,
+                _ => unreachable!(),
+            };
+            self.advance();
+            
+            let right = self.parse_multiplicative()?;
+            let span = left.span().merge(right.span());
+            
+            left = ASTNode::BinaryExpr {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+                span,
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    /// Parse multiplicative expression: unary ((*|/) unary)*
+    fn parse_multiplicative(&mut self) -> Result<ASTNode, ParseError> {
+        let mut left = self.parse_unary()?;
+        
+        while matches!(self.current().kind, TokenKind::Star | TokenKind::Slash) {
+            let op = match self.current().kind {
+                TokenKind::Star => BinOp::Mul,
+                TokenKind::Slash => BinOp::Div,
+                _ => unreachable!(),
+            };
+            self.advance();
+            
+            let right = self.parse_unary()?;
+            let span = left.span().merge(right.span());
+            
+            left = ASTNode::BinaryExpr {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+                span,
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    /// Parse unary expression: (- | + | !) unary | (++|--) unary | postfix
+    fn parse_unary(&mut self) -> Result<ASTNode, ParseError> {
+        let op = match self.current().kind {
+            TokenKind::Minus => Some(UnaryOp::Neg),
+            TokenKind::Plus => Some(UnaryOp::Plus),
+            TokenKind::Bang => Some(UnaryOp::Not),
+            _ => None,
+        };
+        if let Some(op) = op {
+            let start = self.advance().span;
+            let operand = self.parse_unary()?;
+            let span = start.merge(operand.span());
+            
+            return Ok(ASTNode::UnaryExpr {
+                op,
+                operand: Box::new(operand),
+                span,
+            });
+        }
+        
+        let op = match self.current().kind {
+            TokenKind::PlusPlus => UpdateOp::Increment,
+            TokenKind::MinusMinus => UpdateOp::Decrement,
+            _ => return self.parse_postfix(),
+        };
+        let start = self.advance().span;
+        
+        let target = self.parse_unary()?;
+        self.check_assignment_target(&target)?;
+        let span = start.merge(target.span());
+        
+        Ok(ASTNode::UpdateExpr {
+            op,
+            prefix: true,
+            target: Box::new(target),
+            span,
+        })
+    }
+    
+    /// Parse postfix expression: call (++|--)?
+    fn parse_postfix(&mut self) -> Result<ASTNode, ParseError> {
+        let expr = self.parse_call()?;
+        
+        // `++`/`--` on the next line starts a new statement instead
+        let op = match self.current().kind {
+            _ if self.current().newline_before => return Ok(expr),
+            TokenKind::PlusPlus => UpdateOp::Increment,
+            TokenKind::MinusMinus => UpdateOp::Decrement,
+            _ => return Ok(expr),
+        };
+        self.check_assignment_target(&expr)?;
+        let end = self.advance().span;
+        let span = expr.span().merge(end);
+        
+        Ok(ASTNode::UpdateExpr {
+            op,
+            prefix: false,
+            target: Box::new(expr),
+            span,
+        })
+    }
+    
+    /// Parse call and member expressions: primary ((args) | .name | [expr])*
+    fn parse_call(&mut self) -> Result<ASTNode, ParseError> {
         let mut expr = self.parse_primary()?;
         
-        while matches!(self.current().kind, TokenKind::LeftParen) {
+        loop {
+            match self.current().kind {
+                TokenKind::LeftParen => {}
+                TokenKind::Dot => {
+                    self.advance();
+                    let name_token = self.advance();
+                    let name = match &name_token.kind {
+                        TokenKind::Identifier(name) => name.clone(),
+                        kind => match kind.keyword_name() {
+                            Some(name) => name.to_string(),
+                            None => {
+                                return Err(ParseError::UnexpectedToken {
+                                    expected: "property name".to_string(),
+                                    found: format!("{:?}", kind),
+                                    span: name_token.span,
+                                });
+                            }
+                        },
+                    };
+                    
+                    let span = expr.span().merge(name_token.span);
+                    expr = ASTNode::MemberExpr {
+                        object: Box::new(expr),
+                        property: Box::new(ASTNode::StringLiteral { value: name, span: name_token.span }),
+                        computed: false,
+                        span,
+                    };
+                    continue;
+                }
+                TokenKind::NoSubstitutionTemplate(_) | TokenKind::TemplateHead(_) => {
+                    let (quasis, expressions, template_span) = self.parse_template(true)?;
+                    let span = expr.span().merge(template_span);
+                    expr = ASTNode::TaggedTemplate {
+                        tag: Box::new(expr),
+                        quasis,
+                        expressions,
+                        span,
+                    };
+                    continue;
+                }
+                TokenKind::LeftBracket => {
+                    self.advance();
+                    let property = self.parse_expression()?;
+                    let end_span = self.expect(TokenKind::RightBracket)?.span;
+                    
+                    let span = expr.span().merge(end_span);
+                    expr = ASTNode::MemberExpr {
+                        object: Box::new(expr),
+                        property: Box::new(property),
+                        computed: true,
+                        span,
+                    };
+                    continue;
+                }
+                _ => break,
+            }
+            
             self.advance();
             
             let mut args = Vec::new();
             while !matches!(self.current().kind, TokenKind::RightParen) {
-                args.push(self.parse_expression()?);
+                if self.check(&TokenKind::Ellipsis) {
+                    let start_span = self.advance().span;
+                    let argument = self.parse_expression()?;
+                    let span = start_span.merge(argument.span());
+                    args.push(ASTNode::Spread { argument: Box::new(argument), span });
+                } else {
+                    args.push(self.parse_expression()?);
+                }
                 
-                if matches!(self.current().kind, TokenKind::Comma) {
-                    self.advance();
+                if !matches!(self.current().kind, TokenKind::RightParen) {
+                    self.expect(TokenKind::Comma)?;
                 }
             }
             
@@ -369,21 +1160,168 @@ impl Parser {
         Ok(expr)
     }
     
-    /// Parse primary expression: number | identifier | (expr)
+    /// Parse a template: its text pieces and the substitutions between them.
+    /// Pieces with invalid escapes are only allowed when `tagged`.
+    fn parse_template(&mut self, tagged: bool) -> Result<(Vec<TemplatePart>, Vec<ASTNode>, Span), ParseError> {
+        let mut token = self.advance();
+        let start_span = token.span;
+        let mut quasis = Vec::new();
+        let mut expressions = Vec::new();
+        
+        loop {
+            let (part, done) = match token.kind {
+                TokenKind::NoSubstitutionTemplate(part) | TokenKind::TemplateTail(part) => (part, true),
+                TokenKind::TemplateHead(part) | TokenKind::TemplateMiddle(part) => (part, false),
+                _ => unreachable!("only template tokens are consumed here"),
+            };
+            if part.cooked.is_none() && !tagged {
+                return Err(ParseError::InvalidSyntax {
+                    message: "Invalid escape sequence in template literal".to_string(),
+                    span: token.span,
+                });
+            }
+            quasis.push(part);
+            if done {
+                return Ok((quasis, expressions, start_span.merge(token.span)));
+            }
+            
+            expressions.push(self.parse_expression()?);
+            match &self.current().kind {
+                TokenKind::TemplateMiddle(_) | TokenKind::TemplateTail(_) => token = self.advance(),
+                TokenKind::Error(message) => {
+                    return Err(ParseError::InvalidSyntax {
+                        message: message.clone(),
+                        span: self.current().span,
+                    });
+                }
+                kind => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "}".to_string(),
+                        found: format!("{:?}", kind),
+                        span: self.current().span,
+                    });
+                }
+            }
+        }
+    }
+    
+    /// Parse object literal: { (key: expr | identifier),* }
+    fn parse_object_literal(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.expect(TokenKind::LeftBrace)?.span;
+        let mut properties = Vec::new();
+        
+        while !matches!(self.current().kind, TokenKind::RightBrace) {
+            let key_token = self.advance();
+            let key = match &key_token.kind {
+                TokenKind::Identifier(name) | TokenKind::String(name) => name.clone(),
+                TokenKind::Number(n) => Value::Number(*n).to_string(),
+                kind => match kind.keyword_name() {
+                    Some(name) => name.to_string(),
+                    None => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "property name".to_string(),
+                            found: format!("{:?}", kind),
+                            span: key_token.span,
+                        });
+                    }
+                },
+            };
+            
+            let value = match (&self.current().kind, &key_token.kind) {
+                (TokenKind::Colon, _) => {
+                    self.advance();
+                    self.parse_expression()?
+                }
+                // Shorthand `{ name }` reads the variable of the same name
+                (_, TokenKind::Identifier(name)) => ASTNode::Identifier {
+                    name: name.clone(),
+                    span: key_token.span,
+                },
+                _ => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: format!("{:?}", TokenKind::Colon),
+                        found: format!("{:?}", self.current().kind),
+                        span: self.current().span,
+                    });
+                }
+            };
+            properties.push((key, value));
+            
+            if !matches!(self.current().kind, TokenKind::RightBrace) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        
+        let end = self.expect(TokenKind::RightBrace)?.span;
+        Ok(ASTNode::ObjectLiteral {
+            properties,
+            span: start.merge(end),
+        })
+    }
+    
+    /// Parse array literal: [ expr,* ]
+    fn parse_array_literal(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.expect(TokenKind::LeftBracket)?.span;
+        let mut elements = Vec::new();
+        
+        while !matches!(self.current().kind, TokenKind::RightBracket) {
+            elements.push(self.parse_expression()?);
+            
+            if !matches!(self.current().kind, TokenKind::RightBracket) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        
+        let end = self.expect(TokenKind::RightBracket)?.span;
+        Ok(ASTNode::ArrayLiteral {
+            elements,
+            span: start.merge(end),
+        })
+    }
+    
+    /// Parse primary expression: number | string | boolean | identifier | (expr) | object | array
     fn parse_primary(&mut self) -> Result<ASTNode, ParseError> {
         match &self.current().kind {
+            TokenKind::LeftBrace => self.parse_object_literal(),
+            TokenKind::LeftBracket => self.parse_array_literal(),
+            TokenKind::Function => self.parse_function_expr(),
             TokenKind::Number(n) => {
                 let value = *n;
                 let span = self.current().span;
                 self.advance();
                 Ok(ASTNode::NumberLiteral { value, span })
             }
+            TokenKind::String(s) => {
+                let value = s.clone();
+                let span = self.current().span;
+                self.advance();
+                Ok(ASTNode::StringLiteral { value, span })
+            }
+            TokenKind::True | TokenKind::False => {
+                let value = matches!(self.current().kind, TokenKind::True);
+                let span = self.current().span;
+                self.advance();
+                Ok(ASTNode::BooleanLiteral { value, span })
+            }
+            TokenKind::Null => {
+                let span = self.current().span;
+                self.advance();
+                Ok(ASTNode::NullLiteral { span })
+            }
             TokenKind::Identifier(name) => {
                 let name = name.clone();
                 let span = self.current().span;
                 self.advance();
                 Ok(ASTNode::Identifier { name, span })
             }
+            TokenKind::NoSubstitutionTemplate(_) | TokenKind::TemplateHead(_) => {
+                let (quasis, expressions, span) = self.parse_template(false)?;
+                Ok(ASTNode::TemplateLiteral { quasis, expressions, span })
+            }
+            TokenKind::Error(message) => Err(ParseError::InvalidSyntax {
+                message: message.clone(),
+                span: self.current().span,
+            }),
             TokenKind::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
@@ -399,6 +1337,25 @@ impl Parser {
     }
 }
 
+/// Pair every `(` with the `)` that closes it, by token position, so finding
+/// the end of a parenthesised list does not rescan the tokens after it
+fn match_parens(tokens: &[Token]) -> HashMap<usize, usize> {
+    let mut closing = HashMap::new();
+    let mut open = Vec::new();
+    for (pos, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => open.push(pos),
+            TokenKind::RightParen => {
+                if let Some(start) = open.pop() {
+                    closing.insert(start, pos);
+                }
+            }
+            _ => {}
+        }
+    }
+    closing
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -416,6 +1373,31 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_parse_string() {
+        let mut parser = Parser::new("'hello'".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            assert_eq!(stmts.len(), 1);
+            if let ASTNode::StringLiteral { value, .. } = &stmts[0] {
+                assert_eq!(value, "hello");
+            } else {
+                panic!("Expected StringLiteral node");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_error_unterminated_string() {
+        let mut parser = Parser::new("let s = 'oops;".to_string());
+        let result = parser.parse();
+        
+        assert!(matches!(result, Err(ParseError::InvalidSyntax { .. })));
+    }
+    
     #[test]
     fn test_parse_binary_expr() {
         let mut parser = Parser::new("1 + 2".to_string());
@@ -429,6 +1411,249 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_parse_comparison_precedence() {
+        // Relational binds tighter than equality, additive tighter than both
+        let mut parser = Parser::new("a + 1 < b == true".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            if let ASTNode::BinaryExpr { op: BinOp::Equal, left, right, .. } = &stmts[0] {
+                assert!(matches!(**left, ASTNode::BinaryExpr { op: BinOp::Less, .. }));
+                assert!(matches!(**right, ASTNode::BooleanLiteral { value: true, .. }));
+            } else {
+                panic!("Expected equality at the root");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_unary_operators() {
+        let mut parser = Parser::new("-a * !b".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            if let ASTNode::BinaryExpr { op: BinOp::Mul, left, right, .. } = &stmts[0] {
+                assert!(matches!(**left, ASTNode::UnaryExpr { op: UnaryOp::Neg, .. }));
+                assert!(matches!(**right, ASTNode::UnaryExpr { op: UnaryOp::Not, .. }));
+            } else {
+                panic!("Expected unary operands to bind tighter than *");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_logical_precedence() {
+        // && binds tighter than ||, and both looser than equality
+        let mut parser = Parser::new("a || b && c == d".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            if let ASTNode::LogicalExpr { op: LogicalOp::Or, right, .. } = &stmts[0] {
+                if let ASTNode::LogicalExpr { op: LogicalOp::And, right, .. } = &**right {
+                    assert!(matches!(**right, ASTNode::BinaryExpr { op: BinOp::Equal, .. }));
+                } else {
+                    panic!("Expected && on the right of ||");
+                }
+            } else {
+                panic!("Expected || at the root");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_nullish_mixing() {
+        assert!(Parser::new("a ?? b ?? null".to_string()).parse().is_ok());
+        assert!(Parser::new("(a || b) ?? c".to_string()).parse().is_ok());
+        assert!(Parser::new("a ?? (b && c)".to_string()).parse().is_ok());
+        assert!(Parser::new("a || b ?? c".to_string()).parse().is_err());
+        assert!(Parser::new("a ?? b && c".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_all_comparison_operators() {
+        let cases = [
+            ("a < b", BinOp::Less),
+            ("a <= b", BinOp::LessEqual),
+            ("a > b", BinOp::Greater),
+            ("a >= b", BinOp::GreaterEqual),
+            ("a == b", BinOp::Equal),
+            ("a != b", BinOp::NotEqual),
+            ("a === b", BinOp::StrictEqual),
+            ("a !== b", BinOp::StrictNotEqual),
+        ];
+        
+        for (source, expected) in cases {
+            let ast = Parser::new(source.to_string()).parse().unwrap();
+            if let ASTNode::Program(stmts) = ast.root {
+                assert!(matches!(&stmts[0], ASTNode::BinaryExpr { op, .. } if *op == expected), "{}", source);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_parse_assignment_is_right_associative() {
+        let mut parser = Parser::new("a = b += 1".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            if let ASTNode::AssignExpr { op: AssignOp::Assign, target, value, .. } = &stmts[0] {
+                assert!(matches!(**target, ASTNode::Identifier { ref name, .. } if name == "a"));
+                assert!(matches!(**value, ASTNode::AssignExpr { op: AssignOp::AddAssign, .. }));
+            } else {
+                panic!("Expected AssignExpr node");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_update_expressions() {
+        let mut parser = Parser::new("++i; i--".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            assert!(matches!(stmts[0], ASTNode::UpdateExpr { op: UpdateOp::Increment, prefix: true, .. }));
+            assert!(matches!(stmts[1], ASTNode::UpdateExpr { op: UpdateOp::Decrement, prefix: false, .. }));
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_member_access() {
+        let mut parser = Parser::new("a.b[c].if = 1".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            let ASTNode::AssignExpr { target, .. } = &stmts[0] else {
+                panic!("Expected AssignExpr node");
+            };
+            let ASTNode::MemberExpr { object, property, computed: false, .. } = &**target else {
+                panic!("Expected MemberExpr node");
+            };
+            assert!(matches!(&**property, ASTNode::StringLiteral { value, .. } if value == "if"));
+            assert!(matches!(&**object, ASTNode::MemberExpr { computed: true, .. }));
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_object_literal() {
+        let mut parser = Parser::new("({ a: 1, 'b c': 2, 3: x, y, })".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            let ASTNode::ObjectLiteral { properties, .. } = &stmts[0] else {
+                panic!("Expected ObjectLiteral node");
+            };
+            let keys: Vec<_> = properties.iter().map(|(key, _)| key.as_str()).collect();
+            assert_eq!(keys, ["a", "b c", "3", "y"]);
+            assert!(matches!(&properties[3].1, ASTNode::Identifier { name, .. } if name == "y"));
+        } else {
+            panic!("Expected Program node");
+        }
+        
+        assert!(Parser::new("({ a: 1 b: 2 })".to_string()).parse().is_err());
+        assert!(Parser::new("a.1".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_array_literal() {
+        let mut parser = Parser::new("[1, 'two', [3],]; []; a[0][1] = 2".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            let ASTNode::ArrayLiteral { elements, .. } = &stmts[0] else {
+                panic!("Expected ArrayLiteral node");
+            };
+            assert_eq!(elements.len(), 3);
+            assert!(matches!(&elements[2], ASTNode::ArrayLiteral { elements, .. } if elements.len() == 1));
+            assert!(matches!(&stmts[1], ASTNode::ArrayLiteral { elements, .. } if elements.is_empty()));
+            assert!(matches!(&stmts[2], ASTNode::AssignExpr { target, .. } if matches!(**target, ASTNode::MemberExpr { computed: true, .. })));
+        } else {
+            panic!("Expected Program node");
+        }
+        
+        assert!(Parser::new("[1 2]".to_string()).parse().is_err());
+        assert!(Parser::new("[1, 2".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_invalid_assignment_target() {
+        assert!(Parser::new("1 = 2".to_string()).parse().is_err());
+        assert!(Parser::new("a + b = 2".to_string()).parse().is_err());
+        assert!(Parser::new("++3".to_string()).parse().is_err());
+        assert!(Parser::new("f()++".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_while_and_do_while() {
+        let mut parser = Parser::new("while (x < 3) x++; do { x--; } while (x > 0);".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            assert_eq!(stmts.len(), 2);
+            assert!(matches!(stmts[0], ASTNode::WhileStmt { .. }));
+            assert!(matches!(stmts[1], ASTNode::DoWhileStmt { .. }));
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_labeled_break_continue() {
+        let source = "outer: for (let i = 0; i < 3; i++) { while (true) { continue outer; } break; }";
+        let mut parser = Parser::new(source.to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            if let ASTNode::LabeledStmt { label, body, .. } = &stmts[0] {
+                assert_eq!(label, "outer");
+                assert!(body.is_loop());
+            } else {
+                panic!("Expected LabeledStmt node");
+            }
+        } else {
+            panic!("Expected Program node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_jump_label_on_next_line() {
+        // A label on the next line is not the jump's label but a new statement
+        let source = "outer: while (true) { break\nouter; }";
+        let ast = Parser::new(source.to_string()).parse().unwrap();
+        
+        let ASTNode::Program(stmts) = ast.root else { panic!("Expected Program node") };
+        let ASTNode::LabeledStmt { body, .. } = &stmts[0] else { panic!("Expected LabeledStmt node") };
+        let ASTNode::WhileStmt { body, .. } = &**body else { panic!("Expected WhileStmt node") };
+        let ASTNode::BlockStmt { statements, .. } = &**body else { panic!("Expected BlockStmt node") };
+        assert!(matches!(&statements[0], ASTNode::BreakStmt { label: None, .. }));
+        assert!(matches!(&statements[1], ASTNode::Identifier { name, .. } if name == "outer"));
+    }
+    
+    #[test]
+    fn test_parse_illegal_break_continue() {
+        assert!(Parser::new("break;".to_string()).parse().is_err());
+        assert!(Parser::new("continue;".to_string()).parse().is_err());
+        assert!(Parser::new("while (true) { break nowhere; }".to_string()).parse().is_err());
+        // A labelled block may be broken out of, but not continued
+        assert!(Parser::new("block: { break block; }".to_string()).parse().is_ok());
+        assert!(Parser::new("block: { while (true) { continue block; } }".to_string()).parse().is_err());
+        // Loops outside a function body are not break targets inside it
+        assert!(Parser::new("while (true) { function f() { break; } }".to_string()).parse().is_err());
+        assert!(Parser::new("a: a: while (true) {}".to_string()).parse().is_err());
+    }
+    
     #[test]
     fn test_parse_let_decl() {
         let mut parser = Parser::new("let x = 10;".to_string());
@@ -436,16 +1661,35 @@ mod tests {
         
         if let ASTNode::Program(stmts) = ast.root {
             assert_eq!(stmts.len(), 1);
-            if let ASTNode::LetDecl { name, .. } = &stmts[0] {
+            if let ASTNode::VarDecl { kind: DeclKind::Let, name, .. } = &stmts[0] {
                 assert_eq!(name, "x");
             } else {
-                panic!("Expected LetDecl node");
+                panic!("Expected VarDecl node");
             }
         } else {
             panic!("Expected Program node");
         }
     }
     
+    #[test]
+    fn test_parse_const_and_var_decl() {
+        let mut parser = Parser::new("const c = 1; var v; let l;".to_string());
+        let ast = parser.parse().unwrap();
+        
+        if let ASTNode::Program(stmts) = ast.root {
+            assert!(matches!(&stmts[0], ASTNode::VarDecl { kind: DeclKind::Const, init: Some(_), .. }));
+            assert!(matches!(&stmts[1], ASTNode::VarDecl { kind: DeclKind::Var, init: None, .. }));
+            assert!(matches!(&stmts[2], ASTNode::VarDecl { kind: DeclKind::Let, init: None, .. }));
+        } else {
+            panic!("Expected Program node");
+        }
+        
+        assert!(matches!(
+            Parser::new("const c;".to_string()).parse(),
+            Err(ParseError::InvalidSyntax { message, .. }) if message.contains("initializer")
+        ));
+    }
+    
     #[test]
     fn test_parse_function_decl() {
         let mut parser = Parser::new("function add(a, b) { return a + b; }".to_string());
@@ -501,4 +1745,295 @@ mod tests {
         
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_parse_with_recovery() {
+        let source = "let a = ;\nlet b = 2;\nfunction f() { let c = 1 +; return c; }\nb )";
+        let mut parser = Parser::new(source.to_string());
+        let (ast, errors) = parser.parse_with_recovery();
+        
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].span(), Some(Span::new(8, 9)));
+        assert_eq!(errors[1].span(), Some(Span::new(47, 48)));
+        assert_eq!(errors[2].span(), Some(Span::new(63, 64)));
+        
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        assert_eq!(stmts.len(), 4);
+        assert_eq!(stmts[0], ASTNode::Error { span: Span::new(0, 9) });
+        assert!(matches!(&stmts[1], ASTNode::VarDecl { name, .. } if name == "b"));
+        // The function keeps the statements around its bad one
+        let ASTNode::FunctionDecl { body, .. } = &stmts[2] else {
+            panic!("Expected FunctionDecl node");
+        };
+        let ASTNode::BlockStmt { statements, .. } = &**body else {
+            panic!("Expected BlockStmt node");
+        };
+        assert!(matches!(statements[0], ASTNode::Error { .. }));
+        assert!(matches!(statements[1], ASTNode::ReturnStmt { .. }));
+        assert_eq!(stmts[3], ASTNode::Error { span: Span::new(61, 64) });
+    }
+    
+    #[test]
+    fn test_parse_with_recovery_restores_context() {
+        // A stray `}` at the top level is skipped, and the loop the error
+        // happened in does not make the later `break` legal
+        let mut parser = Parser::new("} while (x) { 1 +; } break;".to_string());
+        let (_, errors) = parser.parse_with_recovery();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[2].to_string().contains("break"));
+        
+        // Without errors, recovery parses exactly like `parse`
+        let source = "let x = 1; while (x) { x = x - 1; }";
+        let (ast, errors) = Parser::new(source.to_string()).parse_with_recovery();
+        assert!(errors.is_empty());
+        assert_eq!(ast, Parser::new(source.to_string()).parse().unwrap());
+    }
+    
+    #[test]
+    fn test_parse_return_without_value() {
+        for source in ["function f() { return; }", "function f() { return }", "function f() { return\n1; }"] {
+            let ast = Parser::new(source.to_string()).parse().unwrap();
+            let ASTNode::Program(stmts) = ast.root else {
+                panic!("Expected Program node");
+            };
+            let ASTNode::FunctionDecl { body, .. } = &stmts[0] else {
+                panic!("Expected FunctionDecl node");
+            };
+            let ASTNode::BlockStmt { statements, .. } = &**body else {
+                panic!("Expected BlockStmt node");
+            };
+            assert!(matches!(statements[0], ASTNode::ReturnStmt { value: None, .. }), "{}", source);
+        }
+    }
+    
+    #[test]
+    fn test_parse_automatic_semicolons() {
+        // Inserted before `}`, at the end of input and after a line break
+        assert!(Parser::new("let a = 1\nlet b = 2".to_string()).parse().is_ok());
+        assert!(Parser::new("{ 1 }".to_string()).parse().is_ok());
+        assert!(Parser::new("do {} while (false) 1".to_string()).parse().is_ok());
+        
+        // Never between tokens on the same line, nor inside a for header
+        for source in ["let a = 1 let b = 2", "a b", "{ 1 2 }", "for (let i = 0\n i < 1\n i++) {}"] {
+            let err = Parser::new(source.to_string()).parse().unwrap_err();
+            assert!(matches!(err, ParseError::UnexpectedToken { ref expected, .. } if expected == "Semicolon"), "{}", source);
+        }
+        
+        // A postfix operator must be on the same line as its operand
+        let ast = Parser::new("a\n++b".to_string()).parse().unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        assert!(matches!(stmts[0], ASTNode::Identifier { .. }));
+        assert!(matches!(stmts[1], ASTNode::UpdateExpr { prefix: true, .. }));
+    }
+    
+    #[test]
+    fn test_parse_templates() {
+        let ast = Parser::new("`a${x + 1}b`; tag`c${y}`".to_string()).parse().unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::TemplateLiteral { quasis, expressions, span } = &stmts[0] else {
+            panic!("Expected TemplateLiteral node");
+        };
+        let cooked: Vec<_> = quasis.iter().map(|q| q.cooked.as_deref().unwrap()).collect();
+        assert_eq!(cooked, ["a", "b"]);
+        assert!(matches!(expressions[..], [ASTNode::BinaryExpr { .. }]));
+        assert_eq!(*span, Span::new(0, 12));
+        
+        let ASTNode::TaggedTemplate { tag, quasis, expressions, span } = &stmts[1] else {
+            panic!("Expected TaggedTemplate node");
+        };
+        assert!(matches!(&**tag, ASTNode::Identifier { name, .. } if name == "tag"));
+        assert_eq!(quasis.len(), 2);
+        assert_eq!(expressions.len(), 1);
+        assert_eq!(*span, Span::new(14, 24));
+    }
+    
+    #[test]
+    fn test_parse_template_errors() {
+        // Invalid escapes are only allowed in tagged templates
+        let err = Parser::new(r"`\x`".to_string()).parse().unwrap_err();
+        assert!(err.to_string().contains("Invalid escape sequence"));
+        assert!(Parser::new(r"tag`\x`".to_string()).parse().is_ok());
+        
+        let err = Parser::new("`${a b}`".to_string()).parse().unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(5, 6)));
+        assert!(Parser::new("`${}`".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_try_statements() {
+        let ast = Parser::new("try { a(); } catch (e) { b(); } finally { c(); }\ntry {} catch {}".to_string())
+            .parse()
+            .unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::TryStmt { param, handler, finalizer, span, .. } = &stmts[0] else {
+            panic!("Expected TryStmt node");
+        };
+        assert_eq!(param.as_deref(), Some("e"));
+        assert!(handler.is_some() && finalizer.is_some());
+        assert_eq!(*span, Span::new(0, 48));
+        assert!(matches!(&stmts[1], ASTNode::TryStmt { param: None, handler: Some(_), finalizer: None, .. }));
+        
+        let ast = Parser::new("throw x + 1".to_string()).parse().unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        assert!(matches!(&stmts[0], ASTNode::ThrowStmt { value, .. } if matches!(**value, ASTNode::BinaryExpr { .. })));
+    }
+    
+    #[test]
+    fn test_parse_try_errors() {
+        let err = Parser::new("try {}".to_string()).parse().unwrap_err();
+        assert!(err.to_string().contains("Missing catch or finally"));
+        
+        // No line break is allowed between `throw` and its value
+        let err = Parser::new("throw\n1".to_string()).parse().unwrap_err();
+        assert_eq!(err.message(), "Illegal newline after throw");
+        
+        assert!(Parser::new("try {} catch (1) {}".to_string()).parse().is_err());
+        assert!(Parser::new("try x; finally {}".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_function_expressions() {
+        let ast = Parser::new("let f = function fact(n) { return n; };\n(function () {})();".to_string())
+            .parse()
+            .unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::VarDecl { init: Some(init), .. } = &stmts[0] else {
+            panic!("Expected VarDecl node");
+        };
+        let ASTNode::FunctionExpr { name, params, span, .. } = &**init else {
+            panic!("Expected FunctionExpr node");
+        };
+        assert_eq!(name.as_deref(), Some("fact"));
+        assert_eq!(params, &[Param::new("n")]);
+        assert_eq!(*span, Span::new(8, 38));
+        
+        let ASTNode::CallExpr { callee, .. } = &stmts[1] else {
+            panic!("Expected CallExpr node");
+        };
+        assert!(matches!(&**callee, ASTNode::FunctionExpr { name: None, .. }));
+    }
+    
+    #[test]
+    fn test_parse_arrow_functions() {
+        let ast = Parser::new("x => x * 2;\n(a, b) => { return a; };\n() => 1;\n(a) + b;".to_string())
+            .parse()
+            .unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::ArrowFunction { params, body, span } = &stmts[0] else {
+            panic!("Expected ArrowFunction node");
+        };
+        assert_eq!(params, &[Param::new("x")]);
+        assert!(matches!(**body, ASTNode::BinaryExpr { .. }));
+        assert_eq!(*span, Span::new(0, 10));
+        
+        assert!(matches!(&stmts[1], ASTNode::ArrowFunction { params, body, .. }
+            if params.len() == 2 && matches!(**body, ASTNode::BlockStmt { .. })));
+        assert!(matches!(&stmts[2], ASTNode::ArrowFunction { params, .. } if params.is_empty()));
+        
+        // A parenthesised expression not followed by `=>` is just grouping
+        assert!(matches!(&stmts[3], ASTNode::BinaryExpr { .. }));
+        
+        // No line break is allowed before `=>`
+        let err = Parser::new("(a)\n=> a".to_string()).parse().unwrap_err();
+        assert_eq!(err.message(), "Illegal newline before =>");
+        assert!(Parser::new("(a, 1) => a".to_string()).parse().is_err());
+        
+        // Only the `)` matching each `(` decides, however the parentheses nest
+        let ast = Parser::new("((a = (1), b) => (a))((2))".to_string()).parse().unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        assert!(matches!(&stmts[0], ASTNode::CallExpr { callee, .. }
+            if matches!(&**callee, ASTNode::ArrowFunction { params, .. } if params.len() == 2)));
+    }
+    
+    #[test]
+    fn test_match_parens() {
+        let tokens = Lexer::new(")(a(b)(".to_string()).tokenize();
+        assert_eq!(match_parens(&tokens), HashMap::from([(3, 5)]));
+    }
+    
+    #[test]
+    fn test_parse_default_and_rest_params() {
+        let ast = Parser::new("function f(a, b = a + 1, ...rest) {}\n(x = 2, ...ys) => ys;".to_string())
+            .parse()
+            .unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::FunctionDecl { params, .. } = &stmts[0] else {
+            panic!("Expected FunctionDecl node");
+        };
+        assert_eq!(params[0], Param::new("a"));
+        assert_eq!(params[1].name, "b");
+        assert!(matches!(params[1].default, Some(ASTNode::BinaryExpr { .. })));
+        assert!(params[2].rest && params[2].default.is_none());
+        
+        let ASTNode::ArrowFunction { params, .. } = &stmts[1] else {
+            panic!("Expected ArrowFunction node");
+        };
+        assert!(params[0].default.is_some() && !params[0].rest);
+        assert!(params[1].rest);
+    }
+    
+    #[test]
+    fn test_parse_param_errors() {
+        let err = Parser::new("function f(...a, b) {}".to_string()).parse().unwrap_err();
+        assert_eq!(err.message(), "Rest parameter must be last formal parameter");
+        assert_eq!(err.span(), Some(Span::new(11, 14)));
+        
+        let err = Parser::new("function f(...a = []) {}".to_string()).parse().unwrap_err();
+        assert_eq!(err.message(), "Rest parameter may not have a default initializer");
+        assert!(Parser::new("function f(...) {}".to_string()).parse().is_err());
+    }
+    
+    #[test]
+    fn test_parse_lists_need_commas() {
+        for (source, offset) in [("function f(a b) {}", 13), ("(a b) => 1", 3), ("print(1 2)", 8)] {
+            let err = Parser::new(source.to_string()).parse().unwrap_err();
+            assert!(matches!(&err, ParseError::UnexpectedToken { expected, .. } if expected == "Comma"), "{source}: {err}");
+            assert_eq!(err.span(), Some(Span::new(offset, offset + 1)), "{source}");
+        }
+        
+        // A trailing comma is still allowed
+        assert!(Parser::new("function f(a, b,) {} f(1, 2,)".to_string()).parse().is_ok());
+    }
+    
+    #[test]
+    fn test_parse_spread_arguments() {
+        let ast = Parser::new("f(a, ...b, ...[1, 2])".to_string()).parse().unwrap();
+        let ASTNode::Program(stmts) = ast.root else {
+            panic!("Expected Program node");
+        };
+        
+        let ASTNode::CallExpr { args, .. } = &stmts[0] else {
+            panic!("Expected CallExpr node");
+        };
+        assert!(matches!(&args[0], ASTNode::Identifier { .. }));
+        assert!(matches!(&args[1], ASTNode::Spread { argument, span }
+            if matches!(**argument, ASTNode::Identifier { .. }) && *span == Span::new(5, 9)));
+        assert!(matches!(&args[2], ASTNode::Spread { argument, .. } if matches!(**argument, ASTNode::ArrayLiteral { .. })));
+        
+        // Spread is only allowed in arguments
+        assert!(Parser::new("let a = ...b;".to_string()).parse().is_err());
+    }
 }
@@ -1,6 +1,7 @@
 // Scope management for variable resolution
 
-use std::collections::HashMap;
+use crate::ast::{ASTNode, DeclKind, Param};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Type of scope
 #[derive(Debug, Clone, PartialEq)]
@@ -10,13 +11,224 @@ pub enum ScopeType {
     Block,
 }
 
+/// Where a resolved variable lives, relative to the function doing the lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    /// Slot in the current function's frame
+    Local(usize),
+    /// Variable shared with closures, held in a heap cell at this index of the frame's upvalues
+    Captured(usize),
+    /// Slot in the top-level script frame
+    Global(usize),
+}
+
+/// Result of analysing a function body for closure conversion
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionAnalysis {
+    /// Names the function uses but does not declare, including those used by nested functions
+    pub free: BTreeSet<String>,
+    /// Names the function declares that nested functions use
+    pub captured: BTreeSet<String>,
+    /// `var` declarations anywhere in the body, outside nested functions, in order
+    pub vars: Vec<String>,
+    /// `let`/`const` names that appear in the source before their declaration
+    pub used_before_declaration: BTreeSet<String>,
+    /// Whether the function uses its `arguments` object, itself or from a nested arrow function
+    pub arguments: bool,
+}
+
+/// Names declared by one statement list or `for` header
+#[derive(Default)]
+struct BlockNames {
+    declared: BTreeSet<String>,
+    /// `let`/`const` names whose declaration has not been reached yet
+    pending: BTreeSet<String>,
+}
+
+impl FunctionAnalysis {
+    /// Analyse a function with the given parameters and body, without an
+    /// `arguments` object of its own (an arrow function or a script)
+    pub fn analyze(params: &[Param], body: &ASTNode) -> Self {
+        let mut analysis = Self::default();
+        Self::collect_vars(body, &mut analysis.vars);
+        
+        let mut root = BlockNames::default();
+        root.declared.extend(params.iter().map(|param| param.name.clone()));
+        root.declared.extend(analysis.vars.iter().cloned());
+        let mut blocks = vec![root];
+        
+        // Default values run in the parameters' scope, before the body
+        for default in params.iter().filter_map(|param| param.default.as_ref()) {
+            analysis.visit(default, &mut blocks);
+        }
+        match body {
+            ASTNode::Program(statements) | ASTNode::BlockStmt { statements, .. } => {
+                analysis.visit_statements(statements, &mut blocks);
+            }
+            _ => analysis.visit(body, &mut blocks),
+        }
+        
+        analysis
+    }
+    
+    /// Analyse a `function` function, which has its own `arguments` object
+    pub fn analyze_function(params: &[Param], body: &ASTNode) -> Self {
+        let analysis = Self::analyze(params, body);
+        if !analysis.free.contains("arguments") {
+            return analysis;
+        }
+        
+        // Declared after the parameters, so nested arrow functions capture it like one
+        let mut params = params.to_vec();
+        params.push(Param::new("arguments"));
+        Self {
+            arguments: true,
+            ..Self::analyze(&params, body)
+        }
+    }
+    
+    fn collect_vars(node: &ASTNode, vars: &mut Vec<String>) {
+        match node {
+            ASTNode::FunctionDecl { .. } | ASTNode::FunctionExpr { .. } | ASTNode::ArrowFunction { .. } => {}
+            ASTNode::VarDecl { kind: DeclKind::Var, name, .. } => {
+                if !vars.contains(name) {
+                    vars.push(name.clone());
+                }
+            }
+            _ => node.for_each_child(|child| Self::collect_vars(child, vars)),
+        }
+    }
+    
+    /// Visit a statement list whose declarations belong to the innermost block
+    fn visit_statements(&mut self, statements: &[ASTNode], blocks: &mut Vec<BlockNames>) {
+        let block = blocks.last_mut().expect("no enclosing block");
+        for statement in statements {
+            match statement {
+                ASTNode::VarDecl { kind, name, .. } if kind.is_lexical() => {
+                    block.declared.insert(name.clone());
+                    block.pending.insert(name.clone());
+                }
+                ASTNode::FunctionDecl { name, .. } => {
+                    block.declared.insert(name.clone());
+                }
+                _ => {}
+            }
+        }
+        
+        for statement in statements {
+            self.visit(statement, blocks);
+        }
+    }
+    
+    fn visit(&mut self, node: &ASTNode, blocks: &mut Vec<BlockNames>) {
+        match node {
+            ASTNode::BlockStmt { statements, .. } => {
+                blocks.push(BlockNames::default());
+                self.visit_statements(statements, blocks);
+                blocks.pop();
+            }
+            ASTNode::ForStmt { init, cond, update, body, .. } if init.is_lexical_decl() => {
+                // The header's bindings get a scope of their own around the loop
+                blocks.push(BlockNames::default());
+                self.visit_statements(std::slice::from_ref(&**init), blocks);
+                self.visit(cond, blocks);
+                self.visit(update, blocks);
+                self.visit(body, blocks);
+                blocks.pop();
+            }
+            ASTNode::TryStmt { block, param, handler, finalizer, .. } => {
+                self.visit(block, blocks);
+                if let Some(handler) = handler {
+                    // The catch parameter is scoped to its clause
+                    let mut names = BlockNames::default();
+                    names.declared.extend(param.iter().cloned());
+                    blocks.push(names);
+                    self.visit(handler, blocks);
+                    blocks.pop();
+                }
+                if let Some(finalizer) = finalizer {
+                    self.visit(finalizer, blocks);
+                }
+            }
+            ASTNode::FunctionDecl { name, params, body, .. } => {
+                if Self::declaring_block(blocks, name).is_none() {
+                    blocks.last_mut().expect("no enclosing block").declared.insert(name.clone());
+                }
+                self.visit_function(Self::analyze_function(params, body), blocks);
+            }
+            ASTNode::FunctionExpr { name, params, body, .. } => {
+                // A named function expression's own name is scoped around it
+                let mut names = BlockNames::default();
+                names.declared.extend(name.iter().cloned());
+                blocks.push(names);
+                self.visit_function(Self::analyze_function(params, body), blocks);
+                blocks.pop();
+            }
+            ASTNode::ArrowFunction { params, body, .. } => {
+                self.visit_function(Self::analyze(params, body), blocks);
+            }
+            ASTNode::VarDecl { kind, name, init, .. } => {
+                // The initializer runs before the variable is initialised
+                if let Some(init) = init {
+                    self.visit(init, blocks);
+                }
+                if kind.is_lexical() {
+                    match Self::declaring_block(blocks, name) {
+                        Some(index) => {
+                            blocks[index].pending.remove(name);
+                        }
+                        None => {
+                            blocks.last_mut().expect("no enclosing block").declared.insert(name.clone());
+                        }
+                    }
+                }
+            }
+            ASTNode::Identifier { name, .. } => match Self::declaring_block(blocks, name) {
+                Some(index) if blocks[index].pending.contains(name) => {
+                    self.used_before_declaration.insert(name.clone());
+                }
+                Some(_) => {}
+                None => {
+                    self.free.insert(name.clone());
+                }
+            },
+            _ => node.for_each_child(|child| self.visit(child, blocks)),
+        }
+    }
+    
+    /// Record the names a nested function uses as captured from this one or free in it
+    fn visit_function(&mut self, nested: FunctionAnalysis, blocks: &[BlockNames]) {
+        for name in nested.free {
+            if Self::declaring_block(blocks, &name).is_some() {
+                self.captured.insert(name);
+            } else {
+                self.free.insert(name);
+            }
+        }
+    }
+    
+    /// Index of the innermost block that declares `name`
+    fn declaring_block(blocks: &[BlockNames], name: &str) -> Option<usize> {
+        blocks.iter().rposition(|block| block.declared.contains(name))
+    }
+}
+
 /// Scope for managing variable bindings
 #[derive(Debug, Clone)]
 pub struct Scope {
     parent: Option<Box<Scope>>,
     variables: HashMap<String, usize>,
+    /// Variables held in heap cells, by upvalue index
+    cells: HashMap<String, usize>,
+    /// How `var`/`let`/`const` variables were declared; absent for parameters and functions
+    kinds: HashMap<String, DeclKind>,
+    /// `let`/`const` variables of this scope whose declaration has been compiled
+    initialized: HashSet<String>,
     scope_type: ScopeType,
     next_index: usize,
+    next_cell: usize,
+    /// Highest slot count reached by this scope and the blocks nested in it
+    max_index: usize,
 }
 
 impl Scope {
@@ -25,8 +237,13 @@ impl Scope {
         Self {
             parent,
             variables: HashMap::new(),
+            cells: HashMap::new(),
+            kinds: HashMap::new(),
+            initialized: HashSet::new(),
             scope_type,
             next_index: 0,
+            next_cell: 0,
+            max_index: 0,
         }
     }
     
@@ -40,19 +257,101 @@ impl Scope {
         Self::new(ScopeType::Function, Some(Box::new(self.clone())))
     }
     
-    /// Create a block scope with this scope as parent
+    /// Create a block scope with this scope as parent.
+    ///
+    /// Blocks share their function's frame, so their slots and cells are
+    /// numbered on from the parent's.
     pub fn block_scope(&self) -> Self {
-        Self::new(ScopeType::Block, Some(Box::new(self.clone())))
+        let mut block = Self::new(ScopeType::Block, Some(Box::new(self.clone())));
+        block.next_index = self.next_index;
+        block.next_cell = self.next_cell;
+        block
+    }
+    
+    /// Leave a block scope, returning its parent with the block's slots freed for reuse
+    pub fn exit_block(self) -> Scope {
+        let local_count = self.local_count();
+        let mut parent = *self.parent.expect("block scope has a parent");
+        parent.max_index = parent.max_index.max(local_count);
+        parent
     }
     
     /// Declare a new variable in this scope
     pub fn declare(&mut self, name: String) -> usize {
         let index = self.next_index;
+        // A redeclaration starts out uninitialised again
+        self.initialized.remove(&name);
         self.variables.insert(name, index);
         self.next_index += 1;
         index
     }
     
+    /// Declare a variable that lives in a heap cell, returning its upvalue index
+    pub fn declare_cell(&mut self, name: String) -> usize {
+        let index = self.next_cell;
+        self.initialized.remove(&name);
+        self.cells.insert(name, index);
+        self.next_cell += 1;
+        index
+    }
+    
+    /// Declare a variable, reusing its cell if it was declared captured up front
+    pub fn declare_binding(&mut self, name: String) -> Binding {
+        match self.cells.get(&name) {
+            Some(&index) => Binding::Captured(index),
+            None => Binding::Local(self.declare(name)),
+        }
+    }
+    
+    /// Whether this scope already has a frame slot for `name`
+    pub fn declares_local(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+    
+    /// Record how a variable of this scope was declared
+    pub fn set_kind(&mut self, name: String, kind: DeclKind) {
+        self.kinds.insert(name, kind);
+    }
+    
+    /// How the variable `name` resolves to was declared, if by `var`/`let`/`const`
+    pub fn kind(&self, name: &str) -> Option<DeclKind> {
+        let scope = self.declaring_scope(name)?;
+        scope.kinds.get(name).copied()
+    }
+    
+    /// Record that the declaration of this scope's `let`/`const` variable `name` has run
+    pub fn mark_initialized(&mut self, name: String) {
+        self.initialized.insert(name);
+    }
+    
+    /// Forget which of this scope's `let`/`const` declarations have run
+    pub fn forget_initialized(&mut self) {
+        self.initialized.clear();
+    }
+    
+    /// Whether the declaration of the variable `name` resolves to is known to have run
+    pub fn is_initialized(&self, name: &str) -> bool {
+        self.declaring_scope(name)
+            .is_some_and(|scope| scope.initialized.contains(name))
+    }
+    
+    /// The innermost scope with a slot or cell for `name`
+    fn declaring_scope(&self, name: &str) -> Option<&Scope> {
+        let mut scope = self;
+        while !scope.variables.contains_key(name) && !scope.cells.contains_key(name) {
+            scope = scope.parent.as_deref()?;
+        }
+        Some(scope)
+    }
+    
+    /// Type of the scope owning the frame this scope's slots live in
+    fn frame_type(&self) -> &ScopeType {
+        match (&self.scope_type, &self.parent) {
+            (ScopeType::Block, Some(parent)) => parent.frame_type(),
+            (scope_type, _) => scope_type,
+        }
+    }
+    
     /// Look up a variable in this scope or parent scopes
     pub fn lookup(&self, name: &str) -> Option<usize> {
         if let Some(&index) = self.variables.get(name) {
@@ -64,15 +363,58 @@ impl Scope {
         }
     }
     
+    /// Resolve a variable to the frame it lives in.
+    ///
+    /// Variables of enclosing functions are only reachable through the
+    /// cells a closure captured; any other such variable resolves to `None`.
+    pub fn resolve(&self, name: &str) -> Option<Binding> {
+        let mut scope = self;
+        let mut crossed_function = false;
+        
+        loop {
+            if let Some(&index) = scope.cells.get(name) {
+                return (!crossed_function).then_some(Binding::Captured(index));
+            }
+            
+            if let Some(&index) = scope.variables.get(name) {
+                return match scope.frame_type() {
+                    ScopeType::Global if crossed_function => Some(Binding::Global(index)),
+                    _ if crossed_function => None,
+                    _ => Some(Binding::Local(index)),
+                };
+            }
+            
+            if scope.scope_type == ScopeType::Function {
+                crossed_function = true;
+            }
+            scope = scope.parent.as_deref()?;
+        }
+    }
+    
     /// Get the scope type
     pub fn scope_type(&self) -> &ScopeType {
         &self.scope_type
     }
     
-    /// Get the number of variables in this scope
+    /// Get the number of frame slots this scope and its blocks need
     pub fn local_count(&self) -> usize {
+        self.next_index.max(self.max_index)
+    }
+    
+    /// Variables declared directly in this scope, with their slots
+    pub fn variables(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.variables.iter().map(|(name, &index)| (name.as_str(), index))
+    }
+    
+    /// Get the number of variables declared directly in this scope, excluding its blocks
+    pub fn declared_count(&self) -> usize {
         self.next_index
     }
+    
+    /// Get the number of heap cells declared in this scope
+    pub fn cell_count(&self) -> usize {
+        self.next_cell
+    }
 }
 
 #[cfg(test)]
@@ -132,7 +474,151 @@ mod tests {
         // Block scope can see all variables
         assert_eq!(block.lookup("a"), Some(0));
         assert_eq!(block.lookup("b"), Some(0));
-        assert_eq!(block.lookup("c"), Some(0));
+        assert_eq!(block.lookup("c"), Some(1));
+    }
+    
+    #[test]
+    fn test_block_scope_shadowing() {
+        let mut func = Scope::global().function_scope();
+        func.declare("x".to_string());
+        
+        // A block's slots follow its function's, and shadow outer names
+        let mut block = func.block_scope();
+        assert_eq!(block.declare("x".to_string()), 1);
+        assert_eq!(block.declare("y".to_string()), 2);
+        assert_eq!(block.resolve("x"), Some(Binding::Local(1)));
+        
+        // Leaving the block frees its slots but keeps the frame big enough
+        let mut func = block.exit_block();
+        assert_eq!(func.resolve("x"), Some(Binding::Local(0)));
+        assert_eq!(func.resolve("y"), None);
+        assert_eq!(func.local_count(), 3);
+        
+        let mut sibling = func.block_scope();
+        assert_eq!(sibling.declare("z".to_string()), 1);
+        func = sibling.exit_block();
+        assert_eq!(func.local_count(), 3);
+        
+        // Top-level blocks still live in the script frame
+        let mut block = Scope::global().block_scope();
+        block.declare("t".to_string());
+        assert_eq!(block.function_scope().resolve("t"), Some(Binding::Global(0)));
+    }
+    
+    #[test]
+    fn test_resolve_binding() {
+        let mut global = Scope::global();
+        global.declare("a".to_string());
+        
+        let mut func = global.function_scope();
+        func.declare("b".to_string());
+        
+        assert_eq!(global.resolve("a"), Some(Binding::Local(0)));
+        assert_eq!(func.resolve("a"), Some(Binding::Global(0)));
+        assert_eq!(func.resolve("b"), Some(Binding::Local(0)));
+        assert_eq!(func.resolve("c"), None);
+        
+        // A function nested in a function cannot reach its parent's frame
+        let inner = func.function_scope();
+        assert_eq!(inner.resolve("b"), None);
+        assert_eq!(inner.resolve("a"), Some(Binding::Global(0)));
+    }
+    
+    #[test]
+    fn test_resolve_captured() {
+        let global = Scope::global();
+        let mut outer = global.function_scope();
+        let cell = outer.declare_cell("count".to_string());
+        
+        assert_eq!(outer.declare_binding("count".to_string()), Binding::Captured(cell));
+        assert_eq!(outer.declare_binding("other".to_string()), Binding::Local(0));
+        assert_eq!(outer.resolve("count"), Some(Binding::Captured(cell)));
+        assert_eq!(outer.cell_count(), 1);
+        
+        // A nested function only sees the cell once it captures it itself
+        let mut inner = outer.function_scope();
+        assert_eq!(inner.resolve("count"), None);
+        inner.declare_cell("count".to_string());
+        assert_eq!(inner.resolve("count"), Some(Binding::Captured(0)));
+    }
+    
+    #[test]
+    fn test_analyze_function() {
+        let source = "let a = 1; let b = 2; function inner(c) { return a + c + g; } b";
+        let ast = crate::parser::Parser::new(source.to_string()).parse().unwrap();
+        
+        let analysis = FunctionAnalysis::analyze(&[Param::new("p")], &ast.root);
+        let names = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(names(&analysis.captured), vec!["a"]);
+        assert_eq!(names(&analysis.free), vec!["g"]);
+    }
+    
+    #[test]
+    fn test_analyze_arguments_object() {
+        let source = "{ let f = () => arguments[0]; function g() { return arguments; } }";
+        let ast = crate::parser::Parser::new(source.to_string()).parse().unwrap();
+        
+        // An arrow function uses the `arguments` around it; `g` has its own
+        let analysis = FunctionAnalysis::analyze_function(&[], &ast.root);
+        assert!(analysis.arguments);
+        assert_eq!(analysis.captured.iter().collect::<Vec<_>>(), ["arguments"]);
+        assert!(analysis.free.is_empty());
+        
+        let analysis = FunctionAnalysis::analyze(&[], &ast.root);
+        assert!(!analysis.arguments);
+        assert_eq!(analysis.free.iter().collect::<Vec<_>>(), ["arguments"]);
+        
+        // Default values are analysed with the parameters in scope
+        let params = [Param::new("a"), Param { default: Some(ast.root.clone()), ..Param::new("b") }];
+        let analysis = FunctionAnalysis::analyze_function(&params, &ASTNode::Program(vec![]));
+        assert!(analysis.arguments);
+    }
+    
+    #[test]
+    fn test_declaration_kinds() {
+        let source = "var a; function f() { let hidden = 1; } if (a) { const b = 2; }";
+        let ast = crate::parser::Parser::new(source.to_string()).parse().unwrap();
+        
+        let analysis = FunctionAnalysis::analyze(&[], &ast.root);
+        assert_eq!(analysis.vars, vec!["a".to_string()]);
+        assert!(analysis.used_before_declaration.is_empty());
+        
+        let ast = crate::parser::Parser::new("x; let x = y; let y = 1;".to_string()).parse().unwrap();
+        let analysis = FunctionAnalysis::analyze(&[], &ast.root);
+        let names = analysis.used_before_declaration.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(names, vec!["x", "y"]);
+        
+        let mut global = Scope::global();
+        global.declare("b".to_string());
+        global.set_kind("b".to_string(), DeclKind::Const);
+        let mut func = global.function_scope();
+        func.declare("p".to_string());
+        assert_eq!(func.kind("b"), Some(DeclKind::Const));
+        assert_eq!(func.kind("p"), None);
+        assert!(func.declares_local("p"));
+        assert!(!func.declares_local("b"));
+        
+        let mut block = func.block_scope();
+        block.declare("b".to_string());
+        block.set_kind("b".to_string(), DeclKind::Let);
+        assert!(!block.is_initialized("b"));
+        block.mark_initialized("b".to_string());
+        assert!(block.is_initialized("b"));
+        assert!(!block.exit_block().is_initialized("b"));
+    }
+    
+    #[test]
+    fn test_analyze_block_shadowing() {
+        let source = "let x = 1; { x; let x = 2; function f() { return x + y; } } \
+                      for (let i = 0; i < 3; i = i + 1) { let y = i; } y;";
+        let ast = crate::parser::Parser::new(source.to_string()).parse().unwrap();
+        
+        let analysis = FunctionAnalysis::analyze(&[], &ast.root);
+        let names = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(names(&analysis.captured), vec!["x"]);
+        assert_eq!(names(&analysis.used_before_declaration), vec!["x"]);
+        // The loop body's `y` is out of scope for `f` and the final statement
+        assert_eq!(names(&analysis.free), vec!["y"]);
     }
     
     #[test]
@@ -0,0 +1,168 @@
+// Source map for turning character spans into lines, columns and source snippets
+
+use crate::error::{Error, ParseError};
+use crate::lexer::is_line_terminator;
+use crate::types::Span;
+use std::fmt::Write;
+
+/// A 1-based line and column position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A named source text with the offsets where each of its lines start
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    name: String,
+    text: Vec<char>,
+    /// Character offset of the first character of every line
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    /// Index `source` under `name`, usually a file path or `<repl>`
+    pub fn new(name: impl Into<String>, source: &str) -> Self {
+        let text: Vec<char> = source.chars().collect();
+        let mut line_starts = vec![0];
+        for (offset, &ch) in text.iter().enumerate() {
+            // `\r\n` is a single line break
+            if is_line_terminator(ch) && !(ch == '\r' && text.get(offset + 1) == Some(&'\n')) {
+                line_starts.push(offset + 1);
+            }
+        }
+    
+        Self {
+            name: name.into(),
+            text,
+            line_starts,
+        }
+    }
+    
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+    
+    /// The line and column of a character offset, clamped to the end of the source
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        LineCol {
+            line,
+            column: offset - self.line_starts[line - 1] + 1,
+        }
+    }
+    
+    /// The text of a 1-based line, without its terminator
+    pub fn line_text(&self, line: usize) -> Option<String> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        Some(self.text[start..]
+            .iter()
+            .take_while(|&&ch| !is_line_terminator(ch))
+            .collect())
+    }
+    
+    /// Render a diagnostic in the style of rustc: the message, the location, and
+    /// the first line of the span with the span underlined
+    pub fn render(&self, message: &str, span: Option<Span>) -> String {
+        let mut out = format!("{}\n", message);
+        let Some(span) = span else {
+            let _ = writeln!(out, " --> {}", self.name);
+            return out;
+        };
+    
+        let start = self.line_col(span.start);
+        let line = self.line_text(start.line).unwrap_or_default();
+        let gutter = " ".repeat(start.line.to_string().len());
+        let _ = writeln!(out, "{}--> {}:{}:{}", gutter, self.name, start.line, start.column);
+        let _ = writeln!(out, "{} |", gutter);
+        let _ = writeln!(out, "{} | {}", start.line, line);
+    
+        // Spans running past the line are underlined to its end
+        let line_len = line.chars().count();
+        let end = self.line_col(span.end.max(span.start));
+        let end_column = if end.line == start.line { end.column } else { line_len + 1 };
+        let width = end_column.saturating_sub(start.column).max(1);
+        // Keep tabs so the carets line up with the text above
+        let padding: String = line.chars()
+            .take(start.column - 1)
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let _ = writeln!(out, "{} | {}{}", gutter, padding, "^".repeat(width));
+        out
+    }
+    
+    /// Render an engine error against this source
+    pub fn render_error(&self, err: &Error) -> String {
+        let span = match err {
+            // Point at the end of the input
+            Error::ParseError(ParseError::UnexpectedEOF) => {
+                Some(Span::new(self.text.len(), self.text.len()))
+            }
+            _ => err.span(),
+        };
+        self.render(&err.message(), span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RuntimeError;
+    
+    #[test]
+    fn test_line_col() {
+        let map = SourceMap::new("test.js", "let a = 1;\nlet b = 2;\r\n\nc");
+        assert_eq!(map.line_count(), 4);
+        assert_eq!(map.line_col(0), LineCol { line: 1, column: 1 });
+        assert_eq!(map.line_col(10), LineCol { line: 1, column: 11 });
+        assert_eq!(map.line_col(11), LineCol { line: 2, column: 1 });
+        assert_eq!(map.line_col(15), LineCol { line: 2, column: 5 });
+        assert_eq!(map.line_col(23), LineCol { line: 3, column: 1 });
+        assert_eq!(map.line_col(24), LineCol { line: 4, column: 1 });
+        assert_eq!(map.line_col(100), LineCol { line: 4, column: 2 });
+    
+        assert_eq!(map.line_text(2), Some("let b = 2;".to_string()));
+        assert_eq!(map.line_text(3), Some(String::new()));
+        assert_eq!(map.line_text(5), None);
+        assert_eq!(map.line_text(0), None);
+    }
+    
+    #[test]
+    fn test_render_underlines_span() {
+        let map = SourceMap::new("main.js", "let a = 1;\n\tfoo(bar);\n");
+        let rendered = map.render("error: something", Some(Span::new(16, 19)));
+        assert_eq!(
+            rendered,
+            "error: something\n --> main.js:2:6\n  |\n2 | \tfoo(bar);\n  | \t    ^^^\n"
+        );
+    }
+    
+    #[test]
+    fn test_render_multiline_and_empty_spans() {
+        let map = SourceMap::new("main.js", "foo(1,\n  2)");
+        // A span crossing lines is underlined to the end of its first line
+        let rendered = map.render("msg", Some(Span::new(3, 10)));
+        assert!(rendered.ends_with("1 | foo(1,\n  |    ^^^\n"));
+    
+        // An empty span still gets a caret
+        let rendered = map.render("msg", Some(Span::new(0, 0)));
+        assert!(rendered.ends_with("  | ^\n"));
+    }
+    
+    #[test]
+    fn test_render_without_span() {
+        let map = SourceMap::new("main.js", "1 / 0");
+        let rendered = map.render_error(&Error::RuntimeError(RuntimeError::DivisionByZero { span: None }));
+        assert_eq!(rendered, "Runtime error: Division by zero\n --> main.js\n");
+    
+        let rendered = map.render_error(&Error::ParseError(ParseError::UnexpectedEOF));
+        assert!(rendered.contains(" --> main.js:1:6\n"));
+        assert!(rendered.ends_with("1 | 1 / 0\n  |      ^\n"));
+    }
+}
@@ -1,7 +1,7 @@
 // TurboFan JIT compiler
 
 use crate::bytecode::{BytecodeChunk, Instruction};
-use crate::ir::{IR, IRNode, NodeId, Type};
+use crate::ir::{CompareOp, IR, IRNode, NodeId, ShortCircuitOp, Type};
 use crate::types::FunctionId;
 use std::collections::HashMap;
 
@@ -27,15 +27,19 @@ impl TurboFan {
         // Map local variable indices to their current IR node IDs
         let mut locals: HashMap<usize, NodeId> = HashMap::new();
         
-        for instruction in &bytecode.instructions {
+        // Short-circuit jumps waiting for their target: (target, op, left operand)
+        let mut pending: Vec<(usize, ShortCircuitOp, NodeId)> = Vec::new();
+        
+        for (pc, instruction) in bytecode.instructions.iter().enumerate() {
+            self.merge_short_circuits(&mut ir, &mut pending, pc);
+            
             match instruction {
                 Instruction::LoadConst(idx) => {
-                    if let Some(value) = bytecode.constants.get(*idx) {
-                        if let crate::types::Value::Number(n) = value {
-                            let node_id = ir.add_constant(*n);
-                            self.value_stack.push(node_id);
-                        }
-                    }
+                    let node_id = match bytecode.constants.get(*idx) {
+                        Some(crate::types::Value::Number(n)) => ir.add_constant(*n),
+                        _ => ir.add_heap_constant(*idx),
+                    };
+                    self.value_stack.push(node_id);
                 }
                 
                 Instruction::LoadLocal(idx) => {
@@ -48,12 +52,121 @@ impl TurboFan {
                 
                 Instruction::StoreLocal(idx) => {
                     if let Some(value) = self.value_stack.pop() {
-                        let node_id = ir.add_store_local(*idx, value);
+                        ir.add_store_local(*idx, value);
                         locals.insert(*idx, value);
+                    }
+                }
+                
+                Instruction::LoadGlobal(idx) => {
+                    // Globals can be changed by any call, so no type feedback is assumed
+                    let node_id = ir.add_load_global(*idx);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::StoreGlobal(idx) => {
+                    if let Some(value) = self.value_stack.pop() {
+                        ir.add_store_global(*idx, value);
+                    }
+                }
+                
+                Instruction::LoadGlobalName(name_idx, _) => {
+                    let node_id = ir.add_load_global_name(*name_idx);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::StoreGlobalName(name_idx, _) => {
+                    if let Some(value) = self.value_stack.pop() {
+                        ir.add_store_global_name(*name_idx, value);
+                    }
+                }
+                
+                Instruction::LoadUpvalue(idx) => {
+                    // Cells are shared with closures, so no type feedback is assumed
+                    let node_id = ir.add_load_upvalue(*idx);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::StoreUpvalue(idx) => {
+                    if let Some(value) = self.value_stack.pop() {
+                        ir.add_store_upvalue(*idx, value);
+                    }
+                }
+                
+                Instruction::FreshCell(idx) => {
+                    ir.add_fresh_cell(*idx);
+                }
+                
+                Instruction::LoadHole => {
+                    let node_id = ir.add_hole();
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::CheckInitialized(_) => {
+                    if let Some(value) = self.value_stack.pop() {
+                        let node_id = ir.add_check_hole(value);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
+                Instruction::Throw => {
+                    if let Some(value) = self.value_stack.pop() {
+                        ir.add_throw(value);
+                    }
+                }
+                
+                Instruction::Rethrow => {
+                    if let Some(value) = self.value_stack.pop() {
+                        ir.add_rethrow(value);
+                    }
+                }
+                
+                Instruction::ThrowConstAssignment(name_idx) => {
+                    ir.add_throw_const_assignment(*name_idx);
+                }
+                
+                Instruction::MakeClosure(func_id) => {
+                    let node_id = ir.add_make_closure(*func_id);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::CreateObject(count) => {
+                    let split = self.value_stack.len().saturating_sub(count * 2);
+                    let entries = self.value_stack.split_off(split);
+                    let node_id = ir.add_create_object(entries);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::CreateArray(count) => {
+                    let split = self.value_stack.len().saturating_sub(*count);
+                    let elements = self.value_stack.split_off(split);
+                    let node_id = ir.add_create_array(elements);
+                    self.value_stack.push(node_id);
+                }
+                
+                Instruction::AppendSpread => {
+                    // The array stays on the stack for further elements
+                    if let (Some(iterable), Some(&array)) = (self.value_stack.pop(), self.value_stack.last()) {
+                        ir.add_append_spread(array, iterable);
+                    }
+                }
+                
+                Instruction::GetProperty => {
+                    if let (Some(key), Some(object)) = (self.value_stack.pop(), self.value_stack.pop()) {
+                        let node_id = ir.add_get_property(object, key);
                         self.value_stack.push(node_id);
                     }
                 }
                 
+                Instruction::SetProperty => {
+                    if let (Some(value), Some(key), Some(object)) =
+                        (self.value_stack.pop(), self.value_stack.pop(), self.value_stack.pop())
+                    {
+                        ir.add_set_property(object, key, value);
+                        // The stored value is the result of the assignment
+                        self.value_stack.push(value);
+                    }
+                }
+                
                 Instruction::Add => {
                     if let (Some(right), Some(left)) = (self.value_stack.pop(), self.value_stack.pop()) {
                         let node_id = ir.add_add(left, right);
@@ -82,6 +195,72 @@ impl TurboFan {
                     }
                 }
                 
+                Instruction::Equal
+                | Instruction::NotEqual
+                | Instruction::StrictEqual
+                | Instruction::StrictNotEqual
+                | Instruction::Less
+                | Instruction::LessEqual
+                | Instruction::Greater
+                | Instruction::GreaterEqual => {
+                    let op = match instruction {
+                        Instruction::Equal => CompareOp::Equal,
+                        Instruction::NotEqual => CompareOp::NotEqual,
+                        Instruction::StrictEqual => CompareOp::StrictEqual,
+                        Instruction::StrictNotEqual => CompareOp::StrictNotEqual,
+                        Instruction::Less => CompareOp::Less,
+                        Instruction::LessEqual => CompareOp::LessEqual,
+                        Instruction::Greater => CompareOp::Greater,
+                        _ => CompareOp::GreaterEqual,
+                    };
+                    if let (Some(right), Some(left)) = (self.value_stack.pop(), self.value_stack.pop()) {
+                        let node_id = ir.add_compare(op, left, right);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
+                Instruction::Pop => {
+                    self.value_stack.pop();
+                }
+                
+                Instruction::Dup => {
+                    if let Some(&top) = self.value_stack.last() {
+                        self.value_stack.push(top);
+                    }
+                }
+                
+                Instruction::Dup2 => {
+                    let split = self.value_stack.len().saturating_sub(2);
+                    let top = self.value_stack[split..].to_vec();
+                    self.value_stack.extend(top);
+                }
+                
+                Instruction::MoveUnder(depth) => {
+                    if let Some(value) = self.value_stack.pop() {
+                        let index = self.value_stack.len().saturating_sub(*depth);
+                        self.value_stack.insert(index, value);
+                    }
+                }
+                
+                Instruction::ToNumber => {
+                    // Operands are speculated to be numbers already (see LoadLocal),
+                    // so the conversion is the identity on the IR value
+                }
+                
+                Instruction::Negate => {
+                    if let Some(value) = self.value_stack.pop() {
+                        let node_id = ir.add_negate(value);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
+                Instruction::Not => {
+                    if let Some(value) = self.value_stack.pop() {
+                        let node_id = ir.add_not(value);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
                 Instruction::Call(arg_count) => {
                     // Pop arguments
                     let mut args = Vec::new();
@@ -99,17 +278,64 @@ impl TurboFan {
                     }
                 }
                 
+                Instruction::CallMethod(arg_count) => {
+                    let split = self.value_stack.len().saturating_sub(*arg_count);
+                    let args = self.value_stack.split_off(split);
+                    if let (Some(key), Some(object)) = (self.value_stack.pop(), self.value_stack.pop()) {
+                        let node_id = ir.add_call_method(object, key, args);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
+                Instruction::CallWithSpread => {
+                    if let (Some(args), Some(callee)) = (self.value_stack.pop(), self.value_stack.pop()) {
+                        let node_id = ir.add_call_with_spread(callee, args);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
+                Instruction::CallMethodWithSpread => {
+                    if let (Some(args), Some(key), Some(object)) =
+                        (self.value_stack.pop(), self.value_stack.pop(), self.value_stack.pop())
+                    {
+                        let node_id = ir.add_call_method_with_spread(object, key, args);
+                        self.value_stack.push(node_id);
+                    }
+                }
+                
                 Instruction::Return => {
                     if let Some(value) = self.value_stack.pop() {
                         ir.add_return(value);
                     }
                 }
                 
-                Instruction::Jump(_) | Instruction::JumpIfFalse(_) => {
+                Instruction::Jump(_) => {
                     // Control flow is simplified in IR for now
                     // Full implementation would handle basic blocks
                 }
                 
+                Instruction::JumpIfFalse(_) => {
+                    // The condition is consumed by the branch
+                    if let Some(cond) = self.value_stack.pop() {
+                        ir.add_branch(cond);
+                    }
+                }
+                
+                Instruction::JumpIfTrueOrPop(offset)
+                | Instruction::JumpIfFalseOrPop(offset)
+                | Instruction::JumpIfNotNullishOrPop(offset) => {
+                    // The right operand is lowered next; both sides meet at the jump target
+                    let op = match instruction {
+                        Instruction::JumpIfFalseOrPop(_) => ShortCircuitOp::And,
+                        Instruction::JumpIfTrueOrPop(_) => ShortCircuitOp::Or,
+                        _ => ShortCircuitOp::Nullish,
+                    };
+                    if let Some(left) = self.value_stack.pop() {
+                        let target = ((pc as isize) + 1 + offset) as usize;
+                        pending.push((target, op, left));
+                    }
+                }
+                
                 Instruction::Print => {
                     // Print instruction: pop value (side effect)
                     // For IR purposes, we just pop and push a constant 0 (representing undefined)
@@ -120,10 +346,28 @@ impl TurboFan {
                 }
             }
         }
+        self.merge_short_circuits(&mut ir, &mut pending, bytecode.instructions.len());
         
         ir
     }
     
+    /// Join short-circuit operands whose jumps target `pc` into `ShortCircuit` nodes
+    fn merge_short_circuits(&mut self, ir: &mut IR, pending: &mut Vec<(usize, ShortCircuitOp, NodeId)>, pc: usize) {
+        // Jumps to the same target nest, so the most recent one merges first
+        while let Some(&(target, op, left)) = pending.last() {
+            if target != pc {
+                break;
+            }
+            pending.pop();
+            
+            let node_id = match self.value_stack.pop() {
+                Some(right) => ir.add_short_circuit(op, left, right),
+                None => left,
+            };
+            self.value_stack.push(node_id);
+        }
+    }
+    
     /// Compile bytecode to optimized IR
     pub fn compile(&mut self, bytecode: &BytecodeChunk, _func_id: FunctionId) -> IR {
         // Lower to IR
@@ -189,6 +433,13 @@ impl TurboFan {
                         }
                     }
                     
+                    IRNode::Negate { value, id } => {
+                        if let Some(IRNode::Constant { value: v, .. }) = ir.get_node(value) {
+                            ir.nodes[i] = IRNode::Constant { value: -v, id };
+                            changed = true;
+                        }
+                    }
+                    
                     IRNode::Div { left, right, id } => {
                         if let (Some(IRNode::Constant { value: l, .. }), 
                                 Some(IRNode::Constant { value: r, .. })) = 
@@ -263,7 +514,7 @@ impl TurboFan {
             let node = ir.nodes[i].clone();
             
             match node {
-                IRNode::Add { left, right, id } => {
+                IRNode::Add { left, right, .. } => {
                     // Check if operands have type guards
                     let left_is_number = self.has_number_guard(ir, left);
                     let right_is_number = self.has_number_guard(ir, right);
@@ -291,10 +542,8 @@ impl TurboFan {
     /// Check if a value has a Number type guard
     fn has_number_guard(&self, ir: &IR, value_id: NodeId) -> bool {
         // Check if the value is directly a TypeGuard with Number type
-        if let Some(node) = ir.get_node(value_id) {
-            if let IRNode::TypeGuard { expected_type: Type::Number, .. } = node {
-                return true;
-            }
+        if let Some(IRNode::TypeGuard { expected_type: Type::Number, .. }) = ir.get_node(value_id) {
+            return true;
         }
         
         // Check if any TypeGuard node guards this value
@@ -337,7 +586,7 @@ mod tests {
         
         let ir = tf.lower_to_ir(&chunk);
         
-        assert!(ir.nodes.len() > 0);
+        assert!(!ir.nodes.is_empty());
         assert!(matches!(ir.nodes[0], IRNode::Constant { value: 42.0, .. }));
     }
     
@@ -442,6 +691,86 @@ mod tests {
         assert!(load_count >= 1, "Should have at least one LoadLocal");
     }
     
+    #[test]
+    fn test_lower_comparison_and_branch() {
+        let mut tf = TurboFan::new();
+        let mut chunk = BytecodeChunk::new();
+        
+        // if (i < 10) ...
+        chunk.set_local_count(1);
+        let idx = chunk.add_constant(Value::Number(10.0));
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::LoadConst(idx));
+        chunk.emit(Instruction::Less);
+        chunk.emit(Instruction::JumpIfFalse(1));
+        
+        let ir = tf.lower_to_ir(&chunk);
+        
+        let cmp = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::Compare { op: CompareOp::Less, .. }))
+            .expect("Should lower Less to a Compare node");
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::Branch { cond, .. } if *cond == cmp.id())));
+        assert!(tf.value_stack.is_empty());
+    }
+    
+    #[test]
+    fn test_lower_short_circuit() {
+        let mut tf = TurboFan::new();
+        let mut chunk = BytecodeChunk::new();
+        
+        // a || (b && c)
+        chunk.set_local_count(3);
+        chunk.emit(Instruction::LoadLocal(0));
+        chunk.emit(Instruction::JumpIfTrueOrPop(3));
+        chunk.emit(Instruction::LoadLocal(1));
+        chunk.emit(Instruction::JumpIfFalseOrPop(1));
+        chunk.emit(Instruction::LoadLocal(2));
+        
+        let ir = tf.lower_to_ir(&chunk);
+        
+        let and = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::ShortCircuit { op: ShortCircuitOp::And, .. }))
+            .expect("Should merge && at its jump target");
+        let or = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::ShortCircuit { op: ShortCircuitOp::Or, .. }))
+            .expect("Should merge || at its jump target");
+        assert!(matches!(or, IRNode::ShortCircuit { right, .. } if *right == and.id()));
+        assert_eq!(tf.value_stack, vec![or.id()]);
+    }
+    
+    #[test]
+    fn test_lower_upvalues() {
+        let mut tf = TurboFan::new();
+        let mut chunk = BytecodeChunk::new();
+        
+        chunk.emit(Instruction::LoadUpvalue(0));
+        chunk.emit(Instruction::StoreUpvalue(1));
+        
+        let ir = tf.lower_to_ir(&chunk);
+        
+        let load = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::LoadUpvalue { index: 0, .. }))
+            .expect("Should lower LoadUpvalue");
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::StoreUpvalue { index: 1, value, .. } if *value == load.id())));
+        assert!(!ir.nodes.iter().any(|n| matches!(n, IRNode::TypeGuard { .. })));
+    }
+    
+    #[test]
+    fn test_fold_negated_constant() {
+        let mut tf = TurboFan::new();
+        let mut chunk = BytecodeChunk::new();
+        
+        let idx = chunk.add_constant(Value::Number(4.0));
+        chunk.emit(Instruction::LoadConst(idx));
+        chunk.emit(Instruction::Negate);
+        chunk.emit(Instruction::Not);
+        
+        let ir = tf.compile(&chunk, 0);
+        
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::Constant { value, .. } if *value == -4.0)));
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::Not { .. })));
+    }
+    
     #[test]
     fn test_type_specialization() {
         let mut tf = TurboFan::new();
@@ -463,24 +792,52 @@ mod tests {
         assert!(guard_count >= 2, "Should have type guards for both loads");
     }
     
+    #[test]
+    fn test_lower_object_properties() {
+        let mut tf = TurboFan::new();
+        let mut chunk = BytecodeChunk::new();
+        
+        // ({ a: 1 }).a = 2
+        let key = chunk.add_constant(Value::String("a".into()));
+        let one = chunk.add_constant(Value::Number(1.0));
+        let two = chunk.add_constant(Value::Number(2.0));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::LoadConst(one));
+        chunk.emit(Instruction::CreateObject(1));
+        chunk.emit(Instruction::LoadConst(key));
+        chunk.emit(Instruction::LoadConst(two));
+        chunk.emit(Instruction::SetProperty);
+        chunk.emit(Instruction::Return);
+        
+        let ir = tf.lower_to_ir(&chunk);
+        
+        let object = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::CreateObject { entries, .. } if entries.len() == 2))
+            .expect("Should lower CreateObject");
+        assert!(matches!(ir.nodes[0], IRNode::HeapConstant { index: 0, .. }));
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::SetProperty { object: o, .. } if *o == object.id())));
+        // The assignment's value is what gets returned
+        assert!(matches!(ir.nodes.last(), Some(IRNode::Return { value: 4, .. })));
+    }
+    
     #[test]
     fn test_function_inlining_candidates() {
         let mut tf = TurboFan::new();
         let mut chunk = BytecodeChunk::new();
         
         // Small function call with 1 argument
-        let idx1 = chunk.add_constant(Value::Function(1));
         let idx2 = chunk.add_constant(Value::Number(42.0));
         
-        chunk.emit(Instruction::LoadConst(idx1)); // callee
+        chunk.emit(Instruction::MakeClosure(1)); // callee
         chunk.emit(Instruction::LoadConst(idx2)); // arg
         chunk.emit(Instruction::Call(1));
         
-        let _ir = tf.lower_to_ir(&chunk);
+        let ir = tf.lower_to_ir(&chunk);
         
-        // The lowering should process the Call instruction
-        // Even if it doesn't create a Call node in IR, the test passes
-        // as long as it doesn't panic
-        assert!(true);
+        // The callee is a closure node feeding the call
+        let closure = ir.nodes.iter()
+            .find(|n| matches!(n, IRNode::MakeClosure { func_id: 1, .. }))
+            .expect("Should lower MakeClosure");
+        assert!(ir.nodes.iter().any(|n| matches!(n, IRNode::Call { callee, .. } if *callee == closure.id())));
     }
 }
@@ -1,15 +1,33 @@
 // Core data types for V8-RS
 
+use crate::error::RuntimeError;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::rc::Rc;
 
 /// Represents a JavaScript value in the engine
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum Value {
     /// Numeric value (f64)
     Number(f64),
-    /// Function reference by ID
-    Function(FunctionId),
+    /// Immutable string value, shared between copies
+    String(Rc<str>),
+    /// Boolean value
+    Boolean(bool),
+    /// Function value: compiled code plus its closure environment
+    Function(Rc<Closure>),
+    /// Heap-allocated object, shared between copies
+    Object(ObjectRef),
+    /// Heap-allocated array, shared between copies
+    Array(ArrayRef),
+    /// The `null` value
+    Null,
+    /// Marker held by a `let`/`const` binding until its declaration runs; never visible to scripts
+    Hole,
     /// Undefined value
+    #[default]
     Undefined,
 }
 
@@ -21,20 +39,596 @@ impl fmt::Display for Value {
                 if n.fract() == 0.0 && n.is_finite() {
                     // Integer-like numbers: print without decimal
                     write!(f, "{}", *n as i64)
+                } else if n.is_infinite() {
+                    write!(f, "{}", if *n > 0.0 { "Infinity" } else { "-Infinity" })
                 } else {
                     // Floating point numbers: print with decimals
                     write!(f, "{}", n)
                 }
             }
-            Value::Function(id) => write!(f, "[Function: {}]", id),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Function(closure) => write!(f, "[Function: {}]", closure.func_id),
+            Value::Object(obj) => fmt_object(obj, f, &mut Vec::new()),
+            Value::Array(arr) => fmt_array(arr, f, &mut Vec::new()),
+            Value::Null => write!(f, "null"),
+            Value::Hole => write!(f, "<hole>"),
             Value::Undefined => write!(f, "undefined"),
         }
     }
 }
 
+impl Value {
+    /// Name of the value's type, as reported by `typeof`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "boolean",
+            Value::Function(_) => "function",
+            Value::Object(_) | Value::Array(_) | Value::Null => "object",
+            Value::Hole | Value::Undefined => "undefined",
+        }
+    }
+    
+    /// JavaScript truthiness (ToBoolean)
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Number(n) => *n != 0.0 && !n.is_nan(),
+            Value::String(s) => !s.is_empty(),
+            Value::Boolean(b) => *b,
+            Value::Function(_) | Value::Object(_) | Value::Array(_) => true,
+            Value::Null | Value::Hole | Value::Undefined => false,
+        }
+    }
+    
+    /// JavaScript numeric conversion (ToNumber)
+    pub fn to_number(&self) -> f64 {
+        match self {
+            Value::Number(n) => *n,
+            Value::String(s) => string_to_number(s),
+            Value::Boolean(b) => if *b { 1.0 } else { 0.0 },
+            Value::Null => 0.0,
+            // `[]` is 0 and `[7]` is 7, by way of the array's string form
+            // An array too long to convert has commas in it, so is not a number either
+            Value::Array(_) => self.to_js_string().map_or(f64::NAN, |s| string_to_number(&s)),
+            Value::Function(_) | Value::Object(_) | Value::Hole | Value::Undefined => f64::NAN,
+        }
+    }
+    
+    /// JavaScript string conversion (ToString), as used by `+` concatenation.
+    /// Fails with a RangeError if the string would be longer than `MAX_STRING_LENGTH`.
+    pub fn to_js_string(&self) -> Result<String, RuntimeError> {
+        let string = self.js_string(&mut Vec::new())?;
+        check_string_length(string.len())?;
+        Ok(string)
+    }
+    
+    fn js_string(&self, seen: &mut Vec<*const ()>) -> Result<String, RuntimeError> {
+        Ok(match self {
+            Value::Object(obj) => {
+                // Errors read as `name: message`, like `Error.prototype.toString`
+                if !obj.borrow().is_error() {
+                    return Ok("[object Object]".to_string());
+                }
+                let ptr = obj.as_ptr() as *const ();
+                if seen.contains(&ptr) {
+                    return Ok(String::new());
+                }
+                seen.push(ptr);
+                let obj = obj.borrow();
+                let part = |key: &str, default: &str, seen: &mut Vec<*const ()>| match obj.get(key) {
+                    None | Some(Value::Undefined) => Ok(default.to_string()),
+                    Some(value) => value.js_string(seen),
+                };
+                let name = part("name", "Error", seen)?;
+                let message = part("message", "", seen)?;
+                seen.pop();
+                match (name.is_empty(), message.is_empty()) {
+                    (_, true) => name,
+                    (true, false) => message,
+                    (false, false) => format!("{}: {}", name, message),
+                }
+            }
+            Value::Array(arr) => {
+                // A cyclic reference joins as the empty string, like `Array.prototype.join`
+                let ptr = arr.as_ptr() as *const ();
+                if seen.contains(&ptr) {
+                    return Ok(String::new());
+                }
+                seen.push(ptr);
+                let arr = arr.borrow();
+                
+                // Only stored elements are visited; the commas for the holes
+                // between them are written in one go
+                let separators = arr.len().saturating_sub(1);
+                check_string_length(separators)?;
+                let mut joined = String::new();
+                let mut written = 0;
+                for (index, value) in arr.entries() {
+                    joined.extend(std::iter::repeat_n(',', index - written));
+                    written = index;
+                    if !value.is_nullish() {
+                        joined.push_str(&value.js_string(seen)?);
+                        check_string_length(joined.len())?;
+                    }
+                }
+                joined.extend(std::iter::repeat_n(',', separators.saturating_sub(written)));
+                check_string_length(joined.len())?;
+                seen.pop();
+                joined
+            }
+            other => other.to_string(),
+        })
+    }
+    
+    /// Convert the value to a property key (ToPropertyKey)
+    pub fn to_property_key(&self) -> Result<Rc<str>, RuntimeError> {
+        match self {
+            Value::String(s) => Ok(s.clone()),
+            other => Ok(other.to_js_string()?.into()),
+        }
+    }
+    
+    /// The array index this value denotes as a property key, if any
+    pub fn to_array_index(&self) -> Option<usize> {
+        match self {
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 && *n < u32::MAX as f64 => Some(*n as usize),
+            Value::String(s) => s.parse::<u32>().ok()
+                .filter(|index| index.to_string() == **s && *index < u32::MAX)
+                .map(|index| index as usize),
+            _ => None,
+        }
+    }
+    
+    /// Write the value as it appears inside an object or array, with strings quoted
+    fn fmt_nested(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "'{}'", s),
+            Value::Object(obj) => fmt_object(obj, f, seen),
+            Value::Array(arr) => fmt_array(arr, f, seen),
+            other => write!(f, "{}", other),
+        }
+    }
+    
+    /// Whether the value is `null` or `undefined`
+    pub fn is_nullish(&self) -> bool {
+        matches!(self, Value::Null | Value::Undefined)
+    }
+    
+    /// Strict equality (`===`)
+    pub fn strict_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            // NaN is never equal to itself, and +0 equals -0
+            (Value::Number(a), Value::Number(b)) => a == b,
+            // Heap values are equal only if they are the same value; comparing
+            // the references never borrows, so it is safe while one is in use
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            (Value::Array(a), Value::Array(b)) => Rc::ptr_eq(a, b),
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            _ => self == other,
+        }
+    }
+    
+    /// Loose equality (`==`) with the abstract equality type coercions
+    pub fn loose_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            // null and undefined only equal each other
+            (a, b) if a.is_nullish() || b.is_nullish() => a.is_nullish() && b.is_nullish(),
+            (Value::Number(_), Value::String(_)) | (Value::String(_), Value::Number(_)) => {
+                self.to_number() == other.to_number()
+            }
+            (Value::Boolean(_), _) => Value::Number(self.to_number()).loose_equals(other),
+            (_, Value::Boolean(_)) => self.loose_equals(&Value::Number(other.to_number())),
+            _ => self.strict_equals(other),
+        }
+    }
+    
+    /// Abstract relational comparison used by `<`, `<=`, `>` and `>=`.
+    ///
+    /// Two strings compare by UTF-16 code units; anything else compares
+    /// numerically. Returns `None` when either side is NaN.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => Some(a.encode_utf16().cmp(b.encode_utf16())),
+            _ => self.to_number().partial_cmp(&other.to_number()),
+        }
+    }
+}
+
+/// Write an object like Node's `util.inspect`, e.g. `{ a: 1, 'b c': 'x' }`
+fn fmt_object(obj: &ObjectRef, f: &mut fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> fmt::Result {
+    let ptr = obj.as_ptr() as *const ();
+    if seen.contains(&ptr) {
+        return write!(f, "[Circular]");
+    }
+    
+    let obj = obj.borrow();
+    if obj.properties.is_empty() {
+        return write!(f, "{{}}");
+    }
+    
+    seen.push(ptr);
+    write!(f, "{{ ")?;
+    for (i, (key, value)) in obj.properties.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        let is_identifier = key.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if is_identifier {
+            write!(f, "{}: ", key)?;
+        } else {
+            write!(f, "'{}': ", key)?;
+        }
+        value.fmt_nested(f, seen)?;
+    }
+    seen.pop();
+    write!(f, " }}")
+}
+
+/// Write an array like Node's `util.inspect`, e.g. `[ 1, 'a', [] ]`
+fn fmt_array(arr: &ArrayRef, f: &mut fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> fmt::Result {
+    let ptr = arr.as_ptr() as *const ();
+    if seen.contains(&ptr) {
+        return write!(f, "[Circular]");
+    }
+    
+    let arr = arr.borrow();
+    if arr.is_empty() {
+        return write!(f, "[]");
+    }
+    
+    // Runs of holes print as `<N empty items>`, like Node
+    let holes = |f: &mut fmt::Formatter<'_>, count: usize| match count {
+        1 => write!(f, "<1 empty item>"),
+        n => write!(f, "<{} empty items>", n),
+    };
+    seen.push(ptr);
+    write!(f, "[ ")?;
+    let mut next = 0;
+    for (index, value) in arr.entries() {
+        if next > 0 {
+            write!(f, ", ")?;
+        }
+        if index > next {
+            holes(f, index - next)?;
+            write!(f, ", ")?;
+        }
+        value.fmt_nested(f, seen)?;
+        next = index + 1;
+    }
+    if arr.len() > next {
+        if next > 0 {
+            write!(f, ", ")?;
+        }
+        holes(f, arr.len() - next)?;
+    }
+    seen.pop();
+    write!(f, " ]")
+}
+
+/// Longest string, in bytes, the engine builds; about V8's limit
+pub const MAX_STRING_LENGTH: usize = (1 << 29) - 24;
+
+/// Fail with a RangeError if a string of `len` bytes would be too long
+pub fn check_string_length(len: usize) -> Result<(), RuntimeError> {
+    if len > MAX_STRING_LENGTH {
+        return Err(RuntimeError::InvalidStringLength { span: None });
+    }
+    Ok(())
+}
+
+/// Convert a string to a number following the StringToNumber grammar
+fn string_to_number(s: &str) -> f64 {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return 0.0;
+    }
+    
+    let radix_prefixed = |prefix: [&str; 2], radix: u32| {
+        prefix.iter()
+            .find_map(|p| trimmed.strip_prefix(p))
+            .map(|digits| match u64::from_str_radix(digits, radix) {
+                Ok(n) => n as f64,
+                Err(_) => f64::NAN,
+            })
+    };
+    if let Some(n) = radix_prefixed(["0x", "0X"], 16)
+        .or_else(|| radix_prefixed(["0o", "0O"], 8))
+        .or_else(|| radix_prefixed(["0b", "0B"], 2))
+    {
+        return n;
+    }
+    
+    match trimmed {
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        // Rust accepts spellings such as "inf" and "NaN" that JavaScript does not
+        _ if trimmed.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) => {
+            trimmed.parse().unwrap_or(f64::NAN)
+        }
+        _ => f64::NAN,
+    }
+}
+
 /// Function identifier type
 pub type FunctionId = usize;
 
+/// Shared, mutable reference to a heap object
+pub type ObjectRef = Rc<RefCell<JsObject>>;
+
+/// A plain object: string-keyed properties in insertion order
+#[derive(Default)]
+pub struct JsObject {
+    properties: Vec<(Rc<str>, Value)>,
+    /// Whether the engine created this object for an error it raised
+    is_error: bool,
+}
+
+impl JsObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create the object a catch clause receives for an engine error
+    pub fn error(name: &str, message: &str) -> Self {
+        let mut object = Self { is_error: true, ..Self::default() };
+        object.set("name".into(), Value::String(name.into()));
+        object.set("message".into(), Value::String(message.into()));
+        object
+    }
+    
+    /// Whether the engine created this object for an error it raised
+    pub fn is_error(&self) -> bool {
+        self.is_error
+    }
+    
+    /// Get an own property
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.properties.iter()
+            .find(|(k, _)| &**k == key)
+            .map(|(_, value)| value)
+    }
+    
+    /// Set a property, adding it after the existing ones if it is new
+    pub fn set(&mut self, key: Rc<str>, value: Value) {
+        match self.properties.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, slot)) => *slot = value,
+            None => self.properties.push((key, value)),
+        }
+    }
+    
+    /// Property keys in insertion order
+    pub fn keys(&self) -> impl Iterator<Item = &Rc<str>> {
+        self.properties.iter().map(|(key, _)| key)
+    }
+    
+    /// Property values in insertion order
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.properties.iter().map(|(_, value)| value)
+    }
+    
+    /// Remove all properties
+    pub fn clear(&mut self) {
+        self.properties.clear();
+    }
+    
+    /// Number of properties
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+    
+    /// Whether the object has no properties
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+}
+
+impl fmt::Debug for JsObject {
+    // Property values may refer back to this object, so only keys are printed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys()).finish()
+    }
+}
+
+impl PartialEq for JsObject {
+    /// Objects are equal only if they are the same object
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// Shared, mutable reference to a heap array
+pub type ArrayRef = Rc<RefCell<JsArray>>;
+
+/// Largest run of missing elements that a write past the dense part fills with `undefined`
+const MAX_DENSE_GAP: usize = 1024;
+
+/// An array: a dense run of elements from index 0, followed by elements far
+/// beyond it kept sparsely, so that `a[1e9] = 1` does not allocate a billion
+/// slots. Indices below `length` holding no element are holes, read as `undefined`.
+#[derive(Default)]
+pub struct JsArray {
+    elements: Vec<Value>,
+    /// Elements past the dense part, all at indices above `elements.len()`
+    sparse: BTreeMap<usize, Value>,
+    length: usize,
+    /// Properties other than indices and `length`, such as a template's `raw`
+    properties: JsObject,
+}
+
+impl JsArray {
+    pub fn new(elements: Vec<Value>) -> Self {
+        Self {
+            length: elements.len(),
+            elements,
+            sparse: BTreeMap::new(),
+            properties: JsObject::new(),
+        }
+    }
+    
+    /// Get a named (non-index) property
+    pub fn get_named(&self, key: &str) -> Option<&Value> {
+        self.properties.get(key)
+    }
+    
+    /// Set a named (non-index) property
+    pub fn set_named(&mut self, key: Rc<str>, value: Value) {
+        self.properties.set(key, value);
+    }
+    
+    /// Values of the named properties
+    pub fn named_values(&self) -> impl Iterator<Item = &Value> {
+        self.properties.values()
+    }
+    
+    /// Drop all elements and named properties
+    pub fn clear(&mut self) {
+        self.elements.clear();
+        self.sparse.clear();
+        self.length = 0;
+        self.properties.clear();
+    }
+    
+    /// Get the element at `index`, or `None` for a hole or an index past the end
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.elements.get(index).or_else(|| self.sparse.get(&index))
+    }
+    
+    /// Set the element at `index`, growing the array if needed
+    pub fn set(&mut self, index: usize, value: Value) {
+        if index < self.elements.len() {
+            self.elements[index] = value;
+        } else if index - self.elements.len() <= MAX_DENSE_GAP {
+            self.sparse.remove(&index);
+            self.elements.resize(index, Value::Undefined);
+            self.elements.push(value);
+            
+            // Sparse elements that the dense part has reached move into it
+            while let Some(entry) = self.sparse.first_entry() {
+                let at = *entry.key();
+                if at > self.elements.len() {
+                    break;
+                }
+                let value = entry.remove();
+                if at < self.elements.len() {
+                    self.elements[at] = value;
+                } else {
+                    self.elements.push(value);
+                }
+            }
+        } else {
+            self.sparse.insert(index, value);
+        }
+        self.length = self.length.max(index + 1);
+    }
+    
+    /// Truncate the array to `len` elements, or extend it with holes
+    pub fn set_len(&mut self, len: usize) {
+        self.elements.truncate(len);
+        self.sparse.split_off(&len);
+        self.length = len;
+    }
+    
+    /// Append an element, returning the new length
+    pub fn push(&mut self, value: Value) -> usize {
+        self.set(self.length, value);
+        self.length
+    }
+    
+    /// Remove and return the last element
+    pub fn pop(&mut self) -> Option<Value> {
+        if self.length == 0 {
+            return None;
+        }
+        self.length -= 1;
+        if self.length < self.elements.len() {
+            self.elements.pop()
+        } else {
+            Some(self.sparse.remove(&self.length).unwrap_or_default())
+        }
+    }
+    
+    /// The stored elements with their indices, in order, skipping holes
+    pub fn entries(&self) -> impl Iterator<Item = (usize, &Value)> {
+        self.elements.iter()
+            .enumerate()
+            .chain(self.sparse.iter().map(|(&index, value)| (index, value)))
+    }
+    
+    /// The elements from `start` up to `end`, keeping holes as holes
+    pub fn slice(&self, start: usize, end: usize) -> JsArray {
+        let mut sliced = JsArray::default();
+        for (index, value) in self.entries().filter(|&(index, _)| index >= start && index < end) {
+            sliced.set(index - start, value.clone());
+        }
+        sliced.set_len(end.saturating_sub(start));
+        sliced
+    }
+    
+    /// Number of elements, including holes
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+    
+    /// Number of elements actually stored, not counting holes
+    pub fn stored_len(&self) -> usize {
+        self.elements.len() + self.sparse.len()
+    }
+}
+
+impl fmt::Debug for JsArray {
+    // Elements may refer back to this array, so only the length is printed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JsArray(len: {})", self.length)
+    }
+}
+
+impl PartialEq for JsArray {
+    /// Arrays are equal only if they are the same array
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// Heap cell holding a variable shared between a function and its closures
+pub type Upvalue = Rc<RefCell<Value>>;
+
+/// A function together with the cells it captured when it was created
+pub struct Closure {
+    pub func_id: FunctionId,
+    pub upvalues: Vec<Upvalue>,
+}
+
+impl Closure {
+    pub fn new(func_id: FunctionId, upvalues: Vec<Upvalue>) -> Self {
+        Self { func_id, upvalues }
+    }
+}
+
+impl fmt::Debug for Closure {
+    // Captured cells may refer back to this closure, so they are not printed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Closure")
+            .field("func_id", &self.func_id)
+            .field("upvalues", &self.upvalues.len())
+            .finish()
+    }
+}
+
+impl PartialEq for Closure {
+    /// Functions are equal only if they are the same object
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
 /// Represents a source code location span
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Span {
@@ -64,12 +658,6 @@ impl Span {
     }
 }
 
-impl Default for Value {
-    fn default() -> Self {
-        Value::Undefined
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,13 +667,179 @@ mod tests {
         let num = Value::Number(42.0);
         assert_eq!(num, Value::Number(42.0));
 
-        let func = Value::Function(0);
-        assert_eq!(func, Value::Function(0));
+        // Functions compare by identity, not by ID
+        let func = Value::Function(Rc::new(Closure::new(0, Vec::new())));
+        assert_eq!(func, func.clone());
+        assert_ne!(func, Value::Function(Rc::new(Closure::new(0, Vec::new()))));
 
         let undef = Value::Undefined;
         assert_eq!(undef, Value::Undefined);
+        
+        let s = Value::String("hi".into());
+        assert_eq!(s, Value::String("hi".into()));
+    }
+    
+    #[test]
+    fn test_value_display() {
+        assert_eq!(Value::Number(42.0).to_string(), "42");
+        assert_eq!(Value::Number(1.5).to_string(), "1.5");
+        assert_eq!(Value::String("a b".into()).to_string(), "a b");
+        assert_eq!(Value::Undefined.to_string(), "undefined");
+    }
+    
+    #[test]
+    fn test_object_display() {
+        let obj: ObjectRef = Default::default();
+        assert_eq!(Value::Object(obj.clone()).to_string(), "{}");
+        
+        obj.borrow_mut().set("a".into(), Value::Number(1.0));
+        obj.borrow_mut().set("b c".into(), Value::String("x".into()));
+        assert_eq!(Value::Object(obj.clone()).to_string(), "{ a: 1, 'b c': 'x' }");
+        
+        // Overwriting keeps the original position
+        obj.borrow_mut().set("a".into(), Value::Null);
+        obj.borrow_mut().set("self".into(), Value::Object(obj.clone()));
+        assert_eq!(Value::Object(obj.clone()).to_string(), "{ a: null, 'b c': 'x', self: [Circular] }");
+        assert_eq!(Value::Object(obj.clone()).to_js_string().unwrap(), "[object Object]");
+        assert_eq!(obj.borrow().len(), 3);
+        
+        // Break the cycle so the test does not leak
+        obj.borrow_mut().set("self".into(), Value::Undefined);
+    }
+    
+    #[test]
+    fn test_error_object_string() {
+        let err = Rc::new(RefCell::new(JsObject::error("TypeError", "bad")));
+        assert_eq!(Value::Object(err.clone()).to_js_string().unwrap(), "TypeError: bad");
+        
+        err.borrow_mut().set("message".into(), Value::String("".into()));
+        assert_eq!(Value::Object(err.clone()).to_js_string().unwrap(), "TypeError");
+        err.borrow_mut().set("name".into(), Value::Undefined);
+        assert_eq!(Value::Object(err.clone()).to_js_string().unwrap(), "Error");
+    }
+    
+    #[test]
+    fn test_array_display_and_conversion() {
+        let arr: ArrayRef = Default::default();
+        let value = Value::Array(arr.clone());
+        assert_eq!(value.to_string(), "[]");
+        assert_eq!(value.to_number(), 0.0);
+        
+        arr.borrow_mut().push(Value::Number(7.0));
+        assert_eq!(value.to_number(), 7.0);
+        
+        arr.borrow_mut().set(3, Value::String("x".into()));
+        assert_eq!(value.to_string(), "[ 7, undefined, undefined, 'x' ]");
+        assert_eq!(value.to_js_string().unwrap(), "7,,,x");
+        assert!(value.to_number().is_nan());
+        
+        arr.borrow_mut().set_len(1);
+        assert_eq!(arr.borrow().len(), 1);
+        assert_eq!(Value::String("2".into()).to_array_index(), Some(2));
+        assert_eq!(Value::String("02".into()).to_array_index(), None);
+        assert_eq!(Value::Number(1.5).to_array_index(), None);
+    }
+
+    #[test]
+    fn test_sparse_array() {
+        // Far-off indices are stored sparsely instead of filling the gap
+        let mut arr = JsArray::new(vec![Value::Number(1.0)]);
+        arr.set(1_000_000_000, Value::Number(2.0));
+        assert_eq!(arr.len(), 1_000_000_001);
+        assert_eq!(arr.stored_len(), 2);
+        assert_eq!(arr.get(500), None);
+        assert_eq!(arr.get(1_000_000_000), Some(&Value::Number(2.0)));
+        
+        // Small gaps are filled; growing the dense part up to a sparse element takes it in
+        arr.set(2000, Value::Number(4.0));
+        arr.set(3000, Value::Number(3.0));
+        arr.set(1000, Value::Number(5.0));
+        assert_eq!(arr.stored_len(), 1004);
+        arr.set(1999, Value::Number(6.0));
+        assert_eq!(arr.stored_len(), 2003);
+        assert_eq!(arr.entries().skip(2001).map(|(index, _)| index).collect::<Vec<_>>(), [3000, 1_000_000_000]);
+        assert_eq!(arr.get(2000), Some(&Value::Number(4.0)));
+        assert_eq!(arr.get(1500), Some(&Value::Undefined));
+        
+        assert_eq!(arr.pop(), Some(Value::Number(2.0)));
+        assert_eq!(arr.len(), 1_000_000_000);
+        arr.set_len(2001);
+        assert_eq!(arr.stored_len(), 2001);
+        assert_eq!(arr.push(Value::Null), 2002);
+        
+        let sliced = arr.slice(1999, 2002);
+        assert_eq!(sliced.len(), 3);
+        assert_eq!(sliced.entries().map(|(_, value)| value.clone()).collect::<Vec<_>>(), [Value::Number(6.0), Value::Number(4.0), Value::Null]);
+        
+        let mut holes = JsArray::default();
+        holes.set_len(3);
+        holes.set(100_000, Value::Number(1.0));
+        assert_eq!(Value::Array(Rc::new(RefCell::new(holes))).to_string(), "[ <100000 empty items>, 1 ]");
+    }
+
+    #[test]
+    fn test_truthiness() {
+        assert!(Value::Number(1.0).is_truthy());
+        assert!(!Value::Number(0.0).is_truthy());
+        assert!(!Value::Number(f64::NAN).is_truthy());
+        assert!(Value::String("0".into()).is_truthy());
+        assert!(!Value::String("".into()).is_truthy());
+        assert!(!Value::Boolean(false).is_truthy());
+        assert!(!Value::Undefined.is_truthy());
+    }
+    
+    #[test]
+    fn test_to_number() {
+        assert_eq!(Value::String(" 42 ".into()).to_number(), 42.0);
+        assert_eq!(Value::String("".into()).to_number(), 0.0);
+        assert_eq!(Value::String("0x1F".into()).to_number(), 31.0);
+        assert_eq!(Value::String("1e3".into()).to_number(), 1000.0);
+        assert!(Value::String("inf".into()).to_number().is_nan());
+        assert!(Value::String("12px".into()).to_number().is_nan());
+        assert_eq!(Value::Boolean(true).to_number(), 1.0);
+        assert!(Value::Undefined.to_number().is_nan());
+    }
+    
+    #[test]
+    fn test_equality() {
+        let one = Value::Number(1.0);
+        let one_str = Value::String("1".into());
+        
+        assert!(one.loose_equals(&one_str));
+        assert!(!one.strict_equals(&one_str));
+        assert!(one.loose_equals(&Value::Boolean(true)));
+        assert!(!Value::Number(f64::NAN).strict_equals(&Value::Number(f64::NAN)));
+        assert!(Value::Undefined.loose_equals(&Value::Undefined));
+        assert!(!Value::Undefined.loose_equals(&Value::Number(0.0)));
+        assert!(Value::Null.loose_equals(&Value::Undefined));
+        assert!(!Value::Null.strict_equals(&Value::Undefined));
+        assert!(!Value::Null.loose_equals(&Value::Number(0.0)));
+        assert!(!Value::Null.loose_equals(&Value::Boolean(false)));
     }
 
+    #[test]
+    fn test_strict_equality_is_identity() {
+        let arr: ArrayRef = Default::default();
+        let same = Value::Array(arr.clone());
+        assert!(same.strict_equals(&Value::Array(arr.clone())));
+        assert!(!same.strict_equals(&Value::Array(Default::default())));
+        
+        // Comparing does not borrow, so it works while the array is being modified
+        let _guard = arr.borrow_mut();
+        assert!(same.strict_equals(&same.clone()));
+        assert!(same.loose_equals(&same.clone()));
+    }
+    
+    #[test]
+    fn test_compare() {
+        assert_eq!(Value::Number(1.0).compare(&Value::Number(2.0)), Some(Ordering::Less));
+        assert_eq!(Value::String("b".into()).compare(&Value::String("a".into())), Some(Ordering::Greater));
+        // "10" < "9" as strings, but not as numbers
+        assert_eq!(Value::String("10".into()).compare(&Value::String("9".into())), Some(Ordering::Less));
+        assert_eq!(Value::String("10".into()).compare(&Value::Number(9.0)), Some(Ordering::Greater));
+        assert_eq!(Value::Undefined.compare(&Value::Number(0.0)), None);
+    }
+    
     #[test]
     fn test_span_creation() {
         let span = Span::new(0, 10);
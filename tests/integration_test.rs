@@ -1,6 +1,6 @@
 // Integration tests for V8-RS engine
 
-use v8_rs::{Engine, Value};
+use v8_rs::{BytecodeGenerator, CompileError, Engine, Error, Parser, RuntimeError, Scope, SourceMap, Span, StackFrame, Value};
 
 #[test]
 fn test_simple_number() {
@@ -87,6 +87,7 @@ fn test_multiple_operations() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_floating_point() {
     let mut engine = Engine::new();
     let result = engine.execute("3.14 * 2").unwrap();
@@ -106,3 +107,1293 @@ fn test_parentheses_override_precedence() {
     let result = engine.execute("(2 + 3) * 4").unwrap();
     assert_eq!(result, Value::Number(20.0));
 }
+
+#[test]
+fn test_string_literal() {
+    let mut engine = Engine::new();
+    let result = engine.execute("'hello'").unwrap();
+    assert_eq!(result, Value::String("hello".into()));
+}
+
+#[test]
+fn test_string_concatenation() {
+    let mut engine = Engine::new();
+    let result = engine.execute("let name = \"world\"; 'hello, ' + name").unwrap();
+    assert_eq!(result, Value::String("hello, world".into()));
+}
+
+#[test]
+fn test_string_number_concatenation() {
+    let mut engine = Engine::new();
+    let result = engine.execute("1 + 2 + 'px'").unwrap();
+    assert_eq!(result, Value::String("3px".into()));
+    
+    let result = engine.execute("'x' + 1.5").unwrap();
+    assert_eq!(result, Value::String("x1.5".into()));
+}
+
+#[test]
+fn test_comparison_operators() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("1 < 2").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("2 <= 1").unwrap(), Value::Boolean(false));
+    assert_eq!(engine.execute("3 > 2").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("3 >= 3").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("'a' < 'b'").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn test_equality_operators() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("1 == '1'").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("1 === '1'").unwrap(), Value::Boolean(false));
+    assert_eq!(engine.execute("1 != 2").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("true !== false").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("1 + 1 == 2").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn test_if_with_comparison() {
+    let mut engine = Engine::new();
+    
+    // The untaken branch must not run
+    let result = engine.execute("let x = 5; if (x < 3) { 1 / 0 } x").unwrap();
+    assert_eq!(result, Value::Number(5.0));
+    
+    let result = engine.execute("let x = 5; if (x >= 5) { 1 / 0 } x");
+    assert!(result.is_err());
+    
+    let result = engine.execute("let x = 5; if (x === '5') { 1 / 0 } else { x } 'done'").unwrap();
+    assert_eq!(result, Value::String("done".into()));
+}
+
+#[test]
+fn test_function_declaration_and_call() {
+    let mut engine = Engine::new();
+    let result = engine.execute("function square(x) { return x * x; } square(7)").unwrap();
+    assert_eq!(result, Value::Number(49.0));
+}
+
+#[test]
+fn test_recursive_function() {
+    let mut engine = Engine::new();
+    let source = "
+        function fib(n) {
+            if (n < 2) { return n; }
+            return fib(n - 1) + fib(n - 2);
+        }
+        fib(15)
+    ";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(610.0));
+}
+
+#[test]
+fn test_function_reads_script_variables() {
+    let mut engine = Engine::new();
+    let source = "let base = 100; function offset(x) { return base + x; } offset(5)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(105.0));
+}
+
+#[test]
+fn test_function_without_return_yields_undefined() {
+    let mut engine = Engine::new();
+    let result = engine.execute("function noop(a) { a + 1; } noop(1)").unwrap();
+    assert_eq!(result, Value::Undefined);
+}
+
+#[test]
+fn test_missing_arguments_are_undefined() {
+    let mut engine = Engine::new();
+    let result = engine.execute("function second(a, b) { return b; } second(1)").unwrap();
+    assert_eq!(result, Value::Undefined);
+}
+
+#[test]
+fn test_calling_non_function_is_type_error() {
+    let mut engine = Engine::new();
+    let result = engine.execute("let x = 1; x()");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_unbounded_recursion_overflows() {
+    let mut engine = Engine::new();
+    let result = engine.execute("function f(n) { return f(n + 1); } f(0)");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_assignment_expression() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("let x = 1; x = 5; x").unwrap(), Value::Number(5.0));
+    // The assigned value is the expression's result
+    assert_eq!(engine.execute("let y = 1; y = 7").unwrap(), Value::Number(7.0));
+    assert_eq!(engine.execute("let a = 0; let b = 0; a = b = 3; a + b").unwrap(), Value::Number(6.0));
+}
+
+#[test]
+fn test_compound_assignment() {
+    let mut engine = Engine::new();
+    let result = engine.execute("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x").unwrap();
+    assert_eq!(result, Value::Number(6.0));
+    
+    let result = engine.execute("let s = 'a'; s += 'b'; s").unwrap();
+    assert_eq!(result, Value::String("ab".into()));
+}
+
+#[test]
+fn test_increment_decrement() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("let i = 1; i++").unwrap(), Value::Number(1.0));
+    assert_eq!(engine.execute("let i = 1; ++i").unwrap(), Value::Number(2.0));
+    assert_eq!(engine.execute("let i = 1; i--; i").unwrap(), Value::Number(0.0));
+    assert_eq!(engine.execute("let i = 1; --i").unwrap(), Value::Number(0.0));
+    // Updates coerce to number
+    assert_eq!(engine.execute("let s = '5'; s++; s").unwrap(), Value::Number(6.0));
+}
+
+#[test]
+fn test_for_loop_with_assignment() {
+    let mut engine = Engine::new();
+    let source = "let sum = 0; for (let i = 0; i < 10; i = i + 1) { sum += i; } sum";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(45.0));
+    
+    let source = "let n = 0; for (let i = 0; i < 5; i++) { n++; } n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(5.0));
+}
+
+#[test]
+fn test_function_assigns_script_variable() {
+    let mut engine = Engine::new();
+    let source = "let count = 0; function bump() { count += 1; } bump(); bump(); count";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_while_loop() {
+    let mut engine = Engine::new();
+    let source = "let i = 0; let sum = 0; while (i < 5) { sum += i; i++; } sum";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(10.0));
+    
+    let source = "let n = 3; while (n > 0) n--; n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(0.0));
+}
+
+#[test]
+fn test_do_while_runs_body_once() {
+    let mut engine = Engine::new();
+    let source = "let n = 0; do { n++; } while (false); n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(1.0));
+    
+    let source = "let n = 0; do n += 2; while (n < 7); n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(8.0));
+}
+
+#[test]
+fn test_break_and_continue() {
+    let mut engine = Engine::new();
+    let source = "let i = 0; while (true) { if (i == 4) { break; } i++; } i";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(4.0));
+    
+    // Skip the first few iterations; continue still runs the update
+    let source = "let sum = 0; for (let i = 0; i < 6; i++) { if (i < 3) { continue; } sum += i; } sum";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(12.0));
+    
+    let source = "let n = 0; do { n++; if (n < 5) { continue; } break; } while (true); n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(5.0));
+}
+
+#[test]
+fn test_labelled_break_and_continue() {
+    let mut engine = Engine::new();
+    let source = "
+        let count = 0;
+        outer: for (let i = 0; i < 3; i++) {
+            for (let j = 0; j < 3; j++) {
+                if (j == 1) { continue outer; }
+                count++;
+            }
+        }
+        count";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(3.0));
+    
+    let source = "
+        let count = 0;
+        outer: while (true) {
+            while (true) {
+                count++;
+                break outer;
+            }
+        }
+        count";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(1.0));
+    
+    let source = "let x = 1; block: { x = 2; break block; } x";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_illegal_break_is_parse_error() {
+    let mut engine = Engine::new();
+    assert!(engine.execute("break;").is_err());
+    assert!(engine.execute("while (true) { continue missing; }").is_err());
+}
+
+#[test]
+fn test_unary_operators() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("-5 + 2").unwrap(), Value::Number(-3.0));
+    assert_eq!(engine.execute("let x = 4; -x * 2").unwrap(), Value::Number(-8.0));
+    assert_eq!(engine.execute("- -3").unwrap(), Value::Number(3.0));
+    assert_eq!(engine.execute("+'42'").unwrap(), Value::Number(42.0));
+    assert_eq!(engine.execute("-true").unwrap(), Value::Number(-1.0));
+    assert_eq!(engine.execute("!0").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("!!'text'").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("!null").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn test_logical_operators_return_operands() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("1 && 'yes'").unwrap(), Value::String("yes".into()));
+    assert_eq!(engine.execute("0 && 'yes'").unwrap(), Value::Number(0.0));
+    assert_eq!(engine.execute("'' || 'fallback'").unwrap(), Value::String("fallback".into()));
+    assert_eq!(engine.execute("7 || 'fallback'").unwrap(), Value::Number(7.0));
+    assert_eq!(engine.execute("null ?? 5").unwrap(), Value::Number(5.0));
+    assert_eq!(engine.execute("0 ?? 5").unwrap(), Value::Number(0.0));
+    assert!(engine.execute("false || null ?? 1").is_err());
+    assert_eq!(engine.execute("(false || null) ?? 1").unwrap(), Value::Number(1.0));
+}
+
+#[test]
+fn test_logical_operators_short_circuit() {
+    let mut engine = Engine::new();
+    let source = "
+        let calls = 0;
+        function touch() { calls++; return true; }
+        false && touch();
+        true || touch();
+        1 ?? touch();
+        true && touch();
+        calls";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(1.0));
+}
+
+#[test]
+fn test_logical_operators_in_conditions() {
+    let mut engine = Engine::new();
+    let source = "
+        let hits = 0;
+        for (let i = 0; i < 10; i++) {
+            if (i > 2 && i < 6 || i == 9) { hits++; }
+        }
+        hits";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(4.0));
+    
+    let source = "let n = 0; while (!(n >= 3)) { n++; } n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(3.0));
+}
+
+#[test]
+fn test_closure_counter() {
+    let mut engine = Engine::new();
+    let source = "
+        function makeCounter() {
+            let count = 0;
+            function increment() {
+                count++;
+                return count;
+            }
+            return increment;
+        }
+        let a = makeCounter();
+        let b = makeCounter();
+        a(); a(); b();
+        a() * 10 + b()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(32.0));
+}
+
+#[test]
+fn test_closure_captures_parameter() {
+    let mut engine = Engine::new();
+    let source = "
+        function adder(n) {
+            function add(x) { return x + n; }
+            return add;
+        }
+        let addFive = adder(5);
+        addFive(10)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(15.0));
+}
+
+#[test]
+fn test_closures_share_captured_variable() {
+    let mut engine = Engine::new();
+    let source = "
+        let get = 0;
+        let set = 0;
+        function box(value) {
+            function read() { return value; }
+            function write(v) { value = v; }
+            get = read;
+            set = write;
+        }
+        box(1);
+        set(42);
+        get()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(42.0));
+}
+
+#[test]
+fn test_nested_closures_and_recursion() {
+    let mut engine = Engine::new();
+    let source = "
+        function outer(base) {
+            function middle() {
+                function inner(n) {
+                    if (n == 0) { return base; }
+                    return inner(n - 1) + 1;
+                }
+                return inner;
+            }
+            return middle()(3);
+        }
+        outer(10)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(13.0));
+}
+
+#[test]
+fn test_callback_sees_later_assignment() {
+    let mut engine = Engine::new();
+    let source = "
+        function run(callback) { return callback(); }
+        function test() {
+            let message = 'before';
+            function report() { return message; }
+            message = 'after';
+            return run(report);
+        }
+        test()";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("after".into()));
+}
+
+#[test]
+fn test_object_literal_property_access() {
+    let mut engine = Engine::new();
+    let source = "
+        let point = { x: 3, 'y': 4, 10: 'ten' };
+        point.x * point['y'] + point[10].length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(15.0));
+    
+    assert_eq!(engine.execute("let o = { a: 1 }; o.missing").unwrap(), Value::Undefined);
+}
+
+#[test]
+fn test_object_property_assignment() {
+    let mut engine = Engine::new();
+    let source = "
+        let o = {};
+        let key = 'count';
+        o[key] = 1;
+        o.count += 4;
+        o.count++;
+        ++o['count'];
+        o.count";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(7.0));
+    
+    let source = "let o = { n: 5 }; let old = o.n--; old * 10 + o.n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(54.0));
+}
+
+#[test]
+fn test_nested_objects_and_shorthand() {
+    let mut engine = Engine::new();
+    let source = "
+        let name = 'box';
+        let shape = { name, size: { w: 2, h: 3 } };
+        shape.size.w = shape.size.w * shape.size.h;
+        shape.name + ':' + shape.size.w";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("box:6".into()));
+}
+
+#[test]
+fn test_objects_are_shared_by_reference() {
+    let mut engine = Engine::new();
+    let source = "
+        function bump(o) { o.hits = o.hits + 1; }
+        let counter = { hits: 0 };
+        let alias = counter;
+        bump(counter);
+        bump(alias);
+        counter.hits";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(2.0));
+    
+    let source = "let a = {}; let b = a; let c = {}; (a === b) && !(a === c)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn test_object_in_closure() {
+    let mut engine = Engine::new();
+    let source = "
+        function makeStore() {
+            let state = { total: 0 };
+            function add(n) { state.total += n; return state.total; }
+            return add;
+        }
+        let add = makeStore();
+        add(2);
+        add(5)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(7.0));
+}
+
+#[test]
+fn test_property_of_undefined_is_error() {
+    let mut engine = Engine::new();
+    assert!(engine.execute("let o = {}; o.a.b").is_err());
+    assert!(engine.execute("let n = null; n.x = 1").is_err());
+}
+
+#[test]
+fn test_predefined_globals() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("undefined").unwrap(), Value::Undefined);
+    assert_eq!(engine.execute("let o = {}; o.missing === undefined").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("NaN === NaN").unwrap(), Value::Boolean(false));
+    assert_eq!(engine.execute("Infinity > 1e308").unwrap(), Value::Boolean(true));
+    assert_eq!(engine.execute("'' + -Infinity").unwrap(), Value::String("-Infinity".into()));
+    
+    // They cannot be assigned, but functions can still shadow them
+    assert!(engine.execute("undefined = 1").is_err());
+    assert!(engine.execute("NaN = 1").is_err());
+    assert_eq!(engine.execute("undefined").unwrap(), Value::Undefined);
+    assert_eq!(engine.execute("(function (undefined) { return undefined; })(5)").unwrap(), Value::Number(5.0));
+}
+
+#[test]
+fn test_object_display() {
+    let mut engine = Engine::new();
+    let result = engine.execute("({ a: 1, b: 'two', c: { d: null } })").unwrap();
+    assert_eq!(result.to_string(), "{ a: 1, b: 'two', c: { d: null } }");
+    
+    let result = engine.execute("'' + {}").unwrap();
+    assert_eq!(result, Value::String("[object Object]".into()));
+}
+
+#[test]
+fn test_array_literal_and_indexing() {
+    let mut engine = Engine::new();
+    let source = "
+        let a = [10, 20, 30];
+        a[1] = a[0] + a[2];
+        a[1] + a.length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(43.0));
+    
+    assert_eq!(engine.execute("let a = [1]; a[5]").unwrap(), Value::Undefined);
+    assert_eq!(engine.execute("let a = []; a[2] = 'x'; a.length").unwrap(), Value::Number(3.0));
+    assert_eq!(engine.execute("let a = [1, 2, 3]; a.length = 1; a").unwrap().to_string(), "[ 1 ]");
+}
+
+#[test]
+fn test_array_large_indices_and_invalid_length() {
+    let mut engine = Engine::new();
+    let source = "let a = []; a[1e9] = 1; a.length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(1e9 + 1.0));
+    assert_eq!(engine.execute("a[1e9] + a.indexOf(1)").unwrap(), Value::Number(1e9 + 1.0));
+    assert_eq!(engine.execute("a").unwrap().to_string(), "[ <1000000000 empty items>, 1 ]");
+    
+    let source = "let b = [1, 2]; b.length = 4294967294; b.push(3) + b.pop() + b.length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(4294967295.0 + 3.0 + 4294967294.0));
+    let err = engine.execute("b.length = 4294967295; b.push(4)").unwrap_err();
+    assert!(matches!(err, Error::RuntimeError(RuntimeError::InvalidArrayLength { .. })));
+    assert_eq!(engine.execute("b.length").unwrap(), Value::Number(4294967295.0));
+    
+    // Joining writes the commas for runs of holes at once, up to the longest string
+    let source = "let c = []; c[3000] = 'x'; c[5] = 1; let joined = c + ''; joined.length + ':' + joined[5]";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("3002:1".into()));
+    let source = "let msg = ''; try { msg = a + ''; } catch (e) { msg = '' + e; } msg";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("RangeError: Invalid string length".into()));
+    assert_eq!(engine.execute("a == 1").unwrap(), Value::Boolean(false));
+    
+    // Lengths must be integers in 0..2^32
+    for bad in ["-1", "1.5", "4294967296", "'x'"] {
+        let err = engine.execute(&format!("b.length = {}", bad)).unwrap_err();
+        assert_eq!(err, Error::RuntimeError(RuntimeError::InvalidArrayLength { span: Some(Span::new(0, 11 + bad.len())) }));
+    }
+    let source = "let msg = ''; try { b.length = -1; } catch (e) { msg = e.name + ': ' + e.message; } msg";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("RangeError: Invalid array length".into()));
+}
+
+#[test]
+fn test_array_methods() {
+    let mut engine = Engine::new();
+    let source = "
+        let stack = [];
+        stack.push(1);
+        let len = stack.push(2, 3);
+        let top = stack.pop();
+        len * 10 + top";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(33.0));
+    
+    let source = "let a = [1, 2, 3, 4, 5]; a.slice(1, -1)";
+    assert_eq!(engine.execute(source).unwrap().to_string(), "[ 2, 3, 4 ]");
+    let source = "let a = [1, 2, 3]; let b = a.slice(); b.push(4); a.length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(3.0));
+    
+    let source = "let a = ['x', 2, 'y', 2]; a.indexOf(2) * 10 + a.indexOf(2, 2)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(13.0));
+    assert_eq!(engine.execute("[1, 2].indexOf('1')").unwrap(), Value::Number(-1.0));
+    assert_eq!(engine.execute("[].pop()").unwrap(), Value::Undefined);
+    assert!(engine.execute("[].nope()").is_err());
+    
+    // An array can contain and search for itself
+    let source = "let list = []; list.push(1, list); list.indexOf(list) * 10 + list.slice(1).indexOf(list)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(10.0));
+    assert_eq!(engine.execute("[{}].indexOf({})").unwrap(), Value::Number(-1.0));
+}
+
+#[test]
+fn test_array_bubble_sort() {
+    let mut engine = Engine::new();
+    let source = "
+        function sort(a) {
+            for (let i = 0; i < a.length; i++) {
+                for (let j = 0; j < a.length - 1 - i; j++) {
+                    if (a[j] > a[j + 1]) {
+                        let t = a[j];
+                        a[j] = a[j + 1];
+                        a[j + 1] = t;
+                    }
+                }
+            }
+            return a;
+        }
+        sort([5, 3, 8, 1, 9, 2])";
+    assert_eq!(engine.execute(source).unwrap().to_string(), "[ 1, 2, 3, 5, 8, 9 ]");
+}
+
+#[test]
+fn test_matrix_multiply() {
+    let mut engine = Engine::new();
+    let source = "
+        function multiply(a, b) {
+            let result = [];
+            for (let i = 0; i < a.length; i++) {
+                let row = [];
+                for (let j = 0; j < b[0].length; j++) {
+                    let sum = 0;
+                    for (let k = 0; k < b.length; k++) {
+                        sum += a[i][k] * b[k][j];
+                    }
+                    row.push(sum);
+                }
+                result.push(row);
+            }
+            return result;
+        }
+        multiply([[1, 2], [3, 4]], [[5, 6], [7, 8]])";
+    assert_eq!(engine.execute(source).unwrap().to_string(), "[ [ 19, 22 ], [ 43, 50 ] ]");
+}
+
+#[test]
+fn test_array_conversions() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("'' + [1, [2, 3], null]").unwrap(), Value::String("1,2,3,".into()));
+    assert_eq!(engine.execute("let a = [1, 2]; a[0]++; a[1] += 5; a[0] * a[1]").unwrap(), Value::Number(14.0));
+    assert_eq!(engine.execute("let s = 'hi'; s[1] + s.length").unwrap(), Value::String("i2".into()));
+}
+
+#[test]
+fn test_collect_garbage_reclaims_cycles() {
+    let mut engine = Engine::new();
+    let source = "
+        function link() {
+            let a = { name: 'a' };
+            let b = { name: 'b', peer: a };
+            a.peer = b;
+            let self = [];
+            self.push(self);
+            return 0;
+        }
+        link()";
+    engine.execute(source).unwrap();
+    
+    let before = engine.heap_stats();
+    assert_eq!(before.objects, 2);
+    assert_eq!(before.arrays, 1);
+    
+    assert_eq!(engine.collect_garbage(), 3);
+    let after = engine.heap_stats();
+    assert_eq!(after.objects + after.arrays, 0);
+    assert_eq!(after.collections, 1);
+    assert!(after.bytes < before.bytes);
+}
+
+#[test]
+fn test_collect_garbage_keeps_returned_values() {
+    let mut engine = Engine::new();
+    let source = "
+        function make() {
+            let node = { value: 42 };
+            node.self = node;
+            function get() { return node.value; }
+            node.get = get;
+            return node;
+        }
+        make()";
+    let node = engine.execute(source).unwrap();
+    
+    // The embedder's handle keeps the whole cycle alive
+    assert_eq!(engine.collect_garbage(), 0);
+    assert_eq!(node.to_string(), "{ value: 42, self: [Circular], get: [Function: 2] }");
+    
+    drop(node);
+    assert_eq!(engine.collect_garbage(), 3);
+    // Only the global `make` function is left
+    assert_eq!(engine.heap_stats().live(), 1);
+}
+
+#[test]
+fn test_automatic_collection_bounds_heap() {
+    let mut engine = Engine::new();
+    let source = "
+        let total = 0;
+        for (let i = 0; i < 20000; i++) {
+            let o = { i };
+            o.self = o;
+            total += o.i;
+        }
+        total";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(199990000.0));
+    
+    let stats = engine.heap_stats();
+    assert!(stats.collections > 0);
+    assert!(stats.freed > 0);
+    assert!(stats.objects < 20000);
+}
+
+#[test]
+fn test_const_declaration() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("const limit = 3; limit * 2").unwrap(), Value::Number(6.0));
+    
+    let err = engine.execute("const limit = 3; limit = 4;").unwrap_err();
+    assert!(err.to_string().contains("Assignment to constant variable 'limit'"));
+    assert!(engine.execute("const c = 1; c += 1").is_err());
+    assert!(engine.execute("const c = 1; c++").is_err());
+    assert!(engine.execute("const c = 1; function f() { c = 2; } f()").is_err());
+    assert!(engine.execute("const c;").is_err());
+    
+    // The object a const refers to can still change
+    assert_eq!(engine.execute("const o = { n: 1 }; o.n = 2; o.n").unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_var_hoisting() {
+    let mut engine = Engine::new();
+    let source = "
+        function f(flag) {
+            if (flag) {
+                var result = 'set';
+            }
+            return result;
+        }
+        f(false)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Undefined);
+    
+    let source = "
+        function count() {
+            total = 5;
+            for (var i = 0; i < 3; i++) { var total = total + 1; }
+            return total + i;
+        }
+        count()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(11.0));
+    
+    assert_eq!(engine.execute("var x = 1; var x; x").unwrap(), Value::Number(1.0));
+    assert_eq!(engine.execute("function f(a) { var a; return a; } f(7)").unwrap(), Value::Number(7.0));
+}
+
+#[test]
+fn test_function_hoisting() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("let r = twice(4); function twice(n) { return n * 2; } r").unwrap(), Value::Number(8.0));
+    
+    let source = "
+        function outer() {
+            return helper();
+            function helper() { return 'hoisted'; }
+        }
+        outer()";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("hoisted".into()));
+}
+
+#[test]
+fn test_temporal_dead_zone() {
+    let mut engine = Engine::new();
+    let err = engine.execute("let y = x; let x = 1;").unwrap_err();
+    assert!(err.to_string().contains("Cannot access 'x' before initialization"));
+    
+    assert!(engine.execute("x = 2; let x = 1;").is_err());
+    assert!(engine.execute("function f() { return v; } f(); const v = 1;").is_err());
+    assert!(engine.execute("function g() { let z = z + 1; return z; } g()").is_err());
+    
+    // Calling the closure after initialisation is fine
+    let source = "
+        function read() { return value; }
+        let value = 'ready';
+        read()";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("ready".into()));
+    
+    // Only lexical declarations have a dead zone
+    assert_eq!(engine.execute("let before = late; var late = 1; before").unwrap(), Value::Undefined);
+}
+
+#[test]
+fn test_block_scoping() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("let x = 'outer'; { let x = 'inner'; } x").unwrap(), Value::String("outer".into()));
+    assert_eq!(engine.execute("let x = 1; if (true) { let x = 2; x = x + 10; } x").unwrap(), Value::Number(1.0));
+    
+    let source = "
+        function f() {
+            let total = 0;
+            for (let i = 0; i < 3; i++) {
+                let total = i;
+            }
+            { let i = 100; total = total + i; }
+            return total;
+        }
+        f()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(100.0));
+    
+    // A shadowing binding has its own dead zone
+    let err = engine.execute("let s = 1; { s; let s = 2; }").unwrap_err();
+    assert!(err.to_string().contains("Cannot access 's' before initialization"));
+    
+    // Inner functions see the binding of the block they were declared in
+    let source = "
+        let name = 'outer';
+        let get;
+        { let name = 'inner'; function read() { return name; } get = read; }
+        get() + ' ' + name";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("inner outer".into()));
+}
+
+#[test]
+fn test_per_iteration_let_bindings() {
+    let mut engine = Engine::new();
+    let source = "
+        let fns = [];
+        for (let i = 0; i < 3; i++) {
+            function get() { return i; }
+            fns.push(get);
+        }
+        fns[0]() + fns[1]() * 10 + fns[2]() * 100";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(210.0));
+    
+    // Bindings declared in a loop body are fresh each time round, too
+    let source = "
+        function collect() {
+            let fns = [];
+            let n = 0;
+            while (n < 3) {
+                let doubled = n * 2;
+                function get() { return doubled; }
+                fns.push(get);
+                n++;
+            }
+            return fns[0]() + fns[1]() + fns[2]();
+        }
+        collect()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(6.0));
+    
+    // Updates inside the body carry over to the next iteration's copy
+    let source = "
+        let seen = [];
+        for (let i = 0; i < 6; i++) {
+            function peek() { return i; }
+            i++;
+            seen.push(peek());
+        }
+        seen";
+    assert_eq!(engine.execute(source).unwrap().to_string(), "[ 1, 3, 5 ]");
+}
+
+#[test]
+fn test_globals_persist_across_executions() {
+    let mut engine = Engine::new();
+    engine.execute("let x = 1;").unwrap();
+    assert_eq!(engine.execute("x").unwrap(), Value::Number(1.0));
+    
+    engine.execute("x = x + 1; var y = 'kept'; const z = [x];").unwrap();
+    assert_eq!(engine.execute("x").unwrap(), Value::Number(2.0));
+    assert_eq!(engine.execute("y").unwrap(), Value::String("kept".into()));
+    assert_eq!(engine.execute("z").unwrap().to_string(), "[ 2 ]");
+    assert!(engine.execute("z = 1").is_err());
+    
+    // Functions declared by one script can be called, and see globals, from the next
+    engine.execute("function bump(n) { x += n; return x; }").unwrap();
+    assert_eq!(engine.execute("bump(10)").unwrap(), Value::Number(12.0));
+    assert_eq!(engine.execute("x").unwrap(), Value::Number(12.0));
+    
+    // A later `var` redeclaration keeps the value; block-scoped variables do not leak
+    assert_eq!(engine.execute("var y; y").unwrap(), Value::String("kept".into()));
+    engine.execute("{ let hidden = 1; }").unwrap();
+    assert_eq!(engine.execute("var fresh; fresh").unwrap(), Value::Undefined);
+    
+    // A failed script keeps the declarations it made before failing
+    assert!(engine.execute("let before = 'set'; let o = null; o.prop").is_err());
+    assert_eq!(engine.execute("before").unwrap(), Value::String("set".into()));
+}
+
+#[test]
+fn test_reference_error_for_unresolved_names() {
+    let mut engine = Engine::new();
+    let err = engine.execute("let a = 1;\na + missing").unwrap_err();
+    assert_eq!(err, Error::RuntimeError(RuntimeError::ReferenceError {
+        name: "missing".to_string(),
+        span: Span::new(15, 22),
+    }));
+    assert!(err.to_string().contains("missing is not defined"));
+    
+    assert!(engine.execute("undeclared = 5").is_err());
+    assert!(engine.execute("function f() { return nowhere; } f()").is_err());
+    
+    // Functions see globals declared by later scripts
+    engine.execute("function later() { return value * 2; }").unwrap();
+    assert!(engine.execute("later()").is_err());
+    engine.execute("let value = 21;").unwrap();
+    assert_eq!(engine.execute("later()").unwrap(), Value::Number(42.0));
+}
+
+#[test]
+fn test_early_reference_errors() {
+    let mut engine = Engine::new();
+    engine.set_early_errors(true);
+    
+    // Nothing runs when the script refers to an unknown name
+    let err = engine.execute("let ran = true; function f() { return ghost; }").unwrap_err();
+    assert_eq!(err, Error::CompileError(CompileError::UnresolvedReference {
+        name: "ghost".to_string(),
+        span: Span::new(38, 43),
+    }));
+    assert!(engine.execute("ran").is_err());
+    
+    engine.execute("let ran = 1;").unwrap();
+    assert_eq!(engine.execute("ran + 1").unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_numeric_literals() {
+    let mut engine = Engine::new();
+    assert_eq!(engine.execute("1e3 + .5").unwrap(), Value::Number(1000.5));
+    assert_eq!(engine.execute("0xff + 0o10 + 0b11").unwrap(), Value::Number(266.0));
+    assert_eq!(engine.execute("1_000 * 2.5e-1").unwrap(), Value::Number(250.0));
+    assert_eq!(engine.execute("function half() { return.5 } half()").unwrap(), Value::Number(0.5));
+    
+    // Malformed literals are errors, not a wrong value
+    assert!(matches!(engine.execute("1.2.3"), Err(Error::ParseError(_))));
+    let err = engine.execute("let n = 1__0;").unwrap_err();
+    assert!(err.to_string().contains("Numeric separators"));
+    assert!(engine.execute("0x").is_err());
+}
+
+#[test]
+fn test_comments() {
+    let mut engine = Engine::new();
+    let source = "
+        // Sum the first few numbers
+        let total = 0; /* running total */
+        for (let i = 1; i <= 4; i++) {
+            total += i; // i / 2 would be a division
+        }
+        /*
+         * The result
+         */
+        total / 2";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(5.0));
+    
+    let err = engine.execute("let x = 1; /* oops").unwrap_err();
+    assert!(err.to_string().contains("Unterminated block comment"));
+}
+
+#[test]
+fn test_error_snippets() {
+    let source = "let a = 1;\nlet b = a +;\n";
+    let map = SourceMap::new("script.js", source);
+    let err = Engine::new().execute(source).unwrap_err();
+    let rendered = map.render_error(&err);
+    assert!(rendered.starts_with("Parse error: "));
+    assert!(rendered.contains(" --> script.js:2:12\n"));
+    assert!(rendered.ends_with("2 | let b = a +;\n  |            ^\n"));
+    
+    let source = "function f() {\n    return ghost;\n}";
+    let map = SourceMap::new("script.js", source);
+    let mut engine = Engine::new();
+    engine.set_early_errors(true);
+    let rendered = map.render_error(&engine.execute(source).unwrap_err());
+    assert_eq!(
+        rendered,
+        "Compile error: 'ghost' is not defined\n --> script.js:2:12\n  |\n2 |     return ghost;\n  |            ^^^^^\n"
+    );
+    
+    let source = "let x = 1;\nx + missing";
+    let map = SourceMap::new("script.js", source);
+    let rendered = map.render_error(&Engine::new().execute(source).unwrap_err());
+    assert!(rendered.starts_with("Runtime error: ReferenceError: missing is not defined\n"));
+    assert!(rendered.ends_with("2 | x + missing\n  |     ^^^^^^^\n"));
+}
+
+#[test]
+fn test_parse_error_recovery() {
+    let source = "let a = ;\nlet b = 2;\nif (b { b = 3; }\nb *= ;";
+    let (ast, errors) = Parser::new(source.to_string()).parse_with_recovery();
+    let lines: Vec<usize> = errors.iter()
+        .map(|err| SourceMap::new("script.js", source).line_col(err.span().unwrap().start).line)
+        .collect();
+    assert_eq!(lines, [1, 3, 4]);
+    
+    // The partial AST cannot be run
+    let mut generator = BytecodeGenerator::new(Scope::global());
+    generator.generate(&ast.root);
+    assert!(!generator.errors().is_empty());
+    
+    // Plain parsing still stops at the first error
+    let err = Engine::new().execute(source).unwrap_err();
+    assert_eq!(err, Error::ParseError(errors[0].clone()));
+}
+
+#[test]
+fn test_automatic_semicolon_insertion() {
+    let mut engine = Engine::new();
+    
+    // `return` followed by a line break returns undefined
+    let source = "function f() {\n    return\n    42;\n}\nf()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Undefined);
+    
+    // A line starting with `(` or `[` continues the previous expression
+    let source = "function id(x) { return x; }\nlet called = id\n(7)\ncalled";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(7.0));
+    let source = "let arr = [10, 20]\nlet second = arr\n[1]\nsecond";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(20.0));
+    
+    // `++` on its own line applies to the following operand
+    let source = "let p = 1\nlet q = 1\np\n++\nq\np * 10 + q";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(12.0));
+    
+    // Statements on one line still need a semicolon
+    assert!(matches!(engine.execute("let r = 1 let s = 2"), Err(Error::ParseError(_))));
+    assert!(matches!(engine.execute("let t = 1\nt t"), Err(Error::ParseError(_))));
+    
+    let source = "let n = 0\ndo n = n + 1; while (n < 3) n";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(3.0));
+}
+
+#[test]
+fn test_template_literals() {
+    let mut engine = Engine::new();
+    // Without the `;` the template would be a tagged call on `2`
+    let source = "let who = 'world'\nlet n = 2;\n`hello ${who}, ${n * 3} times${''}\nover ${[1, 2]} ${ {a: 1}.a }`";
+    assert_eq!(
+        engine.execute(source).unwrap(),
+        Value::String("hello world, 6 times\nover 1,2 1".into())
+    );
+    assert_eq!(engine.execute("`outer ${`inner ${n}`}`").unwrap(), Value::String("outer inner 2".into()));
+    assert_eq!(engine.execute("`${n}${n}`").unwrap(), Value::String("22".into()));
+}
+
+#[test]
+fn test_tagged_templates() {
+    let mut engine = Engine::new();
+    let source = "function tag(strings, a, b) {\n    return strings.length + '|' + strings[0] + '|' + strings.raw[1] + '|' + (a + b)\n}\ntag`x\\n${1}\\t${2}`";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("3|x\n|\\t|3".into()));
+    
+    // Invalid escapes leave the cooked string undefined
+    let source = "function cooked(s) { return s[0] === undefined }\ncooked`\\unicode`";
+    assert_eq!(engine.execute(source).unwrap(), Value::Boolean(true));
+    
+    // A method tag is called with its object as the receiver
+    let source = "let o = { parts: [] }\no.parts.push`a${1}`\no.parts.length";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_try_catch_finally() {
+    let mut engine = Engine::new();
+    let source = r#"
+        let log = [];
+        try {
+            log.push("try");
+            throw "boom";
+            log.push("unreachable");
+        } catch (e) {
+            log.push("catch " + e);
+        } finally {
+            log.push("finally");
+        }
+        "" + log
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("try,catch boom,finally".into()));
+    
+    // An exception escaping the catch clause still runs the finally block
+    let source = r#"
+        let steps = [];
+        try {
+            try { throw 1; } catch (e) { throw e + 1; } finally { steps.push("inner"); }
+        } catch (e) {
+            steps.push(e);
+        }
+        "" + steps
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("inner,2".into()));
+}
+
+#[test]
+fn test_finally_runs_on_break_and_return() {
+    let mut engine = Engine::new();
+    let source = r#"
+        let log = [];
+        function f() {
+            try {
+                return "value";
+            } finally {
+                log.push("f");
+            }
+        }
+        for (let i = 0; i < 3; i++) {
+            try {
+                if (i == 1) { continue; }
+                if (i == 2) { break; }
+                log.push(i);
+            } finally {
+                log.push("loop " + i);
+            }
+        }
+        log.push(f());
+        "" + log
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("0,loop 0,loop 1,loop 2,f,value".into()));
+    
+    // A return in the finally block overrides the pending exception
+    let source = "function g() { try { throw 1; } finally { return 2; } } g()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_break_and_continue_out_of_finally() {
+    let mut engine = Engine::new();
+    // Leaving a finally block discards the exception it was running for
+    let source = r#"for (let i = 0; i < 1; i++) { try { throw "leaked"; } finally { break; } }"#;
+    assert_eq!(engine.execute(source).unwrap(), Value::Undefined);
+    let source = "for (let i = 0; i < 3; i++) { try { throw i; } finally { continue; } }";
+    assert_eq!(engine.execute(source).unwrap(), Value::Undefined);
+    
+    let source = r#"
+        function count() {
+            let log = [];
+            outer: for (let i = 0; i < 3; i++) {
+                for (let j = 0; j < 3; j++) {
+                    try {
+                        if (j == 1) { throw "skip"; }
+                        log.push(i + ":" + j);
+                    } finally {
+                        if (j == 0) { continue; }
+                        if (i == 1) { break outer; }
+                        continue outer;
+                    }
+                }
+            }
+            return log;
+        }
+        "" + count() + "|" + (count().length + 1)
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("0:0,1:0|3".into()));
+    
+    // The normal path has no pending exception to discard
+    let source = r#"
+        let log = [];
+        for (let i = 0; i < 3; i++) {
+            try { log.push(i); } finally { if (i == 0) { continue; } break; }
+        }
+        "" + log
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("0,1".into()));
+}
+
+#[test]
+fn test_exceptions_unwind_call_frames() {
+    let mut engine = Engine::new();
+    let source = r#"
+        function inner(n) { if (n == 0) { throw { name: "Done", message: "at bottom" }; } return inner(n - 1); }
+        function outer() { try { return inner(5); } catch (e) { return e.name + ": " + e.message; } }
+        outer()
+    "#;
+    assert_eq!(engine.execute(source).unwrap(), Value::String("Done: at bottom".into()));
+}
+
+#[test]
+fn test_internal_errors_are_catchable() {
+    let mut engine = Engine::new();
+    // A try statement has no completion value here, so the result is stored
+    let source = "let n = null; let caught; try { n.x; } catch (e) { caught = e.name; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("TypeError".into()));
+    
+    let source = "try { missing; } catch (e) { caught = e.name + ': ' + e.message; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("ReferenceError: missing is not defined".into()));
+    
+    let source = "function r() { return r(); } try { r(); } catch (e) { caught = e.message; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("Maximum call stack size exceeded".into()));
+    
+    // Engine errors convert to strings like JS errors; plain thrown objects do not
+    let source = "try { null.x; } catch (e) { caught = '' + e; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("TypeError: expected object, found null".into()));
+    let source = "try { throw { name: 'Oops', message: 'bad' }; } catch (e) { caught = '' + e; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("[object Object]".into()));
+    
+    // The engine stays usable after an uncaught exception
+    let err = engine.execute("throw { name: 'Oops', message: 'bad' };").unwrap_err();
+    assert_eq!(err.to_string(), "Runtime error: Uncaught Oops: bad at 0:39");
+    assert!(matches!(err, Error::RuntimeError(RuntimeError::Thrown { .. })));
+    assert_eq!(engine.execute("1 + 1").unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_runtime_error_stack_trace() {
+    let mut engine = Engine::new();
+    let source = "function check(v) {\n  return v.length;\n}\nfunction run() {\n  return check(null);\n}\nrun();";
+    assert!(engine.execute_named("app.js", source).is_err());
+    
+    let trace = engine.stack_trace();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace[0], StackFrame {
+        function: Some("check".to_string()),
+        file: "app.js".to_string(),
+        line: 2,
+        column: 10,
+    });
+    assert_eq!(trace[1].to_string(), "at run (app.js:5:10)");
+    assert_eq!(trace[2].to_string(), "at app.js:7:1");
+    
+    // Caught errors leave no trace
+    assert!(engine.execute("try { run(); } catch (e) {}").is_ok());
+    assert!(engine.stack_trace().is_empty());
+}
+
+#[test]
+fn test_runtime_errors_carry_spans() {
+    let mut engine = Engine::new();
+    let source = "let o = null;\nlet n = o.value;";
+    let err = engine.execute(source).unwrap_err();
+    assert_eq!(err.span(), Some(Span::new(22, 29)));
+    
+    let rendered = SourceMap::new("main.js", source).render_error(&err);
+    assert!(rendered.contains(" --> main.js:2:9\n"));
+    assert!(rendered.ends_with("2 | let n = o.value;\n  |         ^^^^^^^\n"));
+    
+    // Errors raised inside a function point into its body
+    let err = engine.execute("function f(x) { return 1 / x; } f(0)").unwrap_err();
+    assert_eq!(err, Error::RuntimeError(RuntimeError::DivisionByZero { span: Some(Span::new(23, 28)) }));
+}
+
+#[test]
+fn test_function_expressions_and_arrows() {
+    let mut engine = Engine::new();
+    
+    // Immediately invoked, stored and passed around like any other value
+    assert_eq!(engine.execute("(function (a, b) { return a * b; })(6, 7)").unwrap(), Value::Number(42.0));
+    assert_eq!(engine.execute("let twice = (f, x) => f(f(x)); twice(x => x + 3, 1)").unwrap(), Value::Number(7.0));
+    assert_eq!(engine.execute("let ops = { add: (a, b) => a + b }; ops.add(2, 3)").unwrap(), Value::Number(5.0));
+    assert_eq!(engine.execute("(() => { let n = 4; return n * n; })()").unwrap(), Value::Number(16.0));
+    
+    // A named function expression can call itself, but its name stays local
+    let source = "let fact = function f(n) { if (n <= 1) { return 1; } return n * f(n - 1); }; fact(5)";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(120.0));
+    assert!(engine.execute("f").is_err());
+    
+    // Closures created by expressions keep their captured state
+    let source = "let make = function () { let c = 0; return () => { c = c + 1; return c; }; };
+        let a = make(); let b = make(); a(); a(); a() * 10 + b()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(31.0));
+}
+
+#[test]
+fn test_anonymous_functions_in_stack_traces() {
+    let mut engine = Engine::new();
+    let source = "let check = (v) => v.length;\n(function () {\n  return check(null);\n})();";
+    assert!(engine.execute_named("app.js", source).is_err());
+    
+    // Arrows take the name of their variable; other frames without a name print as `<anonymous>`
+    let trace: Vec<String> = engine.stack_trace().iter().map(|frame| frame.to_string()).collect();
+    assert_eq!(trace, ["at check (app.js:1:20)", "at <anonymous> (app.js:3:10)", "at app.js:2:2"]);
+}
+
+#[test]
+fn test_default_parameters() {
+    let mut engine = Engine::new();
+    engine.execute("function greet(name = \"world\", punct = \"!\") { return \"hi \" + name + punct; }").unwrap();
+    assert_eq!(engine.execute("greet()").unwrap(), Value::String("hi world!".into()));
+    assert_eq!(engine.execute("greet(\"bob\", \"?\")").unwrap(), Value::String("hi bob?".into()));
+    
+    // Only a missing or undefined argument takes the default, not null
+    assert_eq!(engine.execute("greet(undefined, \".\")").unwrap(), Value::String("hi world.".into()));
+    assert_eq!(engine.execute("greet(null)").unwrap(), Value::String("hi null!".into()));
+    
+    // Defaults see earlier parameters, and run on every call
+    let source = "let calls = 0;
+        function f(a, b = a * 2, c = () => { calls = calls + 1; return a + b; }) { return c(); }
+        f(3) + f(3, 4) * 100 + calls * 10000";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(20709.0));
+}
+
+#[test]
+fn test_rest_parameters_and_spread_arguments() {
+    let mut engine = Engine::new();
+    engine.execute("function sum(first, ...rest) {
+        let total = first;
+        for (let i = 0; i < rest.length; i++) { total = total + rest[i]; }
+        return total;
+    }").unwrap();
+    assert_eq!(engine.execute("sum(1)").unwrap(), Value::Number(1.0));
+    assert_eq!(engine.execute("sum(1, 2, 3, 4)").unwrap(), Value::Number(10.0));
+    
+    // Spreads can be mixed with plain arguments in any order
+    assert_eq!(engine.execute("let nums = [5, 6, 7]; sum(...nums)").unwrap(), Value::Number(18.0));
+    assert_eq!(engine.execute("sum(1, ...nums, 10, ...[100])").unwrap(), Value::Number(129.0));
+    assert_eq!(engine.execute("let count = (...xs) => xs.length; count(...\"abcd\", ...[])").unwrap(), Value::Number(4.0));
+    assert_eq!(engine.execute("let o = { mul: (a, b) => a * b }; o.mul(...[6, 7])").unwrap(), Value::Number(42.0));
+    assert_eq!(engine.execute("let a = [1]; a.push(...[2, 3]); \"\" + a").unwrap(), Value::String("1,2,3".into()));
+    
+    // Only arrays and strings can be spread
+    let err = engine.execute("sum(...5)").unwrap_err();
+    assert!(err.to_string().contains("expected iterable, found number"));
+    
+    // Holes spread as undefined, but a call cannot take more arguments than the limit
+    assert_eq!(engine.execute("let gaps = [1]; gaps[2] = 3; count(...gaps, ...[])").unwrap(), Value::Number(3.0));
+    let source = "let big = []; big[1e9] = 1; let caught; try { count(...big); } catch (e) { caught = '' + e; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("RangeError: Too many arguments in function call".into()));
+    let source = "big.length = 4294967295; try { count(1, ...big); } catch (e) { caught = e.name; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("RangeError".into()));
+    let source = "let many = []; many[65535] = 0; try { count(...many); } catch (e) { caught = e.name + '!'; } caught";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("RangeError!".into()));
+}
+
+#[test]
+fn test_arguments_object() {
+    let mut engine = Engine::new();
+    engine.execute("function args() { return arguments.length + \":\" + arguments[0]; }").unwrap();
+    assert_eq!(engine.execute("args(9, 8, 7)").unwrap(), Value::String("3:9".into()));
+    assert_eq!(engine.execute("args()").unwrap(), Value::String("0:undefined".into()));
+    
+    // Arrow functions use the arguments of the function around them
+    let source = "function outer() { let second = () => arguments[1]; return second(\"x\"); } outer(\"a\", \"b\")";
+    assert_eq!(engine.execute(source).unwrap(), Value::String("b".into()));
+    
+    // Closures keep the arguments alive alongside the parameters
+    let source = "function keep(x, ...r) { return () => x + r.length * 10 + arguments.length * 100; } keep(1, 2, 3)()";
+    assert_eq!(engine.execute(source).unwrap(), Value::Number(321.0));
+    
+    // A parameter named `arguments` shadows the object, and scripts have none
+    assert_eq!(engine.execute("function shadow(arguments) { return arguments; } shadow(5)").unwrap(), Value::Number(5.0));
+    assert!(engine.execute("arguments").is_err());
+}
@@ -4,9 +4,10 @@
 use v8_rs::{
     Engine, Value, Error, ParseError, RuntimeError, CompileError,
     Lexer, ASTNode, BinOp, Parser,
-    Scope, ScopeType, Instruction, BytecodeChunk,
-    BytecodeGenerator, Ignition, Span,
+    Scope, ScopeType, Instruction, BytecodeChunk, PositionTable,
+    BytecodeGenerator, Ignition, Span, Closure,
 };
+use std::rc::Rc;
 
 /// Test that all core modules are accessible
 #[test]
@@ -20,15 +21,13 @@ fn test_module_structure() {
     
     // Test error module
     let _parse_err = ParseError::UnexpectedEOF;
-    let _runtime_err = RuntimeError::DivisionByZero;
+    let _runtime_err = RuntimeError::DivisionByZero { span: None };
     let _compile_err = CompileError::UnsupportedFeature {
         feature: "test".to_string(),
     };
     
     // Test that Error enum can hold all error types
     let _err: Error = ParseError::UnexpectedEOF.into();
-    
-    assert!(true); // All modules accessible
 }
 
 /// Test that the Engine can be instantiated
@@ -37,7 +36,6 @@ fn test_engine_instantiation() {
     let engine = Engine::new();
     // Engine should be created without panicking
     drop(engine);
-    assert!(true);
 }
 
 /// Test that the Engine default implementation works
@@ -45,7 +43,6 @@ fn test_engine_instantiation() {
 fn test_engine_default() {
     let engine = Engine::default();
     drop(engine);
-    assert!(true);
 }
 
 /// Test that Parser can be instantiated
@@ -53,7 +50,6 @@ fn test_engine_default() {
 fn test_parser_instantiation() {
     let parser = Parser::new("42".to_string());
     drop(parser);
-    assert!(true);
 }
 
 /// Test that Lexer can be instantiated
@@ -61,7 +57,6 @@ fn test_parser_instantiation() {
 fn test_lexer_instantiation() {
     let lexer = Lexer::new("42".to_string());
     drop(lexer);
-    assert!(true);
 }
 
 /// Test that Scope can be created
@@ -77,8 +72,6 @@ fn test_scope_creation() {
     let global2 = Scope::global();
     let block_scope = global2.block_scope();
     drop(block_scope);
-    
-    assert!(true);
 }
 
 /// Test that BytecodeGenerator can be instantiated
@@ -87,7 +80,6 @@ fn test_bytecode_generator_instantiation() {
     let scope = Scope::global();
     let generator = BytecodeGenerator::new(scope);
     drop(generator);
-    assert!(true);
 }
 
 /// Test that Ignition interpreter can be instantiated
@@ -95,7 +87,6 @@ fn test_bytecode_generator_instantiation() {
 fn test_interpreter_instantiation() {
     let interpreter = Ignition::new();
     drop(interpreter);
-    assert!(true);
 }
 
 /// Test that BytecodeChunk can be created
@@ -105,9 +96,10 @@ fn test_bytecode_chunk_creation() {
         instructions: vec![],
         constants: vec![],
         local_count: 0,
+        handlers: vec![],
+        positions: PositionTable::new(),
     };
     drop(chunk);
-    assert!(true);
 }
 
 /// Test that all Instruction variants can be created
@@ -134,7 +126,7 @@ fn test_instruction_variants() {
 #[test]
 fn test_value_variants() {
     let num = Value::Number(42.0);
-    let func = Value::Function(0);
+    let func = Value::Function(Rc::new(Closure::new(0, Vec::new())));
     let undef = Value::Undefined;
     
     assert!(matches!(num, Value::Number(_)));
@@ -146,7 +138,7 @@ fn test_value_variants() {
 #[test]
 fn test_error_variants() {
     let parse_err = Error::ParseError(ParseError::UnexpectedEOF);
-    let runtime_err = Error::RuntimeError(RuntimeError::DivisionByZero);
+    let runtime_err = Error::RuntimeError(RuntimeError::DivisionByZero { span: None });
     let compile_err = Error::CompileError(CompileError::UnsupportedFeature {
         feature: "test".to_string(),
     });
@@ -170,14 +162,12 @@ fn test_ast_node_variants() {
         right: Box::new(ASTNode::NumberLiteral { value: 2.0, span }),
         span,
     };
-    
-    assert!(true);
 }
 
 /// Test that BinOp variants exist
 #[test]
 fn test_binop_variants() {
-    let ops = vec![BinOp::Add, BinOp::Sub, BinOp::Mul, BinOp::Div];
+    let ops = [BinOp::Add, BinOp::Sub, BinOp::Mul, BinOp::Div];
     assert_eq!(ops.len(), 4);
 }
 
@@ -187,7 +177,6 @@ fn test_scope_type_variants() {
     let _global = ScopeType::Global;
     let _function = ScopeType::Function;
     let _block = ScopeType::Block;
-    assert!(true);
 }
 
 /// Test that Span utility methods work
@@ -217,7 +206,6 @@ fn test_quickcheck_dependency() {
     }
     
     quickcheck(prop_identity as fn(i32) -> bool);
-    assert!(true);
 }
 
 /// Test that the engine can execute a minimal program
@@ -261,5 +249,4 @@ fn test_multiple_engines() {
 fn test_project_compilation() {
     // If this test runs, it means the project compiled successfully
     // with all dependencies and modules properly linked
-    assert!(true);
 }